@@ -0,0 +1,62 @@
+//! Compares computing the events one at a time with the APIs sharing
+//! the computations common to several events, days or positions.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{ black_box, criterion_group, criterion_main, Criterion };
+use circadia::almanac::year_table;
+use circadia::batch::event_results_for_positions;
+use circadia::epoch::{ event_result_unix, event_results_unix };
+use circadia::{ EventResult, GlobalPosition, SunEvent };
+
+/// 2024-06-21, in days since the Unix epoch.
+const DAY: i64 = 19_895;
+
+fn day_of_events(c: &mut Criterion) {
+    let pos = GlobalPosition::at(51.4810066, 0.0081805);
+    let events = SunEvent::all();
+    let mut group = c.benchmark_group("every event of a day");
+    group.bench_function("one at a time", |b| b.iter(|| {
+        events.iter()
+            .map(|event| event_result_unix(black_box(DAY), &pos, *event))
+            .collect::<Vec<_>>()
+    }));
+    group.bench_function("shared", |b| b.iter(|| event_results_unix(black_box(DAY), &pos, &events)));
+    group.finish();
+}
+
+fn year_of_events(c: &mut Criterion) {
+    let pos = GlobalPosition::at(-33.8688, 151.2093);
+    let events = [SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::DUSK];
+    let first = 19_723;
+    let mut group = c.benchmark_group("four events through a year");
+    group.bench_function("one at a time", |b| b.iter(|| {
+        (first..first + 366)
+            .flat_map(|day| events.iter().map(move |event| (day, *event)))
+            .map(|(day, event)| event_result_unix(black_box(day), &pos, event))
+            .collect::<Vec<_>>()
+    }));
+    group.bench_function("year table", |b| b.iter(|| year_table(black_box(2024), &pos, &events)));
+    group.finish();
+}
+
+fn grid_of_positions(c: &mut Criterion) {
+    let positions: Vec<_> = (0..1024)
+        .map(|i| GlobalPosition::at((i % 128) as f64 - 64.0, (i / 128) as f64 * 45.0 - 180.0))
+        .collect();
+    let mut group = c.benchmark_group("sunset at 1024 positions");
+    group.bench_function("one at a time", |b| b.iter(|| {
+        positions.iter()
+            .map(|pos| event_result_unix(black_box(DAY), pos, SunEvent::SUNSET))
+            .collect::<Vec<_>>()
+    }));
+    group.bench_function("batch", |b| b.iter(|| {
+        let mut results = vec![EventResult::PolarNight; positions.len()];
+        event_results_for_positions(black_box(DAY), &positions, SunEvent::SUNSET, &mut results);
+        results
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, day_of_events, year_of_events, grid_of_positions);
+criterion_main!(benches);
@@ -0,0 +1,150 @@
+//! Python bindings for circadia through PyO3.
+//!
+//! Dates and times cross over as `datetime.date` and timezone-aware
+//! `datetime.datetime` objects in UTC, and events as their names, such
+//! as "civil sunrise" or "solar noon".
+
+// The code generated by `#[pyfunction]` converts the returned errors
+// into `PyErr` even when they already are.
+#![allow(clippy::useless_conversion)]
+
+use chrono::{ DateTime, NaiveDate, TimeZone, Utc };
+use ::circadia::epoch::time_of_event_unix;
+use ::circadia::{ GlobalPosition, SunEvent, SunEvents };
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// A position on the globe, exported to Python as `GlobalPosition`.
+#[pyclass(name = "GlobalPosition", frozen)]
+#[derive(Debug, Clone)]
+struct Position(GlobalPosition);
+
+#[pymethods]
+impl Position {
+
+    /// Creates the position at the latitude `lat` and the longitude
+    /// `lng`, in degrees, raising ValueError when it doesn't lie on
+    /// the globe.
+    #[new]
+    #[pyo3(signature = (lat, lng, elevation = 0.0))]
+    fn new(lat: f64, lng: f64, elevation: f64) -> PyResult<Self> {
+        let pos = GlobalPosition::try_at(lat, lng).map_err(value_error)?;
+        Ok(Position(pos.with_elevation(elevation)))
+    }
+
+    /// Parses a position written in decimal degrees or in degrees,
+    /// minutes and seconds, such as "51°28'52\"N 0°0'29\"E".
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<Self> {
+        text.parse().map(Position).map_err(value_error)
+    }
+
+    /// The latitude, in degrees.
+    #[getter]
+    fn lat(&self) -> f64 {
+        self.0.lat()
+    }
+
+    /// The longitude, in degrees.
+    #[getter]
+    fn lng(&self) -> f64 {
+        self.0.lng()
+    }
+
+    /// The elevation above sea level, in meters.
+    #[getter]
+    fn elevation(&self) -> f64 {
+        self.0.elevation()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("GlobalPosition({}, {})", self.0.lat(), self.0.lng())
+    }
+
+}
+
+/// An endless iterator over the sun events following or preceding a
+/// time, yielding `(name, datetime)` tuples.
+#[pyclass]
+struct EventIterator {
+    events: Box<dyn Iterator<Item = (SunEvent, DateTime<Utc>)> + Send>
+}
+
+#[pymethods]
+impl EventIterator {
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<(String, DateTime<Utc>)> {
+        self.events.next().map(|(event, time)| (event.to_string(), time))
+    }
+
+}
+
+/// Calculates the time of the event named `event` on the UTC `date` at
+/// `pos`, or None if the sun never sets/rises on that day (ie if
+/// you're in the arctic).
+#[pyfunction]
+fn time_of_event(date: NaiveDate, pos: &Position, event: &str) -> PyResult<Option<DateTime<Utc>>> {
+    let event: SunEvent = event.parse().map_err(value_error)?;
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let day = date.signed_duration_since(epoch).num_days();
+    Ok(time_of_event_unix(day, &pos.0, event).and_then(|secs| Utc.timestamp_opt(secs, 0).single()))
+}
+
+/// Iterates over the events named in `events` occurring after `start`
+/// at `pos`, every event when `events` is left out.
+#[pyfunction]
+#[pyo3(signature = (start, pos, events = None))]
+fn forecast(start: DateTime<Utc>, pos: &Position, events: Option<Vec<String>>) -> PyResult<EventIterator> {
+    let events = sun_events(start, pos, events)?.forecast();
+    Ok(EventIterator { events: Box::new(events) })
+}
+
+/// Iterates over the events named in `events` occurring before `start`
+/// at `pos`, latest first, every event when `events` is left out.
+#[pyfunction]
+#[pyo3(signature = (start, pos, events = None))]
+fn history(start: DateTime<Utc>, pos: &Position, events: Option<Vec<String>>) -> PyResult<EventIterator> {
+    let events = sun_events(start, pos, events)?.history();
+    Ok(EventIterator { events: Box::new(events) })
+}
+
+/// The names of every event, in their order through a day.
+#[pyfunction]
+fn all_events() -> Vec<String> {
+    SunEvent::all().iter().map(|event| event.to_string()).collect()
+}
+
+fn sun_events(start: DateTime<Utc>, pos: &Position, events: Option<Vec<String>>) -> PyResult<SunEvents> {
+    let events = match events {
+        Some(names) => names.iter()
+            .map(|name| name.parse())
+            .collect::<Result<Vec<SunEvent>, _>>()
+            .map_err(value_error)?,
+        None => SunEvent::all()
+    };
+    SunEvents::try_starting_from(start, pos.0.clone(), &events).map_err(value_error)
+}
+
+fn value_error<E: ToString>(err: E) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+#[pymodule]
+#[pyo3(name = "circadia")]
+fn py_circadia(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Position>()?;
+    m.add_class::<EventIterator>()?;
+    m.add_function(wrap_pyfunction!(time_of_event, m)?)?;
+    m.add_function(wrap_pyfunction!(forecast, m)?)?;
+    m.add_function(wrap_pyfunction!(history, m)?)?;
+    m.add_function(wrap_pyfunction!(all_events, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+from datetime import date, datetime, timezone
+from itertools import islice
+
+import pytest
+
+import circadia
+
+GREENWICH = circadia.GlobalPosition(51.4810066, 0.0081805)
+
+
+def test_time_of_event_should_fall_on_the_date():
+    sunrise = circadia.time_of_event(date(2024, 6, 21), GREENWICH, "sunrise")
+    assert sunrise.date() == date(2024, 6, 21)
+    assert sunrise.tzinfo is not None
+
+
+def test_time_of_event_should_be_none_in_the_polar_day():
+    svalbard = circadia.GlobalPosition(78.2232, 15.6267)
+    assert circadia.time_of_event(date(2024, 6, 21), svalbard, "sunset") is None
+
+
+def test_forecast_should_alternate_sunrises_and_sunsets():
+    start = datetime(2024, 6, 21, tzinfo=timezone.utc)
+    events = list(islice(circadia.forecast(start, GREENWICH, ["sunrise", "sunset"]), 4))
+    assert [name for name, _ in events] == ["sunrise", "sunset"] * 2
+    assert all(start < time for _, time in events)
+
+
+def test_invalid_input_should_raise_value_error():
+    with pytest.raises(ValueError):
+        circadia.GlobalPosition(100.0, 0.0)
+    with pytest.raises(ValueError):
+        circadia.forecast(datetime.now(timezone.utc), GREENWICH, ["moonrise"])
@@ -0,0 +1,239 @@
+//! uniffi bindings for circadia, so that Kotlin and Swift apps can
+//! compute the sun events on the device.
+//!
+//! Generate the bindings from the built library with
+//! `cargo run --features bindgen --bin uniffi-bindgen -- generate --library <path to the library> --language kotlin --out-dir out`
+//! (or `--language swift`).
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+use chrono::{ DateTime, NaiveDate, TimeZone, Utc };
+
+uniffi::setup_scaffolding!();
+
+/// A position on the globe, in degrees, `elevation` meters above sea
+/// level.
+#[derive(Debug, Clone, Copy, PartialEq, uniffi::Record)]
+pub struct Position {
+    pub lat: f64,
+    pub lng: f64,
+    pub elevation: f64
+}
+
+/// The angle of the sun at which a sunrise or sunset occurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Zenith {
+    Golden,
+    Official,
+    BlueHour,
+    Civil,
+    Nautical,
+    Astronomical
+}
+
+/// The sunrise, the sunset, or the transits of the sun across the
+/// meridian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Event {
+    SolarMidnight,
+    Sunrise,
+    SolarNoon,
+    Sunset
+}
+
+/// A sunrise or sunset at some zenith, or the solar noon or midnight,
+/// whose zenith is ignored and always given as `Official`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct SunEvent {
+    pub zenith: Zenith,
+    pub event: Event
+}
+
+/// A sun event and the time it occurs at.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Occurrence {
+    pub event: SunEvent,
+    pub time: SystemTime
+}
+
+/// The errors of the bindings.
+#[derive(Debug, Clone, PartialEq, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CircadiaError {
+    /// The latitude or longitude doesn't lie on the globe.
+    InvalidPosition,
+    /// The date doesn't exist.
+    InvalidDate,
+    /// The date or time falls outside of the supported years.
+    DateOutOfRange,
+    /// No events were given to list.
+    NoEvents
+}
+
+impl fmt::Display for CircadiaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircadiaError::InvalidPosition => write!(f, "the position doesn't lie on the globe"),
+            CircadiaError::InvalidDate => write!(f, "the date doesn't exist"),
+            CircadiaError::DateOutOfRange => write!(f, "the date falls outside of the supported years"),
+            CircadiaError::NoEvents => write!(f, "no events were given"),
+        }
+    }
+}
+
+impl std::error::Error for CircadiaError {}
+
+/// Calculates the time of `event` on the UTC date `year`-`month`-`day`
+/// at `position`, or null if the sun never sets/rises on that day (ie
+/// if you're in the arctic).
+#[uniffi::export]
+pub fn time_of_event(year: i32, month: u32, day: u32, position: Position, event: SunEvent) -> Result<Option<SystemTime>, CircadiaError> {
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or(CircadiaError::InvalidDate)?;
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    let day = date.signed_duration_since(epoch).num_days();
+    let time = circadia::epoch::try_time_of_event_unix(day, &position.to_circadia()?, event.into())
+        .map_err(|_| CircadiaError::DateOutOfRange)?;
+    Ok(time.map(system_time))
+}
+
+/// The first occurrence of any of `events` after `after` at `position`,
+/// such as the time of the next sunrise alarm.
+#[uniffi::export]
+pub fn next_event(position: Position, events: Vec<SunEvent>, after: SystemTime) -> Result<Option<Occurrence>, CircadiaError> {
+    Ok(EventForecast::new(position, events, after)?.next_events(1).pop())
+}
+
+/// Lists the events following a time, a page at a time.
+#[derive(uniffi::Object)]
+pub struct EventForecast {
+    events: Mutex<circadia::ForecastedSunEvents>
+}
+
+#[uniffi::export]
+impl EventForecast {
+
+    /// Lists `events` occurring after `start` at `position`.
+    #[uniffi::constructor]
+    pub fn new(position: Position, events: Vec<SunEvent>, start: SystemTime) -> Result<Self, CircadiaError> {
+        let events: Vec<circadia::SunEvent> = events.into_iter().map(Into::into).collect();
+        let start = date_time(start)?;
+        let events = circadia::SunEvents::try_starting_from(start, position.to_circadia()?, &events)
+            .map_err(|_| CircadiaError::NoEvents)?
+            .forecast();
+        Ok(EventForecast { events: Mutex::new(events) })
+    }
+
+    /// Returns the next `count` events.
+    pub fn next_events(&self, count: u32) -> Vec<Occurrence> {
+        let mut events = self.events.lock().unwrap();
+        events.by_ref()
+            .take(count as usize)
+            .map(|(event, time)| Occurrence { event: event.into(), time: system_time(time.timestamp()) })
+            .collect()
+    }
+
+}
+
+impl Position {
+
+    fn to_circadia(self) -> Result<circadia::GlobalPosition, CircadiaError> {
+        circadia::GlobalPosition::try_at(self.lat, self.lng)
+            .map(|pos| pos.with_elevation(self.elevation))
+            .map_err(|_| CircadiaError::InvalidPosition)
+    }
+
+}
+
+impl From<SunEvent> for circadia::SunEvent {
+    fn from(event: SunEvent) -> Self {
+        let zenith = match event.zenith {
+            Zenith::Golden => circadia::Zenith::Golden,
+            Zenith::Official => circadia::Zenith::Official,
+            Zenith::BlueHour => circadia::Zenith::BlueHour,
+            Zenith::Civil => circadia::Zenith::Civil,
+            Zenith::Nautical => circadia::Zenith::Nautical,
+            Zenith::Astronomical => circadia::Zenith::Astronomical
+        };
+        let event = match event.event {
+            Event::SolarMidnight => circadia::Event::SolarMidnight,
+            Event::Sunrise => circadia::Event::Sunrise,
+            Event::SolarNoon => circadia::Event::SolarNoon,
+            Event::Sunset => circadia::Event::Sunset
+        };
+        circadia::SunEvent::new(zenith, event)
+    }
+}
+
+impl From<circadia::SunEvent> for SunEvent {
+    fn from(event: circadia::SunEvent) -> Self {
+        let event = circadia::SunEvent::new(event.zenith, event.event);
+        let zenith = match event.zenith {
+            circadia::Zenith::Golden => Zenith::Golden,
+            circadia::Zenith::Official => Zenith::Official,
+            circadia::Zenith::BlueHour => Zenith::BlueHour,
+            circadia::Zenith::Civil => Zenith::Civil,
+            circadia::Zenith::Nautical => Zenith::Nautical,
+            circadia::Zenith::Astronomical => Zenith::Astronomical
+        };
+        let event = match event.event {
+            circadia::Event::SolarMidnight => Event::SolarMidnight,
+            circadia::Event::Sunrise => Event::Sunrise,
+            circadia::Event::SolarNoon => Event::SolarNoon,
+            circadia::Event::Sunset => Event::Sunset,
+            event => panic!("the {} event has no binding", event)
+        };
+        SunEvent { zenith, event }
+    }
+}
+
+fn system_time(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+fn date_time(time: SystemTime) -> Result<DateTime<Utc>, CircadiaError> {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64)
+    };
+    Utc.timestamp_opt(secs, 0).single().ok_or(CircadiaError::DateOutOfRange)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    const GREENWICH: Position = Position { lat: 51.4810066, lng: 0.0081805, elevation: 0.0 };
+    const SUNRISE: SunEvent = SunEvent { zenith: Zenith::Official, event: Event::Sunrise };
+    const SUNSET: SunEvent = SunEvent { zenith: Zenith::Official, event: Event::Sunset };
+
+    #[test]
+    fn times_should_match_the_library() {
+        let time = time_of_event(2024, 6, 21, GREENWICH, SUNRISE).unwrap();
+        let expected = circadia::epoch::time_of_event_unix(19_895, &GREENWICH.to_circadia().unwrap(), circadia::SunEvent::SUNRISE);
+        assert_eq!(time, expected.map(system_time));
+        assert_eq!(time_of_event(2024, 2, 30, GREENWICH, SUNRISE), Err(CircadiaError::InvalidDate));
+        assert_eq!(time_of_event(1800, 6, 21, GREENWICH, SUNRISE), Err(CircadiaError::DateOutOfRange));
+        let position = Position { lat: 91.0, ..GREENWICH };
+        assert_eq!(time_of_event(2024, 6, 21, position, SUNRISE), Err(CircadiaError::InvalidPosition));
+    }
+
+    #[test]
+    fn forecasts_should_page_through_the_events() {
+        let start = system_time(1_718_928_000);
+        let forecast = EventForecast::new(GREENWICH, vec![SUNRISE, SUNSET], start).unwrap();
+        let mut events = forecast.next_events(2);
+        events.extend(forecast.next_events(2));
+        let names: Vec<_> = events.iter().map(|occurrence| occurrence.event.event).collect();
+        assert_eq!(names, vec![Event::Sunrise, Event::Sunset, Event::Sunrise, Event::Sunset]);
+        assert!(events.windows(2).all(|pair| pair[0].time < pair[1].time));
+        assert_eq!(next_event(GREENWICH, vec![SUNSET], start).unwrap().map(|occurrence| occurrence.time), Some(events[1].time));
+        assert!(EventForecast::new(GREENWICH, vec![], start).is_err());
+    }
+
+}
@@ -1,6 +1,9 @@
 use circadia::{ GlobalPosition, SunEvent, time_of_event };
 use chrono::Utc;
 
+// `chrono::Date` is deprecated upstream but remains the date type
+// of circadia's public API.
+#[allow(deprecated)]
 fn main() {
     // Nauticalia Greenwhich
     let pos = GlobalPosition::at(51.4810066, 0.0081805);
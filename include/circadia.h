@@ -0,0 +1,143 @@
+#ifndef CIRCADIA_H
+#define CIRCADIA_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The outcome of [circadia_time_of_event].
+typedef enum CircadiaStatus {
+  // The event occurs, at the time written out.
+  CircadiaStatusOk,
+  // The sun stays above the zenith of the event all day.
+  CircadiaStatusPolarDay,
+  // The sun stays below the zenith of the event all day.
+  CircadiaStatusPolarNight,
+  // The date falls outside of the supported years.
+  CircadiaStatusDateOutOfRange,
+  // A pointer is null or the date doesn't exist.
+  CircadiaStatusInvalidArgument,
+} CircadiaStatus;
+
+// The angle of the sun at which a sunrise or sunset occurs, as
+// [Zenith].
+typedef enum CircadiaZenith {
+  CircadiaZenithGolden,
+  CircadiaZenithOfficial,
+  CircadiaZenithBlueHour,
+  CircadiaZenithCivil,
+  CircadiaZenithNautical,
+  CircadiaZenithAstronomical,
+} CircadiaZenith;
+
+// The crossing of the sun, as [Event].
+typedef enum CircadiaEvent {
+  CircadiaEventSolarMidnight,
+  CircadiaEventSunrise,
+  CircadiaEventSolarNoon,
+  CircadiaEventSunset,
+} CircadiaEvent;
+
+// An iterator over the sun events following or preceding a time.
+typedef struct CircadiaIterator CircadiaIterator;
+
+// A position on the globe.
+typedef struct CircadiaPosition CircadiaPosition;
+
+// A sun event, as [SunEvent]. The zenith of a transit is ignored,
+// and always given as [CircadiaZenith::CircadiaZenithOfficial].
+typedef struct CircadiaSunEvent {
+  enum CircadiaZenith zenith;
+  enum CircadiaEvent event;
+} CircadiaSunEvent;
+
+// A sun event and the time it occurs at.
+typedef struct CircadiaOccurrence {
+  struct CircadiaSunEvent event;
+  // The Unix timestamp of the event, in seconds.
+  int64_t time;
+} CircadiaOccurrence;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates the position at the latitude `lat` and the longitude `lng`,
+// in degrees, to be freed with [circadia_position_free], or returns
+// null when it doesn't lie on the globe.
+struct CircadiaPosition *circadia_position_new(double lat, double lng);
+
+// Places `pos` `meters` above sea level.
+//
+// # Safety
+// `pos` must be null or returned by [circadia_position_new] and not
+// yet freed.
+void circadia_position_set_elevation(struct CircadiaPosition *pos, double meters);
+
+// Frees `pos`.
+//
+// # Safety
+// `pos` must be null or returned by [circadia_position_new] and not
+// yet freed.
+void circadia_position_free(struct CircadiaPosition *pos);
+
+// Calculates the time of `event` on the UTC date `year`-`month`-`day`
+// at `pos`, writing it to `time` when the event occurs.
+//
+// # Safety
+// `pos` must be null or a live position, and `time` null or valid for
+// writes.
+enum CircadiaStatus circadia_time_of_event(const struct CircadiaPosition *pos,
+                                           int32_t year,
+                                           uint32_t month,
+                                           uint32_t day,
+                                           struct CircadiaSunEvent event,
+                                           int64_t *time);
+
+// Lists the `len` events of `events` occurring after the Unix
+// timestamp `start` at `pos`, to be freed with
+// [circadia_iterator_free], or returns null when the arguments are
+// invalid.
+//
+// # Safety
+// `pos` must be null or a live position, and `events` null or valid
+// for reads of `len` events.
+struct CircadiaIterator *circadia_forecast_new(const struct CircadiaPosition *pos,
+                                               int64_t start,
+                                               const struct CircadiaSunEvent *events,
+                                               uintptr_t len);
+
+// Like [circadia_forecast_new], for the events occurring before
+// `start`, latest first.
+//
+// # Safety
+// As for [circadia_forecast_new].
+struct CircadiaIterator *circadia_history_new(const struct CircadiaPosition *pos,
+                                              int64_t start,
+                                              const struct CircadiaSunEvent *events,
+                                              uintptr_t len);
+
+// Writes the next event of `iter` to `occurrence`, returning false
+// when there is none.
+//
+// # Safety
+// `iter` must be null or returned by [circadia_forecast_new] or
+// [circadia_history_new] and not yet freed, and `occurrence` null or
+// valid for writes.
+bool circadia_iterator_next(struct CircadiaIterator *iter, struct CircadiaOccurrence *occurrence);
+
+// Frees `iter`.
+//
+// # Safety
+// `iter` must be null or returned by [circadia_forecast_new] or
+// [circadia_history_new] and not yet freed.
+void circadia_iterator_free(struct CircadiaIterator *iter);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CIRCADIA_H */
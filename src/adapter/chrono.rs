@@ -0,0 +1,250 @@
+//! Adapts the epoch-based API to `chrono` types.
+
+// `chrono::Date` is deprecated upstream but remains the date type
+// of this crate's public API.
+#![allow(deprecated)]
+
+use crate::algorithm::SolarAlgorithm;
+use crate::batch::event_results_for_positions;
+use crate::core::EventResult;
+use crate::epoch::{ checked_time_of_event_unix, event_result_unix, julian_day_from_unix, time_at_altitude_unix, time_of_event_unix, try_time_of_event_unix, unix_from_julian_day };
+use crate::error::{ CircadiaError, DateOutOfRange };
+use crate::event::{ Crossing, SunEvent };
+use crate::pos::GlobalPosition;
+use chrono::{ Date, DateTime, Datelike, NaiveDate, TimeZone, Utc };
+
+/// The number of days from 0001-01-01 to the Unix epoch.
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+/// Calculates the time of the sunrise/sunset (or solar noon/midnight) on the
+/// given date at the given position on the globe.
+///
+/// This is an implementation of the algorithm described by the
+/// United states Naval Observatory
+/// found here: http://edwilliams.org/sunrise_sunset_algorithm.htm
+///
+/// Returns None if the sun never sets/rises on that day
+/// (ie if you're in the arctic).
+pub fn time_of_event(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Utc>> {
+    time_of_event_unix(unix_day(date.naive_utc()), pos, event)
+        .and_then(from_unix)
+}
+
+/// Like [time_of_event], at each of `positions`, in their order.
+///
+/// This works out the day of the year once for all the positions and
+/// computes them [LANES](crate::core::LANES) at a time, as in the
+/// [batch](crate::batch) module, which makes it faster than calling
+/// [time_of_event] for each position.
+pub fn events_for_positions(
+    date: Date<Utc>,
+    positions: &[GlobalPosition],
+    event: SunEvent,
+) -> Vec<Option<DateTime<Utc>>> {
+    let mut results = vec![EventResult::PolarNight; positions.len()];
+    event_results_for_positions(unix_day(date.naive_utc()), positions, event, &mut results);
+    results.into_iter()
+        .map(|result| result.at().and_then(from_unix))
+        .collect()
+}
+
+/// Like [time_of_event], computed with `algorithm`.
+pub fn time_of_event_with(
+    algorithm: &dyn SolarAlgorithm,
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Utc>> {
+    algorithm.event_result_unix(unix_day(date.naive_utc()), pos, event)
+        .at()
+        .and_then(from_unix)
+}
+
+/// Like [time_of_event], but tells whether the sun stays above
+/// ([EventResult::PolarDay]) or below ([EventResult::PolarNight]) the
+/// zenith of the event when it doesn't occur.
+///
+/// # Panics
+/// Panics when the event falls beyond the latest or earliest
+/// `DateTime` representable by `chrono`.
+pub fn event_result(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> EventResult<DateTime<Utc>> {
+    event_result_unix(unix_day(date.naive_utc()), pos, event)
+        .map(|secs| from_unix(secs).expect("the event should be representable"))
+}
+
+/// Like [time_of_event], but returns an error when `date` falls
+/// outside of the [supported years](crate::core::SUPPORTED_YEARS)
+/// instead of silently returning a degraded result.
+pub fn try_time_of_event(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Result<Option<DateTime<Utc>>, DateOutOfRange> {
+    try_time_of_event_unix(unix_day(date.naive_utc()), pos, event)
+        .map(|time| time.and_then(from_unix))
+}
+
+/// Like [time_of_event], but returns an error when `pos` doesn't lie
+/// on the globe, when `date` falls outside of the
+/// [supported years](crate::core::SUPPORTED_YEARS), or when the
+/// event doesn't happen that day.
+pub fn checked_time_of_event(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Result<DateTime<Utc>, CircadiaError> {
+    let secs = checked_time_of_event_unix(unix_day(date.naive_utc()), pos, event)?;
+    Ok(from_unix(secs).expect("a supported date should be representable"))
+}
+
+/// Calculates the time at which the center of the sun crosses
+/// `altitude` degrees above the horizon (negative below it), on its
+/// way up or down, on the local mean `date` at `pos`.
+///
+/// The altitude is not corrected for atmospheric refraction, which
+/// lifts the sun by about half a degree at the horizon.
+///
+/// Returns None if the sun stays above or below that altitude all day.
+pub fn time_at_altitude(
+    date: NaiveDate,
+    pos: &GlobalPosition,
+    altitude: f64,
+    crossing: Crossing,
+) -> Option<DateTime<Utc>> {
+    time_at_altitude_unix(unix_day(date), pos, altitude, crossing)
+        .and_then(from_unix)
+}
+
+/// Calculates the time of the sunrise/sunset (or solar noon/midnight)
+/// falling on the calendar `date` of the timezone `tz`, at the given
+/// position on the globe, in that timezone.
+///
+/// This takes care of the UTC day on which the event falls, which may
+/// be the day before or after `date` far from the meridian of `tz` or
+/// across the date line.
+///
+/// Returns None if the sun never sets/rises on that date
+/// (ie if you're in the arctic).
+pub fn time_of_event_on<Tz: TimeZone>(
+    date: NaiveDate,
+    tz: &Tz,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Tz>> {
+    let day = unix_day(date);
+    (day - 1..=day + 1)
+        .filter_map(|day| time_of_event_unix(day, pos, event))
+        .filter_map(from_unix)
+        .map(|time| time.with_timezone(tz))
+        .find(|time| time.date_naive() == date)
+}
+
+/// Returns the number of days since the Unix epoch of `date`.
+pub(crate) fn unix_day(date: NaiveDate) -> i64 {
+    date.num_days_from_ce() as i64 - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+/// Returns the dates of `year`, from January 1st.
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub(crate) fn days_of_year(year: i32) -> impl Iterator<Item = NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).expect("year should be representable");
+    first.iter_days().take_while(move |date| date.year() == year)
+}
+
+/// Converts a `DateTime<Utc>` to a Julian day.
+pub(crate) fn julian_day(time: DateTime<Utc>) -> f64 {
+    julian_day_from_unix(time.timestamp_millis() as f64 / 1000.0)
+}
+
+/// Converts a Julian day to a `DateTime<Utc>`, to the millisecond.
+pub(crate) fn from_julian_day(jd: f64) -> Option<DateTime<Utc>> {
+    let millis = (unix_from_julian_day(jd) * 1000.0).round() as i64;
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Converts a Unix timestamp (in seconds) to a `DateTime<Utc>`.
+pub(crate) fn from_unix(secs: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn events_for_positions_should_match_each_position() {
+        let positions: Vec<_> = (-8..=8)
+            .map(|i| GlobalPosition::at(i as f64 * 10.0, i as f64 * -20.0))
+            .collect();
+        let date = Utc.with_ymd_and_hms(2024, 6, 21, 0, 0, 0).unwrap().date();
+        let times = events_for_positions(date, &positions, SunEvent::SUNSET);
+        assert_eq!(times.len(), positions.len());
+        for (pos, time) in positions.iter().zip(&times) {
+            assert_eq!(*time, time_of_event(date, pos, SunEvent::SUNSET));
+        }
+        assert_eq!(times[16], None);
+    }
+
+    #[test]
+    fn event_result_should_tell_polar_day_from_polar_night() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let summer = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap().date();
+        let winter = Utc.with_ymd_and_hms(2019, 12, 21, 0, 0, 0).unwrap().date();
+        assert_eq!(event_result(summer, &pos, SunEvent::SUNRISE), EventResult::PolarDay);
+        assert_eq!(event_result(winter, &pos, SunEvent::SUNSET), EventResult::PolarNight);
+        let spring = Utc.with_ymd_and_hms(2019, 3, 21, 0, 0, 0).unwrap().date();
+        let sunrise = time_of_event(spring, &pos, SunEvent::SUNRISE).unwrap();
+        assert_eq!(event_result(spring, &pos, SunEvent::SUNRISE), EventResult::At(sunrise));
+    }
+
+    #[test]
+    fn altitude_crossing_should_generalize_zenith_events() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let day = unix_day(date);
+        let dawn = time_of_event_unix(day, &pos, SunEvent::DAWN).and_then(from_unix);
+        assert_eq!(time_at_altitude(date, &pos, -6.0, Crossing::Rising), dawn);
+        let rising = time_at_altitude(date, &pos, 10.0, Crossing::Rising).unwrap();
+        let setting = time_at_altitude(date, &pos, 10.0, Crossing::Setting).unwrap();
+        assert!(rising < setting);
+        let altitude = crate::solar::sun_position(&pos, rising).altitude;
+        assert!((altitude - 10.0).abs() < 0.2, "{}", altitude);
+        assert_eq!(time_at_altitude(date, &pos, 85.0, Crossing::Rising), None);
+    }
+
+    #[test]
+    fn local_event_should_fall_on_the_local_date() {
+        // Sydney, whose sunsets fall on the previous UTC day.
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let tz = FixedOffset::east_opt(10 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        for event in &[SunEvent::SUNRISE, SunEvent::SUNSET] {
+            let time = time_of_event_on(date, &tz, &pos, *event).unwrap();
+            assert_eq!(time.date_naive(), date);
+        }
+    }
+
+    #[test]
+    fn local_event_should_cross_the_date_line() {
+        // Kiritimati keeps the date of UTC+14 at a longitude of UTC-10.
+        let pos = GlobalPosition::at(1.8721, -157.4278);
+        let tz = FixedOffset::east_opt(14 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2022, 7, 1).unwrap();
+        let sunrise = time_of_event_on(date, &tz, &pos, SunEvent::SUNRISE).unwrap();
+        let sunset = time_of_event_on(date, &tz, &pos, SunEvent::SUNSET).unwrap();
+        assert_eq!((sunrise.date_naive(), sunset.date_naive()), (date, date));
+        assert!(sunrise < sunset);
+        assert_eq!(sunrise.with_timezone(&Utc).date_naive(), date.pred_opt().unwrap());
+    }
+
+}
@@ -0,0 +1,7 @@
+//! Thin adapters converting between the epoch-based core API
+//! and the types of external time libraries.
+
+#[cfg(feature = "chrono")]
+pub(crate) mod chrono;
+#[cfg(feature = "time")]
+pub mod time;
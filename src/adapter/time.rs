@@ -0,0 +1,110 @@
+//! Adapts the epoch-based API to the types of the `time` crate, as an
+//! alternative to `chrono`.
+
+use ::time::{ Date, OffsetDateTime, UtcOffset };
+use crate::algorithm::SolarAlgorithm;
+use crate::core::EventResult;
+use crate::epoch::{ checked_time_of_event_unix, event_result_unix, time_of_event_unix };
+use crate::error::CircadiaError;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// The Julian day number of the Unix epoch (1970-01-01).
+const UNIX_EPOCH_JULIAN_DAY: i64 = 2_440_588;
+
+/// Calculates the time of the sunrise/sunset (or solar noon/midnight) on the
+/// given UTC date at the given position on the globe, as the root
+/// `time_of_event` does for `chrono`.
+///
+/// Returns None if the sun never sets/rises on that day
+/// (ie if you're in the arctic).
+pub fn time_of_event(date: Date, pos: &GlobalPosition, event: SunEvent) -> Option<OffsetDateTime> {
+    time_of_event_unix(unix_day(date), pos, event).and_then(from_unix)
+}
+
+/// Like [time_of_event], computed with `algorithm`.
+pub fn time_of_event_with(
+    algorithm: &dyn SolarAlgorithm,
+    date: Date,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<OffsetDateTime> {
+    algorithm.event_result_unix(unix_day(date), pos, event)
+        .at()
+        .and_then(from_unix)
+}
+
+/// Like [time_of_event], but tells whether the sun stays above
+/// ([EventResult::PolarDay]) or below ([EventResult::PolarNight]) the
+/// zenith of the event when it doesn't occur.
+///
+/// # Panics
+/// Panics when the event falls beyond the latest or earliest
+/// `OffsetDateTime` representable by `time`.
+pub fn event_result(date: Date, pos: &GlobalPosition, event: SunEvent) -> EventResult<OffsetDateTime> {
+    event_result_unix(unix_day(date), pos, event)
+        .map(|secs| from_unix(secs).expect("the event should be representable"))
+}
+
+/// Like [time_of_event], but returns an error when `pos` doesn't lie
+/// on the globe, when `date` falls outside of the
+/// [supported years](crate::core::SUPPORTED_YEARS), or when the
+/// event doesn't happen that day.
+pub fn checked_time_of_event(date: Date, pos: &GlobalPosition, event: SunEvent) -> Result<OffsetDateTime, CircadiaError> {
+    let secs = checked_time_of_event_unix(unix_day(date), pos, event)?;
+    Ok(from_unix(secs).expect("a supported date should be representable"))
+}
+
+/// Calculates the time of the sunrise/sunset (or solar noon/midnight)
+/// falling on the calendar `date` at the UTC `offset`, at the given
+/// position on the globe, at that offset.
+///
+/// Returns None if the sun never sets/rises on that date
+/// (ie if you're in the arctic).
+pub fn time_of_event_on(
+    date: Date,
+    offset: UtcOffset,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<OffsetDateTime> {
+    let day = unix_day(date);
+    (day - 1..=day + 1)
+        .filter_map(|day| time_of_event_unix(day, pos, event))
+        .filter_map(from_unix)
+        .map(|time| time.to_offset(offset))
+        .find(|time| time.date() == date)
+}
+
+/// Returns the number of days since the Unix epoch of `date`.
+fn unix_day(date: Date) -> i64 {
+    date.to_julian_day() as i64 - UNIX_EPOCH_JULIAN_DAY
+}
+
+/// Converts a Unix timestamp to an `OffsetDateTime` in UTC.
+fn from_unix(secs: i64) -> Option<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp(secs).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::time::Month;
+
+    #[test]
+    fn events_should_match_the_epoch_api() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let date = Date::from_calendar_date(2020, Month::January, 1).unwrap();
+        assert_eq!(unix_day(date), 18_262);
+        for &event in &[SunEvent::SUNRISE, SunEvent::SUNSET] {
+            let time = time_of_event(date, &pos, event).unwrap();
+            assert_eq!(Some(time.unix_timestamp()), time_of_event_unix(18_262, &pos, event));
+            let offset = UtcOffset::from_hms(11, 0, 0).unwrap();
+            assert_eq!(time_of_event_on(date, offset, &pos, event).unwrap().date(), date);
+        }
+        let pole = GlobalPosition::at(78.2232, 15.6267);
+        let summer = Date::from_calendar_date(2019, Month::June, 21).unwrap();
+        assert_eq!(event_result(summer, &pole, SunEvent::SUNSET), EventResult::PolarDay);
+        assert_eq!(checked_time_of_event(summer, &pole, SunEvent::SUNSET), Err(CircadiaError::NoEvent(SunEvent::SUNSET)));
+    }
+
+}
@@ -1,103 +1,174 @@
-#![allow(non_snake_case)]
-
-use super::event::SunEvent;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use ::core::fmt;
+use ::core::ops::RangeInclusive;
+#[cfg(feature = "alloc")]
+use super::core::Approximation;
+use super::core::{ self, EventResult, SUPPORTED_YEARS };
+use super::epoch::{ self, civil_from_days };
+use super::error::{ check_year, DateOutOfRange };
+use super::event::{ Event, SunEvent, Zenith };
 use super::pos::GlobalPosition;
-use chrono::{ Date, DateTime, Utc, Datelike, NaiveTime };
-
-const SECS_IN_HOUR: i32 = 3600;
-
-/// Calculates the time of the sunrise/sunset on the given date
-/// at the given position on the globe.
-///
-/// This is an implementation of the algorithm described by the
-/// United states Naval Observatory
-/// found here: http://edwilliams.org/sunrise_sunset_algorithm.htm
-///
-/// Returns None if the sun never sets/rises on that day
-/// (ie if you're in the arctic).
-pub fn time_of_event(
-    mut date: Date<Utc>,
-    pos: &GlobalPosition,
-    event: SunEvent,
-) -> Option<DateTime<Utc>> {
-    let D = date.ordinal() as f64;
-    let t = approximate_time(D, event, pos);
-    let M = mean_anomaly(t);
-    let L = true_longitude(M);
-    let RA = right_ascension(L);
-    let H = match local_hour_angle(L, pos, event) {
-        Some(H) => H,
-        None => return None,
-    };
-    let T = local_mean_time(H, RA, t);
-    let UT = rem_euclid(T - pos.lng_hour(), 24.0);
-    let time = NaiveTime::from_num_seconds_from_midnight((UT * SECS_IN_HOUR as f64) as u32, 0);
-
-    let should_be_yesterday = pos.lng_hour() > 0.0 && UT > 12.0 && event.is_sunrise();
-    let should_be_tomorrow = pos.lng_hour() < 0.0 && UT < 12.0 && event.is_sunset();
-    if should_be_yesterday {
-        date = date.pred();
-    } else if should_be_tomorrow {
-        date = date.succ();
+
+/// A method of computing the time of the sun events, so that the
+/// iterators and the convenience functions can run on any of them.
+pub trait SolarAlgorithm: fmt::Debug + Send + Sync {
+
+    /// Calculates the time of `event` on the UTC day `day`, counted in
+    /// days since the Unix epoch, at `pos`, as a Unix timestamp (in
+    /// seconds), following [epoch::event_result_unix].
+    fn event_result_unix(&self, day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64>;
+
+    /// Like [SolarAlgorithm::event_result_unix], for several events of
+    /// the same day, in the order of `events`.
+    #[cfg(feature = "alloc")]
+    fn event_results_unix(&self, day: i64, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<EventResult<i64>> {
+        events.iter()
+            .map(|event| self.event_result_unix(day, pos, *event))
+            .collect()
+    }
+
+    /// The years over which the algorithm keeps its stated accuracy.
+    fn supported_years(&self) -> RangeInclusive<i32> {
+        SUPPORTED_YEARS
+    }
+
+    /// Like [SolarAlgorithm::event_result_unix], but returns an error
+    /// when `day` falls outside of the
+    /// [supported years](SolarAlgorithm::supported_years) instead of
+    /// silently returning a degraded result.
+    fn try_event_result_unix(&self, day: i64, pos: &GlobalPosition, event: SunEvent) -> Result<EventResult<i64>, DateOutOfRange> {
+        let (year, _, _) = civil_from_days(day);
+        check_year(year, self.supported_years())?;
+        Ok(self.event_result_unix(day, pos, event))
+    }
+
+}
+
+/// The approximation of the United States Naval Observatory, accurate
+/// to a minute or two, used by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usno {
+    /// Whether to evaluate the position of the sun again at the time
+    /// of each sunrise and sunset, as with
+    /// [refined_event_result](crate::core::refined_event_result).
+    pub refine: bool
+}
+
+impl SolarAlgorithm for Usno {
+
+    fn event_result_unix(&self, day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+        if self.refine {
+            epoch::refined_event_result_unix(day, pos, event)
+        } else {
+            epoch::event_result_unix(day, pos, event)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn event_results_unix(&self, day: i64, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<EventResult<i64>> {
+        if self.refine {
+            events.iter()
+                .map(|event| epoch::refined_event_result_unix(day, pos, *event))
+                .collect()
+        } else {
+            epoch::event_results_unix(day, pos, events)
+        }
     }
 
-    date.with_timezone(&Utc)
-        .and_time(time)
 }
 
-fn approximate_time(D: f64, event: SunEvent, pos: &GlobalPosition) -> f64 {
-    D + ((event.event.hour() - pos.lng_hour()) / 24.0)
+/// Calculates the time of the sunrise/sunset, in hours after
+/// midnight UT, on the given day of the year at the given position
+/// on the globe.
+pub(crate) fn ut_of_event(ordinal: u32, pos: &GlobalPosition, event: SunEvent) -> EventResult<f64> {
+    if event.is_transit() {
+        let upper = event.event == Event::SolarNoon;
+        return EventResult::At(core::ut_of_transit(ordinal, pos.lng_hour(), upper));
+    }
+    core::event_result(
+        ordinal,
+        pos.lat(),
+        pos.lng_hour(),
+        zenith_angle(pos, event.zenith),
+        event.is_sunrise(),
+    )
 }
 
-fn mean_anomaly(t: f64) -> f64 {
-    (0.9856 * t) - 3.289
+/// Like [ut_of_event], refining the sunrises and sunsets with
+/// [core::refined_event_result].
+pub(crate) fn refined_ut_of_event(ordinal: u32, pos: &GlobalPosition, event: SunEvent) -> EventResult<f64> {
+    if event.is_transit() {
+        return ut_of_event(ordinal, pos, event);
+    }
+    core::refined_event_result(
+        ordinal,
+        pos.lat(),
+        pos.lng_hour(),
+        zenith_angle(pos, event.zenith),
+        event.is_sunrise(),
+    )
 }
 
-fn true_longitude(M: f64) -> f64 {
-    let L =
-        M + (1.916 * M.to_radians().sin()) + (0.020 * (2.0 * M).to_radians().sin()) + 282.634;
-    rem_euclid(L, 360.0)
+/// Like [ut_of_event], for several events of the same day, sharing
+/// the approximations of the sunrises and of the sunsets.
+#[cfg(feature = "alloc")]
+pub(crate) fn ut_of_events(ordinal: u32, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<EventResult<f64>> {
+    let mut day = DayApproximations::new(ordinal, pos);
+    events.iter()
+        .map(|event| day.ut_of_event(*event))
+        .collect()
 }
 
-fn right_ascension(L: f64) -> f64 {
-    let mut RA = (0.91764 * L.to_radians().tan()).atan().to_degrees();
-    RA = rem_euclid(RA, 360.0);
-    let LQuadrant = (L / 90.0).floor() * 90.0;
-    let RAQuadrant = (RA / 90.0).floor() * 90.0;
-    (RA + (LQuadrant - RAQuadrant)) / 15.0
+/// The intermediates of the algorithm for one day at one position,
+/// computed the first time an event needs them and reused by the
+/// following events: the position of the sun at the approximate time
+/// of the sunrises, and at that of the sunsets, serves every zenith,
+/// and the trigonometry of the latitude every event.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub(crate) struct DayApproximations<'a> {
+    ordinal: u32,
+    pos: &'a GlobalPosition,
+    sin_lat: f64,
+    cos_lat: f64,
+    rising: Option<Approximation>,
+    setting: Option<Approximation>
 }
 
-fn local_hour_angle(L: f64, pos: &GlobalPosition, event: SunEvent) -> Option<f64> {
-    let sinDec = 0.39782 * L.to_radians().sin();
-    let cosDec = sinDec.asin().cos();
-    let z = event.zenith.angle().to_radians();
-    let cosH = (z.cos() - (sinDec * pos.lat().to_radians().sin()))
-        / (cosDec * pos.lat().to_radians().cos());
-    if cosH > 1.0 && event.is_sunrise() {
-        // The sun never rises on this location on the specified date.
-        return None;
+#[cfg(feature = "alloc")]
+impl<'a> DayApproximations<'a> {
+
+    pub(crate) fn new(ordinal: u32, pos: &'a GlobalPosition) -> Self {
+        let (sin_lat, cos_lat) = core::sin_cos(pos.lat());
+        DayApproximations { ordinal, pos, sin_lat, cos_lat, rising: None, setting: None }
     }
-    if cosH < -1.0 && event.is_sunset() {
-        // The sun never sets on this location on the specified date.
-        return None;
+
+    /// Like [ut_of_event], for the day and position of the
+    /// approximations.
+    pub(crate) fn ut_of_event(&mut self, event: SunEvent) -> EventResult<f64> {
+        if event.is_transit() {
+            return ut_of_event(self.ordinal, self.pos, event);
+        }
+        let (ordinal, pos) = (self.ordinal, self.pos);
+        let approximation = if event.is_sunrise() { &mut self.rising } else { &mut self.setting };
+        approximation
+            .get_or_insert_with(|| Approximation::new(ordinal, pos.lng_hour(), event.is_sunrise()))
+            .event_result_at(self.sin_lat, self.cos_lat, zenith_angle(pos, event.zenith))
     }
-    let H = if event.is_sunrise() {
-        360.0 - cosH.acos().to_degrees()
-    } else {
-        cosH.acos().to_degrees()
-    };
-    Some(H / 15.0)
-}
 
-fn local_mean_time(H: f64, RA: f64, t: f64) -> f64 {
-    H + RA - (0.06571 * t) - 6.622
 }
 
-fn rem_euclid(lhs: f64, rhs: f64) -> f64 {
-    let r = lhs % rhs;
-    if r < 0.0 {
-        r + rhs.abs()
-    } else {
-        r
+/// The angle of `zenith` from the vertical at `pos`, adjusting the
+/// horizon of sunrise and sunset for the weather and for its dip from
+/// the elevation of the observer. Twilights are measured from the
+/// true horizon.
+pub(crate) fn zenith_angle(pos: &GlobalPosition, zenith: Zenith) -> f64 {
+    match zenith {
+        Zenith::Official => 90.0 + SOLAR_SEMIDIAMETER + pos.atmosphere().horizon_refraction() + pos.horizon_dip(),
+        _ => zenith.angle()
     }
-}
\ No newline at end of file
+}
+
+/// The apparent radius of the sun, in degrees.
+const SOLAR_SEMIDIAMETER: f64 = 16.0 / 60.0;
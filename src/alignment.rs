@@ -0,0 +1,111 @@
+//! This module computes where along the horizon the sun rises and
+//! sets, and finds the days on which it does so in line with a given
+//! bearing, such as the axis of a street ("Manhattanhenge") or of
+//! a monument.
+
+use chrono::{ DateTime, NaiveDate, Utc };
+use crate::adapter::chrono::{ days_of_year, julian_day, time_of_event_on };
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+use crate::solar;
+
+/// A sunrise or sunset in line with some bearing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    /// The date, in the local mean time of the position.
+    pub date: NaiveDate,
+    /// The instant of the event.
+    pub time: DateTime<Utc>,
+    /// The azimuth of the sun at the event, in degrees clockwise
+    /// from true north.
+    pub azimuth: f64,
+    /// How far the azimuth lies from the bearing, in degrees.
+    pub deviation: f64
+}
+
+/// The azimuth of the sun at sunrise on the local mean `date` at `pos`,
+/// in degrees clockwise from true north, or None if the sun doesn't
+/// rise that day. See [CompassPoint::from_azimuth](crate::CompassPoint::from_azimuth)
+/// to name the bearing.
+pub fn sunrise_azimuth(date: NaiveDate, pos: &GlobalPosition) -> Option<f64> {
+    event_azimuth(date, pos, SunEvent::SUNRISE)
+}
+
+/// The azimuth of the sun at sunset on the local mean `date` at `pos`,
+/// in degrees clockwise from true north, or None if the sun doesn't
+/// set that day.
+pub fn sunset_azimuth(date: NaiveDate, pos: &GlobalPosition) -> Option<f64> {
+    event_azimuth(date, pos, SunEvent::SUNSET)
+}
+
+/// The azimuth of the sun at `event` on the local mean `date` at `pos`,
+/// in degrees clockwise from true north, or None if the event doesn't
+/// happen that day.
+pub fn event_azimuth(date: NaiveDate, pos: &GlobalPosition, event: SunEvent) -> Option<f64> {
+    event_time_and_azimuth(date, pos, event).map(|(_, azimuth)| azimuth)
+}
+
+fn event_time_and_azimuth(date: NaiveDate, pos: &GlobalPosition, event: SunEvent) -> Option<(DateTime<Utc>, f64)> {
+    let time = time_of_event_on(date, &pos.lng_timezone(), pos, event)?.with_timezone(&Utc);
+    let (_, azimuth) = solar::horizontal(julian_day(time), pos);
+    Some((time, azimuth))
+}
+
+/// Lists the days of `year` on which `event` happens at `pos` with the
+/// sun within `tolerance` degrees of `bearing`, the direction in degrees
+/// clockwise from true north of the horizon where the sun is expected.
+///
+/// The sun usually lines up with a bearing on a few consecutive days
+/// twice a year, on either side of a solstice.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn sun_alignments(year: i32, pos: &GlobalPosition, bearing: f64, event: SunEvent, tolerance: f64) -> Vec<Alignment> {
+    days_of_year(year)
+        .filter_map(|date| {
+            let (time, azimuth) = event_time_and_azimuth(date, pos, event)?;
+            let deviation = ((azimuth - bearing).rem_euclid(360.0) + 180.0).rem_euclid(360.0) - 180.0;
+            Some(Alignment { date, time, azimuth, deviation: deviation.abs() })
+        })
+        .filter(|alignment| alignment.deviation <= tolerance)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn sun_should_rise_and_set_due_east_and_west_at_the_equinox() {
+        let pos = GlobalPosition::at(40.7831, -73.9712);
+        let equinox = NaiveDate::from_ymd_opt(2021, 3, 20).unwrap();
+        let sunrise = sunrise_azimuth(equinox, &pos).unwrap();
+        let sunset = sunset_azimuth(equinox, &pos).unwrap();
+        assert!((sunrise - 90.0).abs() < 1.5, "{}", sunrise);
+        assert!((sunset - 270.0).abs() < 1.5, "{}", sunset);
+        let solstice = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        assert!(sunrise_azimuth(solstice, &pos).unwrap() < 60.0);
+        let arctic = GlobalPosition::at(78.2232, 15.6267);
+        assert_eq!(sunset_azimuth(solstice, &arctic), None);
+    }
+
+    #[test]
+    fn should_find_manhattanhenge() {
+        // The sun sets along the cross streets of Manhattan, about 30
+        // degrees north of west, around May 30 and July 12.
+        let pos = GlobalPosition::at(40.7831, -73.9712);
+        let alignments = sun_alignments(2021, &pos, 300.0, SunEvent::SUNSET, 0.3);
+        assert!(!alignments.is_empty());
+        let may = NaiveDate::from_ymd_opt(2021, 5, 30).unwrap();
+        let july = NaiveDate::from_ymd_opt(2021, 7, 12).unwrap();
+        for alignment in &alignments {
+            let near = |date: NaiveDate| (alignment.date - date).num_days().abs() <= 2;
+            assert!(near(may) || near(july), "{:?}", alignment);
+        }
+        assert!(alignments.iter().any(|alignment| alignment.date.month() == 5));
+        assert!(alignments.iter().any(|alignment| alignment.date.month() == 7));
+        assert!(sun_alignments(2021, &pos, 300.0, SunEvent::SUNRISE, 0.3).is_empty());
+    }
+
+}
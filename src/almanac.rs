@@ -0,0 +1,65 @@
+//! This module generates tables of the events of every day of a year,
+//! as printed in almanacs.
+
+use chrono::{ DateTime, NaiveDate, Utc };
+use crate::adapter::chrono::{ days_of_year, from_unix, unix_day };
+use crate::epoch::event_results_unix;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// One day of an almanac table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayRow {
+    /// The date, in the local mean time of the position.
+    pub date: NaiveDate,
+    /// The times of the requested events, in the order they were
+    /// requested, None where an event doesn't happen that day.
+    pub times: Vec<Option<DateTime<Utc>>>
+}
+
+/// Computes the times of `events` on every local mean date of `year`
+/// at `pos`.
+///
+/// This shares the computations common to the events of each day,
+/// which makes it faster than calling [time_of_event](crate::time_of_event)
+/// for every day and event.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn year_table(year: i32, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<DayRow> {
+    days_of_year(year)
+        .map(|date| day_row(date, pos, events))
+        .collect()
+}
+
+/// Computes the row of `events` on the local mean `date` at `pos`.
+pub(crate) fn day_row(date: NaiveDate, pos: &GlobalPosition, events: &[SunEvent]) -> DayRow {
+    let times = event_results_unix(unix_day(date), pos, events)
+        .into_iter()
+        .map(|result| result.at().and_then(from_unix))
+        .collect();
+    DayRow { date, times }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adapter::chrono::time_of_event_on;
+
+    #[test]
+    fn table_should_cover_every_day_of_the_year() {
+        let pos = GlobalPosition::at(40.7831, -73.9712);
+        let events = [SunEvent::SUNRISE, SunEvent::SUNSET];
+        let table = year_table(2020, &pos, &events);
+        assert_eq!(table.len(), 366);
+        assert_eq!(table[59].date, NaiveDate::from_ymd_opt(2020, 2, 29).unwrap());
+        let tz = pos.lng_timezone();
+        for row in table.iter().step_by(30) {
+            for (event, time) in events.iter().zip(&row.times) {
+                let expected = time_of_event_on(row.date, &tz, &pos, *event).map(|time| time.with_timezone(&Utc));
+                assert_eq!(*time, expected);
+            }
+        }
+    }
+
+}
@@ -0,0 +1,167 @@
+//! Coordinate transformations and time scales shared by the sun and
+//! moon computations. Formulas are from Jean Meeus,
+//! "Astronomical Algorithms", 2nd edition.
+
+#![allow(non_snake_case)]
+
+/// The Julian day of the J2000.0 epoch.
+pub(crate) const J2000: f64 = 2451545.0;
+
+/// The number of days in a Julian century.
+pub(crate) const DAYS_IN_CENTURY: f64 = 36525.0;
+
+/// The equatorial radius of the earth, in km.
+pub(crate) const EARTH_RADIUS_KM: f64 = 6378.14;
+
+/// Julian centuries elapsed since J2000.0.
+pub(crate) fn julian_centuries(jd: f64) -> f64 {
+    (jd - J2000) / DAYS_IN_CENTURY
+}
+
+/// Wraps an angle in degrees into [0, 360).
+pub(crate) fn normalize_degrees(angle: f64) -> f64 {
+    angle.rem_euclid(360.0)
+}
+
+/// An estimate of ΔT = TT - UT, in seconds, for the given (fractional)
+/// year, from the polynomials of Espenak and Meeus.
+pub(crate) fn delta_t(year: f64) -> f64 {
+    let long_term = |y: f64| {
+        let u = (y - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    };
+    if year < 1900.0 {
+        long_term(year)
+    } else if year < 1920.0 {
+        let t = year - 1900.0;
+        -2.79 + 1.494119 * t - 0.0598939 * t.powi(2) + 0.0061966 * t.powi(3) - 0.000197 * t.powi(4)
+    } else if year < 1941.0 {
+        let t = year - 1920.0;
+        21.20 + 0.84493 * t - 0.076100 * t.powi(2) + 0.0020936 * t.powi(3)
+    } else if year < 1961.0 {
+        let t = year - 1950.0;
+        29.07 + 0.407 * t - t.powi(2) / 233.0 + t.powi(3) / 2547.0
+    } else if year < 1986.0 {
+        let t = year - 1975.0;
+        45.45 + 1.067 * t - t.powi(2) / 260.0 - t.powi(3) / 718.0
+    } else if year < 2005.0 {
+        let t = year - 2000.0;
+        63.86 + 0.3345 * t - 0.060374 * t.powi(2) + 0.0017275 * t.powi(3)
+            + 0.000651814 * t.powi(4) + 0.00002373599 * t.powi(5)
+    } else if year < 2050.0 {
+        let t = year - 2000.0;
+        62.92 + 0.32217 * t + 0.005589 * t.powi(2)
+    } else if year < 2150.0 {
+        long_term(year) - 0.5628 * (2150.0 - year)
+    } else {
+        long_term(year)
+    }
+}
+
+/// Converts a Julian ephemeris day (TT) to a Julian day (UT).
+pub(crate) fn ephemeris_to_universal(jde: f64) -> f64 {
+    let year = 2000.0 + (jde - J2000) / 365.25;
+    jde - delta_t(year) / 86400.0
+}
+
+/// Converts a Julian day (UT) to a Julian ephemeris day (TT).
+pub(crate) fn universal_to_ephemeris(jd: f64) -> f64 {
+    let year = 2000.0 + (jd - J2000) / 365.25;
+    jd + delta_t(year) / 86400.0
+}
+
+/// The mean obliquity of the ecliptic, in degrees (Meeus 22.2).
+pub(crate) fn mean_obliquity(T: f64) -> f64 {
+    23.4392911 - 0.0130042 * T - 0.000000164 * T * T + 0.000000504 * T * T * T
+}
+
+/// The nutation in longitude and in obliquity, in degrees,
+/// to a precision of about half an arcsecond (Meeus ch. 22).
+pub(crate) fn nutation(T: f64) -> (f64, f64) {
+    let omega = (125.04452 - 1934.136261 * T).to_radians();
+    let L = (280.4665 + 36000.7698 * T).to_radians();
+    let Lm = (218.3165 + 481267.8813 * T).to_radians();
+    let dpsi = -17.20 * omega.sin() - 1.32 * (2.0 * L).sin() - 0.23 * (2.0 * Lm).sin()
+        + 0.21 * (2.0 * omega).sin();
+    let deps = 9.20 * omega.cos() + 0.57 * (2.0 * L).cos() + 0.10 * (2.0 * Lm).cos()
+        - 0.09 * (2.0 * omega).cos();
+    (dpsi / 3600.0, deps / 3600.0)
+}
+
+/// The apparent sidereal time at Greenwich, in degrees (Meeus 12.4).
+pub(crate) fn greenwich_sidereal_time(jd: f64) -> f64 {
+    let T = julian_centuries(jd);
+    let mean = 280.46061837 + 360.98564736629 * (jd - J2000)
+        + 0.000387933 * T * T - T * T * T / 38710000.0;
+    let (dpsi, deps) = nutation(T);
+    let eps = mean_obliquity(T) + deps;
+    normalize_degrees(mean + dpsi * eps.to_radians().cos())
+}
+
+/// Converts ecliptic longitude and latitude into right ascension and
+/// declination, given the obliquity `eps`. All angles in degrees.
+pub(crate) fn ecliptic_to_equatorial(lambda: f64, beta: f64, eps: f64) -> (f64, f64) {
+    let (lambda, beta, eps) = (lambda.to_radians(), beta.to_radians(), eps.to_radians());
+    let ra = (lambda.sin() * eps.cos() - beta.tan() * eps.sin()).atan2(lambda.cos());
+    let dec = (beta.sin() * eps.cos() + beta.cos() * eps.sin() * lambda.sin()).asin();
+    (normalize_degrees(ra.to_degrees()), dec.to_degrees())
+}
+
+/// Converts a local hour angle and declination into altitude and
+/// azimuth (clockwise from north) at latitude `lat`. All angles
+/// in degrees.
+pub(crate) fn equatorial_to_horizontal(hour_angle: f64, dec: f64, lat: f64) -> (f64, f64) {
+    let (H, dec, lat) = (hour_angle.to_radians(), dec.to_radians(), lat.to_radians());
+    let alt = (lat.sin() * dec.sin() + lat.cos() * dec.cos() * H.cos()).asin();
+    let az = H.sin().atan2(H.cos() * lat.sin() - dec.tan() * lat.cos());
+    (alt.to_degrees(), normalize_degrees(az.to_degrees() + 180.0))
+}
+
+/// Corrects geocentric right ascension and declination for the
+/// parallax of a body at `distance_km` seen from latitude `lat` and
+/// `elevation` metres above sea level, given its local hour angle
+/// (Meeus ch. 40). Returns the topocentric hour angle and declination.
+pub(crate) fn topocentric(hour_angle: f64, dec: f64, distance_km: f64, lat: f64, elevation: f64) -> (f64, f64) {
+    let sin_parallax = EARTH_RADIUS_KM / distance_km;
+    let u = (0.99664719 * lat.to_radians().tan()).atan();
+    let h = elevation / 6378140.0;
+    let rho_sin = 0.99664719 * u.sin() + h * lat.to_radians().sin();
+    let rho_cos = u.cos() + h * lat.to_radians().cos();
+    let (H, dec) = (hour_angle.to_radians(), dec.to_radians());
+    let d_ra = (-rho_cos * sin_parallax * H.sin())
+        .atan2(dec.cos() - rho_cos * sin_parallax * H.cos());
+    let dec_topo = ((dec.sin() - rho_sin * sin_parallax) * d_ra.cos())
+        .atan2(dec.cos() - rho_cos * sin_parallax * H.cos());
+    (hour_angle - d_ra.to_degrees(), dec_topo.to_degrees())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn sidereal_time_should_match_meeus() {
+        // Meeus example 12.a: 1987 April 10, 0h UT.
+        // 13h10m46.1351s
+        let theta = greenwich_sidereal_time(2446895.5);
+        assert!((theta - 197.692230).abs() < 0.0005, "{}", theta);
+    }
+
+    #[test]
+    fn ecliptic_coordinates_should_convert_to_equatorial() {
+        // Meeus example 13.a, Pollux.
+        let (ra, dec) = ecliptic_to_equatorial(113.215630, 6.684170, 23.4392911);
+        assert!((ra - 116.328942).abs() < 1e-4, "{}", ra);
+        assert!((dec - 28.026183).abs() < 1e-4, "{}", dec);
+    }
+
+    #[test]
+    fn ephemeris_time_should_round_trip() {
+        // ΔT was about 64 seconds in 2000.
+        let jde = universal_to_ephemeris(J2000);
+        assert!(((jde - J2000) * 86400.0 - 63.86).abs() < 0.1, "{}", jde);
+        assert!((ephemeris_to_universal(jde) - J2000).abs() * 86400.0 < 0.01);
+    }
+
+}
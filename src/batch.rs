@@ -0,0 +1,109 @@
+//! Computes an event over many consecutive days, or over many positions
+//! on one day, [LANES] at a time through
+//! [event_results_lanes](crate::core::event_results_lanes), for
+//! pipelines that would otherwise make millions of scalar calls.
+//!
+//! The functions fill slices given by the caller, so that they run
+//! without allocating, on `no_std` targets too. The results are those of
+//! [event_result_unix], which computes the events one at a time.
+
+use crate::algorithm::zenith_angle;
+use crate::core::{ event_results_lanes, EventResult, LANES };
+use crate::epoch::{ event_result_unix, ordinal_of, unix_of_event };
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// Fills `results` with the time of `event` at `pos` on the UTC days
+/// following `first_day`, counted in days since the Unix epoch, one
+/// day per element: `results[i]` holds the event of `first_day + i`.
+pub fn event_results_for_days(first_day: i64, pos: &GlobalPosition, event: SunEvent, results: &mut [EventResult<i64>]) {
+    fill(results, event, |i| {
+        let day = first_day + i as i64;
+        (day, ordinal_of(day), pos)
+    });
+}
+
+/// Fills `results` with the time of `event` on the UTC day `day`,
+/// counted in days since the Unix epoch, at each of `positions`, in
+/// their order.
+/// # Panics
+/// Panics when `results` and `positions` differ in length.
+pub fn event_results_for_positions(day: i64, positions: &[GlobalPosition], event: SunEvent, results: &mut [EventResult<i64>]) {
+    assert_eq!(positions.len(), results.len(), "there should be a result per position");
+    let ordinal = ordinal_of(day);
+    fill(results, event, |i| (day, ordinal, &positions[i]));
+}
+
+/// Fills `results` with `event` on the day, of the ordinal in its year,
+/// and at the position that `input` gives each index.
+fn fill<'a>(results: &mut [EventResult<i64>], event: SunEvent, input: impl Fn(usize) -> (i64, u32, &'a GlobalPosition)) {
+    if event.is_transit() {
+        // The transits take no inverse trigonometry, and gain little
+        // from the lanes.
+        for (i, result) in results.iter_mut().enumerate() {
+            let (day, _, pos) = input(i);
+            *result = event_result_unix(day, pos, event);
+        }
+        return;
+    }
+    let len = results.len();
+    for (chunk, results) in results.chunks_mut(LANES).enumerate() {
+        // The lanes past the end repeat the last input, and their
+        // results are dropped.
+        let lane = |lane: usize| input((chunk * LANES + lane).min(len - 1));
+        let ordinals = ::core::array::from_fn(|i| lane(i).1);
+        let lats = ::core::array::from_fn(|i| lane(i).2.lat());
+        let lng_hours = ::core::array::from_fn(|i| lane(i).2.lng_hour());
+        let zeniths = ::core::array::from_fn(|i| zenith_angle(lane(i).2, event.zenith));
+        let uts = event_results_lanes(ordinals, lats, lng_hours, zeniths, event.is_sunrise());
+        for (i, (result, ut)) in results.iter_mut().zip(uts).enumerate() {
+            let (day, _, pos) = lane(i);
+            *result = ut.map(|ut| unix_of_event(day, pos, event, ut));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::event::{ Event, Zenith };
+
+    const EVENTS: [SunEvent; 4] = [
+        SunEvent::SUNRISE,
+        SunEvent::SUNSET,
+        SunEvent::SOLAR_NOON,
+        SunEvent::new(Zenith::Nautical, Event::Sunset)
+    ];
+
+    #[test]
+    fn days_should_match_the_scalar_results() {
+        let pos = GlobalPosition::at(69.6492, 18.9553).with_elevation(120.0);
+        // 2023-12-01, across the new year and the polar night of Tromsø.
+        let first_day = 19_692;
+        for &event in &EVENTS {
+            let mut results = [EventResult::PolarDay; 45];
+            event_results_for_days(first_day, &pos, event, &mut results);
+            for (i, result) in results.iter().enumerate() {
+                assert_eq!(*result, event_result_unix(first_day + i as i64, &pos, event), "{} {}", event, i);
+            }
+        }
+    }
+
+    #[test]
+    fn positions_should_match_the_scalar_results() {
+        let positions: Vec<_> = (-17..=17)
+            .map(|i| GlobalPosition::at(i as f64 * 5.0, i as f64 * 10.0))
+            .collect();
+        let day = 19_895;
+        for &event in &EVENTS {
+            let mut results = vec![EventResult::PolarDay; positions.len()];
+            event_results_for_positions(day, &positions, event, &mut results);
+            for (pos, result) in positions.iter().zip(&results) {
+                assert_eq!(*result, event_result_unix(day, pos, event), "{} {}", event, pos);
+            }
+        }
+        event_results_for_positions(day, &[], SunEvent::SUNRISE, &mut []);
+    }
+
+}
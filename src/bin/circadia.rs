@@ -0,0 +1,316 @@
+//! The `circadia` command, printing the sun events of a position.
+
+use std::fmt::Display;
+use std::process;
+use chrono::{ DateTime, Datelike, FixedOffset, Local, NaiveDate, TimeZone, Utc };
+use circadia::{ time_of_event_on, Clock, GlobalPosition, Schedule, SunEvent, SunEvents, SystemClock };
+use clap::{ Args, Parser, Subcommand };
+use serde_json::json;
+
+/// Prints the times of the sunrise, sunset, twilights and transits of
+/// the sun at a position.
+#[derive(Debug, Parser)]
+#[command(name = "circadia", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Prints the next events to occur.
+    Next {
+        #[command(flatten)]
+        common: Common,
+        /// The number of events to print.
+        #[arg(long, short = 'n', default_value_t = 1)]
+        count: usize
+    },
+    /// Prints a table of the events of every date of a month.
+    Table {
+        #[command(flatten)]
+        common: Common,
+        /// The month, as YYYY-MM, the current month by default.
+        #[arg(long, value_parser = parse_month)]
+        month: Option<NaiveDate>
+    },
+    /// Prints the events of the coming days as JSON.
+    Json {
+        #[command(flatten)]
+        common: Common,
+        /// The number of dates to print, starting today.
+        #[arg(long, default_value_t = 7)]
+        days: u32
+    },
+    /// Runs a command at an event every day, such as
+    /// `circadia watch --at sunset-15m -- lights on`.
+    Watch {
+        #[command(flatten)]
+        common: Common,
+        /// The event to run the command at, shifted by an offset such as
+        /// "+1h" or "-15m": "sunset-15m", "civil_dawn+1h30m".
+        #[arg(long)]
+        at: Schedule,
+        /// The command to run, and its arguments.
+        #[arg(last = true, required = true)]
+        command: Vec<String>
+    }
+}
+
+impl Command {
+
+    fn common(&self) -> &Common {
+        match self {
+            Command::Next { common, .. }
+            | Command::Table { common, .. }
+            | Command::Json { common, .. }
+            | Command::Watch { common, .. } => common
+        }
+    }
+
+}
+
+#[derive(Debug, Args)]
+struct Common {
+    /// The latitude, in degrees north.
+    #[arg(long, allow_negative_numbers = true)]
+    lat: f64,
+    /// The longitude, in degrees east.
+    #[arg(long, allow_negative_numbers = true)]
+    lng: f64,
+    /// The elevation above sea level, in meters.
+    #[arg(long, default_value_t = 0.0)]
+    elevation: f64,
+    /// An event to include, such as "sunrise" or "civil sunset", given
+    /// once per event.
+    #[arg(long = "event", short = 'e')]
+    events: Vec<SunEvent>,
+    /// The timezone of the printed times and dates: "utc", "local" or
+    /// an offset such as "+02:00".
+    #[arg(long, default_value = "local", value_parser = parse_zone)]
+    tz: Zone
+}
+
+impl Common {
+
+    fn position(&self) -> GlobalPosition {
+        match GlobalPosition::try_at(self.lat, self.lng) {
+            Ok(pos) => pos.with_elevation(self.elevation),
+            Err(err) => fail(err)
+        }
+    }
+
+    /// The events given, or `default` when there are none, in their
+    /// order through a day.
+    fn events_or(&self, default: &[SunEvent]) -> Vec<SunEvent> {
+        let mut events = if self.events.is_empty() {
+            default.to_vec()
+        } else {
+            self.events.clone()
+        };
+        events.sort();
+        events.dedup();
+        events
+    }
+
+}
+
+/// The timezone to print the times in.
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Utc,
+    Local,
+    Fixed(FixedOffset)
+}
+
+/// The events printed when none are given.
+const DEFAULT_EVENTS: [SunEvent; 5] = [
+    SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET, SunEvent::DUSK
+];
+
+fn main() {
+    let cli = Cli::parse();
+    match cli.command.common().tz {
+        Zone::Utc => run(&cli.command, Utc),
+        Zone::Local => run(&cli.command, Local),
+        Zone::Fixed(offset) => run(&cli.command, offset),
+    }
+}
+
+fn run<Tz: TimeZone>(command: &Command, tz: Tz)
+where
+    Tz::Offset: Display
+{
+    match command {
+        Command::Next { common, count } => {
+            let events = common.events_or(&[SunEvent::SUNRISE, SunEvent::SUNSET]);
+            let upcoming = SunEvents::starting_from(Utc::now(), common.position(), &events)
+                .forecast()
+                .take(*count);
+            for (event, time) in upcoming {
+                println!("{}  {}", time.with_timezone(&tz).to_rfc3339(), event);
+            }
+        },
+        Command::Table { common, month } => {
+            let first = month.unwrap_or_else(|| {
+                let today = Utc::now().with_timezone(&tz).date_naive();
+                today.with_day(1).unwrap()
+            });
+            let events = common.events_or(&DEFAULT_EVENTS);
+            print!("{}", table(first, &common.position(), &events, &tz));
+        },
+        Command::Json { common, days } => {
+            let today = Utc::now().with_timezone(&tz).date_naive();
+            let events = common.events_or(&SunEvent::all());
+            let json = day_events(today, *days, &common.position(), &events, &tz);
+            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        },
+        Command::Watch { common, at, command } => watch(&common.position(), at, command, &tz)
+    }
+}
+
+/// Runs `command` at every occurrence of `at`, until it can't be
+/// started.
+fn watch<Tz: TimeZone>(pos: &GlobalPosition, at: &Schedule, command: &[String], tz: &Tz) -> !
+where
+    Tz::Offset: Display
+{
+    loop {
+        let fire_time = match at.next_after(Utc::now(), pos) {
+            Some(time) => time,
+            None => fail(format!("the {} doesn't happen at this position", at.event()))
+        };
+        eprintln!("circadia: running {:?} at {}", command.join(" "), fire_time.with_timezone(tz).to_rfc3339());
+        SystemClock.sleep_until(fire_time);
+        let status = process::Command::new(&command[0])
+            .args(&command[1..])
+            .env("CIRCADIA_EVENT", at.event().to_string())
+            .env("CIRCADIA_EVENT_TIME", (fire_time - at.offset()).to_rfc3339())
+            .status();
+        match status {
+            Ok(status) if !status.success() => eprintln!("circadia: {:?} exited with {}", command[0], status),
+            Ok(_) => (),
+            Err(err) => fail(format!("couldn't run {:?}: {}", command[0], err))
+        }
+    }
+}
+
+/// Formats the `events` of every date of the month starting on `first`,
+/// one date per line and one event per column.
+fn table<Tz: TimeZone>(first: NaiveDate, pos: &GlobalPosition, events: &[SunEvent], tz: &Tz) -> String
+where
+    Tz::Offset: Display
+{
+    let mut table = format!("{:<10}", "date");
+    for event in events {
+        table += &format!("  {:>20}", event.to_string());
+    }
+    table += "\n";
+    for date in first.iter_days().take_while(|date| date.month() == first.month()) {
+        table += &date.to_string();
+        for &event in events {
+            let time = time_of_event_on(date, tz, pos, event)
+                .map(|time| time.format("%H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            table += &format!("  {:>20}", time);
+        }
+        table += "\n";
+    }
+    table
+}
+
+/// Lists the `events` of the `days` dates starting on `first` as JSON.
+fn day_events<Tz: TimeZone>(first: NaiveDate, days: u32, pos: &GlobalPosition, events: &[SunEvent], tz: &Tz) -> serde_json::Value
+where
+    Tz::Offset: Display
+{
+    let days: Vec<_> = first.iter_days()
+        .take(days as usize)
+        .map(|date| {
+            let events: Vec<_> = events.iter()
+                .map(|&event| {
+                    let time = time_of_event_on(date, tz, pos, event).map(|time: DateTime<Tz>| time.to_rfc3339());
+                    json!({ "event": event.to_string(), "time": time })
+                })
+                .collect();
+            json!({ "date": date.to_string(), "events": events })
+        })
+        .collect();
+    json!({ "lat": pos.lat(), "lng": pos.lng(), "days": days })
+}
+
+fn parse_month(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", text.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("expected a month as YYYY-MM, found {:?}", text))
+}
+
+fn parse_zone(text: &str) -> Result<Zone, String> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "utc" | "z" => Ok(Zone::Utc),
+        "local" => Ok(Zone::Local),
+        offset => DateTime::parse_from_str(&format!("2000-01-01T00:00:00{}", offset), "%Y-%m-%dT%H:%M:%S%:z")
+            .map(|time| Zone::Fixed(*time.offset()))
+            .map_err(|_| format!("expected \"utc\", \"local\" or an offset such as +02:00, found {:?}", text))
+    }
+}
+
+fn fail<E: Display>(err: E) -> ! {
+    eprintln!("circadia: {}", err);
+    process::exit(2)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn arguments_should_parse() {
+        let cli = Cli::try_parse_from([
+            "circadia", "table", "--lat", "-33.8688", "--lng", "151.2093",
+            "--month", "2024-06", "-e", "civil sunrise", "-e", "sunset", "--tz", "+10:00"
+        ]).unwrap();
+        match cli.command {
+            Command::Table { common, month } => {
+                assert_eq!((common.lat, common.lng), (-33.8688, 151.2093));
+                assert_eq!(month, NaiveDate::from_ymd_opt(2024, 6, 1));
+                assert_eq!(common.events, vec![SunEvent::DAWN, SunEvent::SUNSET]);
+                assert!(matches!(common.tz, Zone::Fixed(offset) if offset.local_minus_utc() == 36_000));
+            },
+            command => panic!("{:?}", command)
+        }
+        assert!(Cli::try_parse_from(["circadia", "table", "--lat", "0", "--lng", "0", "--month", "June"]).is_err());
+        assert!(Cli::try_parse_from(["circadia", "next", "--lat", "0", "--lng", "0", "-e", "moonrise"]).is_err());
+        let cli = Cli::try_parse_from(["circadia", "watch", "--lat", "0", "--lng", "0", "--at", "civil_dawn+1h", "--", "echo", "hi"]).unwrap();
+        match cli.command {
+            Command::Watch { at, command, .. } => {
+                assert_eq!(at, Schedule::new(SunEvent::DAWN, Duration::hours(1)));
+                assert_eq!(command, vec!["echo", "hi"]);
+            },
+            command => panic!("{:?}", command)
+        }
+    }
+
+    #[test]
+    fn tables_should_list_every_date_of_the_month() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let table = table(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), &pos, &[SunEvent::SUNRISE], &Utc);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 30);
+        assert!(lines[0].starts_with("date") && lines[0].ends_with("sunrise"));
+        assert!(lines[29].starts_with("2024-02-29  "), "{}", lines[29]);
+    }
+
+    #[test]
+    fn json_should_list_the_events_of_each_date() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let json = day_events(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), 2, &pos, &[SunEvent::SOLAR_NOON, SunEvent::SUNSET], &Utc);
+        assert_eq!(json["days"].as_array().unwrap().len(), 2);
+        assert_eq!(json["days"][1]["date"], "2024-06-22");
+        assert_eq!(json["days"][0]["events"][0]["event"], "solar noon");
+        assert!(json["days"][0]["events"][0]["time"].as_str().unwrap().starts_with("2024-06-21T"));
+        assert!(json["days"][0]["events"][1]["time"].is_null());
+    }
+
+}
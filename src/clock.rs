@@ -0,0 +1,187 @@
+//! This module provides an abstraction over the current time so that
+//! logic driven by sun events can be tested with a mock clock.
+
+use chrono::{ DateTime, Duration, Utc };
+use std::cell::Cell;
+use std::thread;
+use super::event::SunEvent;
+use super::iter::SunEvents;
+use super::pos::GlobalPosition;
+
+/// The longest a wait sleeps before checking the clock again, so that
+/// a clock changed or a machine suspended meanwhile doesn't delay the
+/// wake-up by more than this.
+pub(crate) const MAX_NAP: Duration = Duration::seconds(60);
+
+/// A source of the current time.
+pub trait Clock {
+    /// The current instant.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Blocks the thread until the clock reaches `time`, sleeping for
+    /// at most a minute at a time before reading the clock again, as
+    /// suits the clocks following the system time. Clocks which move
+    /// otherwise, like [ManualClock], move to `time` instead.
+    fn sleep_until(&self, time: DateTime<Utc>) {
+        while let Some(nap) = nap_until(self, time) {
+            thread::sleep(nap);
+        }
+    }
+}
+
+/// How long to sleep before reading `clock` again on the way to `time`,
+/// or None once it has reached it.
+pub(crate) fn nap_until<C: Clock + ?Sized>(clock: &C, time: DateTime<Utc>) -> Option<std::time::Duration> {
+    let remaining = time - clock.now();
+    if remaining <= Duration::zero() {
+        return None;
+    }
+    remaining.min(MAX_NAP).to_std().ok()
+}
+
+/// The system clock, in UTC.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Cell<DateTime<Utc>>
+}
+
+impl ManualClock {
+
+    /// Create a clock stopped at `now`.
+    pub fn at(now: DateTime<Utc>) -> Self {
+        ManualClock { now: Cell::new(now) }
+    }
+
+    /// Move the clock to `now`.
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.set(now);
+    }
+
+    /// Move the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+
+    /// Moves the clock forward to `time` at once, if it's behind.
+    fn sleep_until(&self, time: DateTime<Utc>) {
+        if time > self.now.get() {
+            self.now.set(time);
+        }
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+
+    fn sleep_until(&self, time: DateTime<Utc>) {
+        (**self).sleep_until(time)
+    }
+}
+
+/// Returns the next of the `events` to occur at `position` after the
+/// current time of `clock`.
+/// # Panics
+/// Panics when `events` is empty.
+pub fn next_event_from<C: Clock>(
+    clock: &C,
+    position: &GlobalPosition,
+    events: &[SunEvent],
+) -> Option<(SunEvent, DateTime<Utc>)> {
+    SunEvents::starting_from(clock.now(), position.clone(), events)
+        .forecast()
+        .next()
+}
+
+/// Returns the next of the `events` to occur at `position` from now,
+/// according to the system clock.
+/// # Panics
+/// Panics when `events` is empty.
+pub fn next_event_from_now(
+    position: &GlobalPosition,
+    events: &[SunEvent],
+) -> Option<(SunEvent, DateTime<Utc>)> {
+    next_event_from(&SystemClock, position, events)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use chrono::TimeZone;
+    #[cfg(any(feature = "scheduler", feature = "async"))]
+    use std::sync::{ Arc, Mutex };
+    #[cfg(any(feature = "scheduler", feature = "async"))]
+    use tokio::time::Instant;
+
+    /// A clock driven by the paused time of tokio, which can also jump
+    /// as the system clock does when set.
+    #[cfg(any(feature = "scheduler", feature = "async"))]
+    #[derive(Debug, Clone)]
+    pub(crate) struct TokioClock {
+        start: Instant,
+        base: Arc<Mutex<DateTime<Utc>>>
+    }
+
+    #[cfg(any(feature = "scheduler", feature = "async"))]
+    impl TokioClock {
+
+        pub(crate) fn at(base: DateTime<Utc>) -> Self {
+            TokioClock { start: Instant::now(), base: Arc::new(Mutex::new(base)) }
+        }
+
+        pub(crate) fn jump(&self, duration: Duration) {
+            *self.base.lock().unwrap() += duration;
+        }
+
+    }
+
+    #[cfg(any(feature = "scheduler", feature = "async"))]
+    impl Clock for TokioClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.base.lock().unwrap() + Duration::from_std(self.start.elapsed()).unwrap()
+        }
+    }
+
+    #[test]
+    fn next_event_should_follow_the_clock() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let clock = ManualClock::at(Utc.with_ymd_and_hms(2019, 6, 21, 12, 0, 0).unwrap());
+        let events = &[SunEvent::SUNRISE, SunEvent::SUNSET];
+        let (event, time) = next_event_from(&clock, &pos, events).unwrap();
+        assert_eq!(event, SunEvent::SUNSET);
+        assert!(time > clock.now());
+        clock.advance(Duration::hours(12));
+        let (event, time) = next_event_from(&clock, &pos, events).unwrap();
+        assert_eq!(event, SunEvent::SUNRISE);
+        assert!(time > clock.now());
+    }
+
+    #[test]
+    fn manual_clocks_should_sleep_by_moving() {
+        let start = Utc.with_ymd_and_hms(2019, 6, 21, 12, 0, 0).unwrap();
+        let clock = ManualClock::at(start);
+        clock.sleep_until(start + Duration::days(3));
+        assert_eq!(clock.now(), start + Duration::days(3));
+        clock.sleep_until(start);
+        assert_eq!(clock.now(), start + Duration::days(3));
+    }
+
+}
@@ -0,0 +1,144 @@
+//! This module provides formatting of azimuths as points of the compass.
+
+use core::fmt;
+use crate::math::Float;
+
+/// One of the 16 points of the compass.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum CompassPoint {
+    N, NNE, NE, ENE,
+    E, ESE, SE, SSE,
+    S, SSW, SW, WSW,
+    W, WNW, NW, NNW
+}
+
+/// The languages in which compass points can be named.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Language {
+    English,
+    French,
+    German,
+    Spanish
+}
+
+const POINTS: [CompassPoint; 16] = {
+    use CompassPoint::*;
+    [N, NNE, NE, ENE, E, ESE, SE, SSE, S, SSW, SW, WSW, W, WNW, NW, NNW]
+};
+
+const ABBREVIATIONS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+    "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW"
+];
+
+const ENGLISH: [&str; 16] = [
+    "north", "north-northeast", "northeast", "east-northeast",
+    "east", "east-southeast", "southeast", "south-southeast",
+    "south", "south-southwest", "southwest", "west-southwest",
+    "west", "west-northwest", "northwest", "north-northwest"
+];
+
+const FRENCH: [&str; 16] = [
+    "nord", "nord-nord-est", "nord-est", "est-nord-est",
+    "est", "est-sud-est", "sud-est", "sud-sud-est",
+    "sud", "sud-sud-ouest", "sud-ouest", "ouest-sud-ouest",
+    "ouest", "ouest-nord-ouest", "nord-ouest", "nord-nord-ouest"
+];
+
+const GERMAN: [&str; 16] = [
+    "Nord", "Nordnordost", "Nordost", "Ostnordost",
+    "Ost", "Ostsüdost", "Südost", "Südsüdost",
+    "Süd", "Südsüdwest", "Südwest", "Westsüdwest",
+    "West", "Westnordwest", "Nordwest", "Nordnordwest"
+];
+
+const SPANISH: [&str; 16] = [
+    "norte", "nornordeste", "nordeste", "estenordeste",
+    "este", "estesudeste", "sudeste", "sudsudeste",
+    "sur", "sudsudoeste", "sudoeste", "oestesudoeste",
+    "oeste", "oestenoroeste", "noroeste", "nornoroeste"
+];
+
+impl CompassPoint {
+
+    /// The compass point nearest to `azimuth`, in degrees clockwise
+    /// from true north. Each point covers the 22.5° sector centered
+    /// on it, including its counter-clockwise edge, so 11.25° is NNE.
+    /// Azimuths outside of [0, 360) are wrapped.
+    pub fn from_azimuth(azimuth: f64) -> Self {
+        let sector = Float::floor(Float::rem_euclid(azimuth + 11.25, 360.0) / 22.5) as usize;
+        POINTS[sector % 16]
+    }
+
+    /// The azimuth, in degrees clockwise from true north,
+    /// at the center of this point.
+    pub fn azimuth(self) -> f64 {
+        self.index() as f64 * 22.5
+    }
+
+    /// The abbreviated name of this point, e.g. "ENE".
+    pub fn abbreviation(self) -> &'static str {
+        ABBREVIATIONS[self.index()]
+    }
+
+    /// The English name of this point, e.g. "east-northeast".
+    pub fn name(self) -> &'static str {
+        self.name_in(Language::English)
+    }
+
+    /// The name of this point in the given language.
+    pub fn name_in(self, language: Language) -> &'static str {
+        let names = match language {
+            Language::English => &ENGLISH,
+            Language::French => &FRENCH,
+            Language::German => &GERMAN,
+            Language::Spanish => &SPANISH
+        };
+        names[self.index()]
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+}
+
+impl fmt::Display for CompassPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.abbreviation())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn azimuths_should_round_to_the_nearest_point() {
+        use CompassPoint::*;
+        assert_eq!(CompassPoint::from_azimuth(0.0), N);
+        assert_eq!(CompassPoint::from_azimuth(11.24), N);
+        assert_eq!(CompassPoint::from_azimuth(11.25), NNE);
+        assert_eq!(CompassPoint::from_azimuth(67.5), ENE);
+        assert_eq!(CompassPoint::from_azimuth(348.75), N);
+        assert_eq!(CompassPoint::from_azimuth(348.74), NNW);
+        assert_eq!(CompassPoint::from_azimuth(-90.0), W);
+        assert_eq!(CompassPoint::from_azimuth(450.0), E);
+    }
+
+    #[test]
+    fn points_should_round_trip_through_their_azimuth() {
+        for point in POINTS.iter() {
+            assert_eq!(CompassPoint::from_azimuth(point.azimuth()), *point);
+        }
+    }
+
+    #[test]
+    fn points_should_be_named() {
+        assert_eq!(CompassPoint::ENE.to_string(), "ENE");
+        assert_eq!(CompassPoint::ENE.name(), "east-northeast");
+        assert_eq!(CompassPoint::WSW.name_in(Language::German), "Westsüdwest");
+    }
+
+}
@@ -0,0 +1,412 @@
+//! The sunrise/sunset numerics, free of any dependency on this
+//! crate's types or on external time libraries.
+//!
+//! This is an implementation of the algorithm described by the
+//! United states Naval Observatory
+//! found here: http://edwilliams.org/sunrise_sunset_algorithm.htm
+//!
+//! The functions are generic over the [Real] precision, so that they
+//! can run in `f32` on microcontrollers without a double precision
+//! FPU. Given `f64` arguments, as in the rest of the crate, they
+//! compute in `f64`.
+
+#![allow(non_snake_case)]
+
+use core::fmt;
+use core::ops::{ Add, Div, Mul, Neg, RangeInclusive, Rem, Sub };
+use crate::math::std_or_libm;
+
+/// The years over which the approximation agrees with published
+/// almanacs to within a couple of minutes. The fixed orbital
+/// constants of the algorithm drift outside of this range.
+pub const SUPPORTED_YEARS: RangeInclusive<i32> = 1900..=2100;
+
+/// The outcome of computing a sunrise/sunset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventResult<T> {
+    /// The event occurs at the given time.
+    At(T),
+    /// The sun stays above the zenith of the event all day.
+    PolarDay,
+    /// The sun stays below the zenith of the event all day.
+    PolarNight
+}
+
+impl<T> EventResult<T> {
+
+    /// The time of the event, if it occurs.
+    pub fn at(self) -> Option<T> {
+        match self {
+            EventResult::At(time) => Some(time),
+            _ => None
+        }
+    }
+
+    /// Maps the time of the event, if it occurs.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> EventResult<U> {
+        match self {
+            EventResult::At(time) => EventResult::At(f(time)),
+            EventResult::PolarDay => EventResult::PolarDay,
+            EventResult::PolarNight => EventResult::PolarNight
+        }
+    }
+
+}
+
+/// A floating point type the numerics can compute in, either `f64`
+/// or `f32`.
+///
+/// Single precision puts the events within a few seconds of those
+/// computed in double precision, well inside the accuracy of the
+/// approximation itself. The methods behave as the inherent methods of
+/// the same names.
+pub trait Real:
+    Copy + fmt::Debug + PartialOrd
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+    + Div<Output = Self> + Rem<Output = Self> + Neg<Output = Self>
+{
+    /// Converts a constant of the algorithm to this precision.
+    fn from_f64(value: f64) -> Self;
+    /// Widens the value to `f64`.
+    fn to_f64(self) -> f64;
+    fn to_radians(self) -> Self;
+    fn to_degrees(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+    fn floor(self) -> Self;
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_real {
+    ($t:ident, $($method:ident => $libm:ident),*) => {
+        impl Real for $t {
+
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn to_radians(self) -> Self {
+                $t::to_radians(self)
+            }
+
+            fn to_degrees(self) -> Self {
+                $t::to_degrees(self)
+            }
+
+            std_or_libm!($t, $($method => $libm),*);
+
+        }
+    };
+}
+
+impl_real!(f64, sin => sin, cos => cos, tan => tan, asin => asin, acos => acos, atan => atan, floor => floor, abs => fabs);
+impl_real!(f32, sin => sinf, cos => cosf, tan => tanf, asin => asinf, acos => acosf, atan => atanf, floor => floorf, abs => fabsf);
+
+/// Converts a constant of the algorithm to the precision `F`.
+fn c<F: Real>(value: f64) -> F {
+    F::from_f64(value)
+}
+
+/// Calculates the time of the sunrise (`rising`) or sunset, in hours
+/// after midnight UT, on the `ordinal` day of the year.
+///
+/// `lat` is the latitude in degrees, `lng_hour` the longitude
+/// expressed in hours (degrees / 15), and `zenith` the angle of the
+/// sun from the vertical, in degrees, at which the event occurs.
+///
+/// Returns None if the sun never sets/rises on that day
+/// (ie if you're in the arctic).
+pub fn ut_of_event<F: Real>(ordinal: u32, lat: F, lng_hour: F, zenith: F, rising: bool) -> Option<F> {
+    event_result(ordinal, lat, lng_hour, zenith, rising).at()
+}
+
+/// Like [ut_of_event], but tells whether the sun stays above or below
+/// the zenith when the event doesn't occur.
+pub fn event_result<F: Real>(ordinal: u32, lat: F, lng_hour: F, zenith: F, rising: bool) -> EventResult<F> {
+    Approximation::new(ordinal, lng_hour, rising).event_result(lat, zenith)
+}
+
+/// Like [event_result], but evaluates the position of the sun again at
+/// the time found, until that time changes by less than a second.
+///
+/// The plain approximation places the sun as it is at 6h or 18h local
+/// mean time, which is hours away from the event at high latitudes and
+/// for the twilights, where that costs up to several minutes. Refining
+/// takes that error out, for two to four times the computation. The
+/// fixed constants of the approximation still keep it within half a
+/// minute or so of almanacs, rather than seconds.
+pub fn refined_event_result<F: Real>(ordinal: u32, lat: F, lng_hour: F, zenith: F, rising: bool) -> EventResult<F> {
+    let D: F = c(ordinal as f64);
+    let hour: F = c(if rising { 6.0 } else { 18.0 });
+    let mut t = approximate_time(D, lng_hour, rising);
+    for _ in 0..MAX_REFINEMENTS {
+        let L = true_longitude(mean_anomaly(t));
+        let T = match local_hour_angle(L, lat, zenith, rising) {
+            EventResult::At(H) => local_mean_time(H, right_ascension(L), t),
+            result => return result
+        };
+        // The local mean time of the event, within 12 hours of the
+        // approximate one.
+        let T = hour + rem_euclid(T - hour + c(12.0), c(24.0)) - c(12.0);
+        let refined = D + (T - lng_hour) / c(24.0);
+        let settled = (refined - t).abs() * c(86400.0) < c(1.0);
+        t = refined;
+        if settled {
+            break;
+        }
+    }
+    EventResult::At(rem_euclid((t - D) * c(24.0), c(24.0)))
+}
+
+/// The most times [refined_event_result] evaluates the position of the sun.
+const MAX_REFINEMENTS: usize = 8;
+
+/// The position of the sun at the approximate time of the sunrises
+/// (`rising`) or sunsets of a day, shared by the events at every zenith.
+///
+/// Computing the events of several zeniths from one approximation
+/// saves repeating the mean anomaly, true longitude, right ascension
+/// and declination of the sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Approximation<F = f64> {
+    lng_hour: F,
+    rising: bool,
+    t: F,
+    RA: F,
+    sinDec: F,
+    cosDec: F
+}
+
+impl<F: Real> Approximation<F> {
+
+    /// Approximates the position of the sun on the `ordinal` day of the
+    /// year at the longitude `lng_hour` (degrees / 15).
+    pub fn new(ordinal: u32, lng_hour: F, rising: bool) -> Self {
+        let D = c(ordinal as f64);
+        let t = approximate_time(D, lng_hour, rising);
+        let M = mean_anomaly(t);
+        let L = true_longitude(M);
+        let RA = right_ascension(L);
+        let (sinDec, cosDec) = declination(L);
+        Approximation { lng_hour, rising, t, RA, sinDec, cosDec }
+    }
+
+    /// Like [event_result], for the day, longitude and direction
+    /// of this approximation.
+    pub fn event_result(&self, lat: F, zenith: F) -> EventResult<F> {
+        let (sin_lat, cos_lat) = sin_cos(lat);
+        self.event_result_at(sin_lat, cos_lat, zenith)
+    }
+
+    /// Like [Approximation::event_result], given the sine and cosine of
+    /// the latitude, which the events of a position share.
+    pub(crate) fn event_result_at(&self, sin_lat: F, cos_lat: F, zenith: F) -> EventResult<F> {
+        let cosH = cos_hour_angle(self.sinDec, self.cosDec, sin_lat, cos_lat, zenith);
+        classify_hour_angle(cosH, self.rising).map(|H| {
+            let T = local_mean_time(H, self.RA, self.t);
+            rem_euclid(T - self.lng_hour, c(24.0))
+        })
+    }
+
+}
+
+/// The number of events [event_results_lanes] computes at once.
+pub const LANES: usize = 8;
+
+/// Like [event_result], for [LANES] events at once, each with its own
+/// day, latitude, longitude and zenith, all rising or all setting.
+///
+/// Each step of the algorithm runs over every lane before the next
+/// one, without branching on the lanes, so that the compiler can keep
+/// the lanes in vector registers. The results are those of
+/// [event_result], lane by lane.
+pub fn event_results_lanes<F: Real>(
+    ordinals: [u32; LANES],
+    lats: [F; LANES],
+    lng_hours: [F; LANES],
+    zeniths: [F; LANES],
+    rising: bool
+) -> [EventResult<F>; LANES] {
+    let t = lanes(|i| approximate_time(c(ordinals[i] as f64), lng_hours[i], rising));
+    let L = lanes(|i| true_longitude(mean_anomaly(t[i])));
+    let RA = lanes(|i| right_ascension(L[i]));
+    let cosH = lanes(|i| cos_local_hour_angle(L[i], lats[i], zeniths[i]));
+    // The lanes without an event take a placeholder angle, and are
+    // told apart once all the lanes are computed.
+    let clamped = lanes(|i| if cosH[i] > c(1.0) { c(1.0) } else if cosH[i] < c(-1.0) { c(-1.0) } else { cosH[i] });
+    let H = lanes(|i| hour_angle(clamped[i], rising));
+    let ut = lanes(|i| rem_euclid(local_mean_time(H[i], RA[i], t[i]) - lng_hours[i], c(24.0)));
+    ::core::array::from_fn(|i| {
+        if cosH[i] > c(1.0) {
+            EventResult::PolarNight
+        } else if cosH[i] < c(-1.0) {
+            EventResult::PolarDay
+        } else {
+            EventResult::At(ut[i])
+        }
+    })
+}
+
+fn lanes<F: Real>(f: impl FnMut(usize) -> F) -> [F; LANES] {
+    ::core::array::from_fn(f)
+}
+
+/// Calculates the time of the upper (`upper`) or lower transit of the
+/// sun across the meridian, in hours after midnight UT, on the `ordinal`
+/// day of the year at the longitude `lng_hour` (degrees / 15).
+///
+/// The upper transit is the solar noon; the lower transit is the
+/// solar midnight starting that local day.
+pub fn ut_of_transit<F: Real>(ordinal: u32, lng_hour: F, upper: bool) -> F {
+    let D: F = c(ordinal as f64);
+    let hour: F = c(if upper { 12.0 } else { 0.0 });
+    let t = D + ((hour - lng_hour) / c(24.0));
+    let M = mean_anomaly(t);
+    let L = true_longitude(M);
+    let RA = right_ascension(L);
+    let H = c(if upper { 0.0 } else { 12.0 });
+    let T = local_mean_time(H, RA, t);
+    rem_euclid(T - lng_hour, c(24.0))
+}
+
+fn approximate_time<F: Real>(D: F, lng_hour: F, rising: bool) -> F {
+    let hour: F = c(if rising { 6.0 } else { 18.0 });
+    D + ((hour - lng_hour) / c(24.0))
+}
+
+fn mean_anomaly<F: Real>(t: F) -> F {
+    (c::<F>(0.9856) * t) - c(3.289)
+}
+
+fn true_longitude<F: Real>(M: F) -> F {
+    let L = M + (c::<F>(1.916) * M.to_radians().sin())
+        + (c::<F>(0.020) * (c::<F>(2.0) * M).to_radians().sin()) + c(282.634);
+    rem_euclid(L, c(360.0))
+}
+
+fn right_ascension<F: Real>(L: F) -> F {
+    let mut RA = (c::<F>(0.91764) * L.to_radians().tan()).atan().to_degrees();
+    RA = rem_euclid(RA, c(360.0));
+    let LQuadrant = (L / c(90.0)).floor() * c(90.0);
+    let RAQuadrant = (RA / c(90.0)).floor() * c(90.0);
+    (RA + (LQuadrant - RAQuadrant)) / c(15.0)
+}
+
+fn local_hour_angle<F: Real>(L: F, lat: F, zenith: F, rising: bool) -> EventResult<F> {
+    classify_hour_angle(cos_local_hour_angle(L, lat, zenith), rising)
+}
+
+/// The local hour angle of the sun, in hours, from its cosine, or
+/// whether the sun stays above or below the zenith.
+fn classify_hour_angle<F: Real>(cosH: F, rising: bool) -> EventResult<F> {
+    if cosH > c(1.0) {
+        // The sun never rises on this location on the specified date.
+        return EventResult::PolarNight;
+    }
+    if cosH < c(-1.0) {
+        // The sun never sets on this location on the specified date.
+        return EventResult::PolarDay;
+    }
+    EventResult::At(hour_angle(cosH, rising))
+}
+
+/// The cosine of the local hour angle of the sun at `zenith`, beyond
+/// ±1 when the sun doesn't reach the zenith.
+fn cos_local_hour_angle<F: Real>(L: F, lat: F, zenith: F) -> F {
+    let (sinDec, cosDec) = declination(L);
+    let (sin_lat, cos_lat) = sin_cos(lat);
+    cos_hour_angle(sinDec, cosDec, sin_lat, cos_lat, zenith)
+}
+
+/// The sine and cosine of `angle`, in degrees.
+pub(crate) fn sin_cos<F: Real>(angle: F) -> (F, F) {
+    let angle = angle.to_radians();
+    (angle.sin(), angle.cos())
+}
+
+/// The sine and cosine of the declination of the sun at the true
+/// longitude `L`, which the events at every latitude and zenith share.
+fn declination<F: Real>(L: F) -> (F, F) {
+    let sinDec = c::<F>(0.39782) * L.to_radians().sin();
+    (sinDec, sinDec.asin().cos())
+}
+
+fn cos_hour_angle<F: Real>(sinDec: F, cosDec: F, sin_lat: F, cos_lat: F, zenith: F) -> F {
+    let z = zenith.to_radians();
+    (z.cos() - (sinDec * sin_lat)) / (cosDec * cos_lat)
+}
+
+/// The local hour angle of the sun, in hours, from its cosine.
+fn hour_angle<F: Real>(cosH: F, rising: bool) -> F {
+    let H = if rising {
+        c::<F>(360.0) - cosH.acos().to_degrees()
+    } else {
+        cosH.acos().to_degrees()
+    };
+    H / c(15.0)
+}
+
+fn local_mean_time<F: Real>(H: F, RA: F, t: F) -> F {
+    H + RA - (c::<F>(0.06571) * t) - c(6.622)
+}
+
+pub(crate) fn rem_euclid<F: Real>(lhs: F, rhs: F) -> F {
+    let r = lhs % rhs;
+    if r < c(0.0) {
+        r + rhs.abs()
+    } else {
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn single_precision_should_agree_with_double_precision() {
+        for &(lat, lng) in &[(51.4810066, 0.0081805), (-33.8688, 151.2093), (64.1466, -21.9426)] {
+            for ordinal in (1..=365).step_by(7) {
+                for &rising in &[true, false] {
+                    let double = event_result(ordinal, lat, lng / 15.0, 90.833, rising);
+                    let single = event_result(ordinal, lat as f32, lng as f32 / 15.0, 90.833, rising);
+                    match (double, single) {
+                        (EventResult::At(double), EventResult::At(single)) => {
+                            let hours = rem_euclid(single as f64 - double + 12.0, 24.0) - 12.0;
+                            assert!(hours.abs() * 3600.0 < 5.0, "{} {} {}", lat, ordinal, hours * 3600.0);
+                        },
+                        (double, single) => assert_eq!(double.map(|_| ()), single.map(|_| ()))
+                    }
+                }
+            }
+            let single = ut_of_transit(172, lng as f32 / 15.0, true);
+            assert!((single as f64 - ut_of_transit(172, lng / 15.0, true)).abs() * 3600.0 < 5.0);
+        }
+    }
+
+    #[test]
+    fn lanes_should_match_the_scalar_results() {
+        let lats = [51.4810066, -33.8688, 69.6492, -77.85, 0.0, 78.2232, 45.0, -89.0];
+        let lng_hours = [0.0005, 10.08, 1.26, 11.11, -5.0, 1.04, -8.0, 3.3];
+        for &ordinal in &[1, 80, 172, 264, 355] {
+            for &(zenith, rising) in &[(90.833, true), (96.0, false), (108.0, true)] {
+                let ordinals = [ordinal, ordinal + 1, ordinal, ordinal + 2, ordinal, ordinal, ordinal + 3, ordinal];
+                let results = event_results_lanes(ordinals, lats, lng_hours, [zenith; LANES], rising);
+                for i in 0..LANES {
+                    assert_eq!(results[i], event_result(ordinals[i], lats[i], lng_hours[i], zenith, rising));
+                }
+            }
+        }
+    }
+
+}
@@ -0,0 +1,399 @@
+//! This module computes how much of a span of time the sun
+//! spends above the horizon, and the lengths of days and nights.
+
+use chrono::{ DateTime, Duration, NaiveDate, Utc };
+use std::ops::{ Range, RangeInclusive };
+use super::adapter::chrono::{ days_of_year, unix_day };
+use super::core::EventResult;
+use super::epoch::{ event_result_unix, SECS_IN_DAY };
+use super::event::{ Event, SunEvent, Zenith };
+use super::pos::GlobalPosition;
+
+/// Returns how much of `range` is daylight at `pos`, that is how long
+/// the sun spends above the horizon (the official zenith).
+///
+/// Spans covering several days and polar days/nights are handled.
+pub fn daylight_within(range: Range<DateTime<Utc>>, pos: &GlobalPosition) -> Duration {
+    daylight_within_zenith(range, pos, Zenith::Official)
+}
+
+/// Returns how much of `range` the sun spends above `zenith` at `pos`,
+/// e.g. [Zenith::Civil] for daylight including civil twilight.
+pub fn daylight_within_zenith(range: Range<DateTime<Utc>>, pos: &GlobalPosition, zenith: Zenith) -> Duration {
+    if range.end <= range.start {
+        return Duration::zero();
+    }
+    let first_day = unix_day(range.start.date_naive()) - 1;
+    let last_day = unix_day(range.end.date_naive()) + 1;
+    light_between(range.start.timestamp(), range.end.timestamp(), first_day, last_day, pos, zenith)
+}
+
+/// Returns the length of the day on the local mean `date` at `pos`,
+/// the time from sunrise to sunset: all of it under the midnight sun
+/// and none during the polar night. Days on which the sun only rises
+/// or only sets count the daylight up to midnight.
+pub fn day_length(date: NaiveDate, pos: &GlobalPosition) -> Duration {
+    day_length_zenith(date, pos, Zenith::Official)
+}
+
+/// Like [day_length], for the time the sun spends above `zenith`,
+/// e.g. [Zenith::Civil] for the day including civil twilight.
+pub fn day_length_zenith(date: NaiveDate, pos: &GlobalPosition, zenith: Zenith) -> Duration {
+    let day = unix_day(date);
+    let start = day * SECS_IN_DAY - (pos.lng_hour() * 3600.0) as i64;
+    light_between(start, start + SECS_IN_DAY, day - 1, day + 1, pos, zenith)
+}
+
+/// Whether the sun stays above the horizon all of the local mean
+/// `date` at `pos`, under the midnight sun.
+pub fn is_polar_day(date: NaiveDate, pos: &GlobalPosition) -> bool {
+    is_polar_day_zenith(date, pos, Zenith::Official)
+}
+
+/// Like [is_polar_day], for the sun staying above `zenith`.
+pub fn is_polar_day_zenith(date: NaiveDate, pos: &GlobalPosition, zenith: Zenith) -> bool {
+    day_length_zenith(date, pos, zenith) == Duration::days(1)
+}
+
+/// Whether the sun stays below the horizon all of the local mean
+/// `date` at `pos`, during the polar night.
+pub fn is_polar_night(date: NaiveDate, pos: &GlobalPosition) -> bool {
+    is_polar_night_zenith(date, pos, Zenith::Official)
+}
+
+/// Like [is_polar_night], for the sun staying below `zenith`.
+pub fn is_polar_night_zenith(date: NaiveDate, pos: &GlobalPosition, zenith: Zenith) -> bool {
+    day_length_zenith(date, pos, zenith) == Duration::zero()
+}
+
+/// Returns the length of the night following the local mean `date` at
+/// `pos`, that is how long the sun stays below the horizon from noon
+/// on `date` to noon the next day.
+pub fn night_length(date: NaiveDate, pos: &GlobalPosition) -> Duration {
+    night_length_zenith(date, pos, Zenith::Official)
+}
+
+/// Like [night_length], for the time the sun stays below `zenith`.
+pub fn night_length_zenith(date: NaiveDate, pos: &GlobalPosition, zenith: Zenith) -> Duration {
+    let day = unix_day(date);
+    let noon = day * SECS_IN_DAY + SECS_IN_DAY / 2 - (pos.lng_hour() * 3600.0) as i64;
+    Duration::seconds(SECS_IN_DAY) - light_between(noon, noon + SECS_IN_DAY, day - 1, day + 2, pos, zenith)
+}
+
+/// Returns how long the night following the local mean `date` at `pos`
+/// is fully dark, with the sun below the astronomical zenith.
+pub fn astronomical_darkness_duration(date: NaiveDate, pos: &GlobalPosition) -> Duration {
+    night_length_zenith(date, pos, Zenith::Astronomical)
+}
+
+/// The length of a day and how much it changed from the previous day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaylightChange {
+    /// The date, in the local mean time of the position.
+    pub date: NaiveDate,
+    /// The length of the day, as given by [day_length].
+    pub length: Duration,
+    /// The length of the day minus that of the previous day.
+    pub change: Duration
+}
+
+/// An iterator that yields the change in the length of each day
+/// from a start date on.
+///
+/// Around polar days and nights, the day length reaches all or none
+/// of the day progressively, as the sun rises or sets ever closer
+/// to midnight, and then stays constant.
+#[derive(Debug, Clone)]
+pub struct DaylightChanges {
+    pos: GlobalPosition,
+    date: NaiveDate,
+    previous: Duration
+}
+
+impl DaylightChanges {
+
+    /// List the changes in day length at `position` from the local
+    /// mean `start_date` on.
+    pub fn starting_from(start_date: NaiveDate, position: GlobalPosition) -> Self {
+        let previous = start_date.pred_opt()
+            .map(|date| day_length(date, &position))
+            .unwrap_or_else(|| day_length(start_date, &position));
+        DaylightChanges { pos: position, date: start_date, previous }
+    }
+
+}
+
+impl Iterator for DaylightChanges {
+
+    type Item = DaylightChange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let date = self.date;
+        let length = day_length(date, &self.pos);
+        let change = length - self.previous;
+        self.date = date.succ_opt()?;
+        self.previous = length;
+        Some(DaylightChange { date, length, change })
+    }
+
+}
+
+/// The longest or shortest days of a year.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtremeDays {
+    /// The consecutive local mean dates reaching the extreme length,
+    /// usually a single date but a whole period under the midnight sun
+    /// or during the polar night.
+    pub dates: RangeInclusive<NaiveDate>,
+    /// The length of each of these days, as given by [day_length].
+    pub length: Duration
+}
+
+/// Finds the longest day of `year` at `pos`.
+///
+/// Where the sun doesn't set for a while, this is the longest period
+/// without a sunset within the year.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn longest_day(year: i32, pos: &GlobalPosition) -> ExtremeDays {
+    extreme_days(year, pos, |length, extreme| length > extreme)
+}
+
+/// Finds the shortest day of `year` at `pos`.
+///
+/// Where the sun doesn't rise for a while, this is the longest period
+/// without a sunrise within the year; the polar night of the north
+/// spans two years, so its part in either January or December is given.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn shortest_day(year: i32, pos: &GlobalPosition) -> ExtremeDays {
+    extreme_days(year, pos, |length, extreme| length < extreme)
+}
+
+/// Finds the longest run of days of `year` whose length is the most
+/// `extreme`, as told by `beats`.
+fn extreme_days<F: Fn(Duration, Duration) -> bool>(year: i32, pos: &GlobalPosition, beats: F) -> ExtremeDays {
+    let days: Vec<_> = days_of_year(year)
+        .map(|date| (date, day_length(date, pos)))
+        .collect();
+    let length = days.iter()
+        .map(|(_, length)| *length)
+        .fold(days[0].1, |extreme, length| if beats(length, extreme) { length } else { extreme });
+    // The longest run, then the current one, as (first, last) dates.
+    let mut best: Option<(NaiveDate, NaiveDate)> = None;
+    let mut run: Option<(NaiveDate, NaiveDate)> = None;
+    for (date, day) in days {
+        run = match run {
+            _ if day != length => None,
+            Some((start, _)) => Some((start, date)),
+            None => Some((date, date))
+        };
+        if let Some((start, end)) = run {
+            match best {
+                Some((best_start, best_end)) if best_end - best_start >= end - start => {},
+                _ => best = Some((start, end))
+            }
+        }
+    }
+    let (start, end) = best.expect("some day should reach the extreme length");
+    ExtremeDays { dates: start..=end, length }
+}
+
+/// Returns how long the sun spends above `zenith` between the Unix
+/// seconds `start` and `end`, which fall within the local days
+/// `first_day..=last_day`.
+fn light_between(start: i64, end: i64, first_day: i64, last_day: i64, pos: &GlobalPosition, zenith: Zenith) -> Duration {
+    let secs: i64 = light_intervals(first_day, last_day, pos, zenith)
+        .into_iter()
+        .map(|(from, to)| (to.min(end) - from.max(start)).max(0))
+        .sum();
+    Duration::seconds(secs)
+}
+
+/// Returns the disjoint, sorted intervals (in Unix seconds) during which
+/// the sun is above `zenith` on the local days `first_day..=last_day`
+/// (counted in days since the Unix epoch).
+pub(crate) fn light_intervals(first_day: i64, last_day: i64, pos: &GlobalPosition, zenith: Zenith) -> Vec<(i64, i64)> {
+    let lng_offset = (pos.lng_hour() * 3600.0) as i64;
+    let mut intervals: Vec<(i64, i64)> = vec![];
+    for day in first_day..=last_day {
+        let day_start = day * SECS_IN_DAY - lng_offset;
+        let day_end = day_start + SECS_IN_DAY;
+        let rise = event_result_unix(day, pos, SunEvent::new(zenith, Event::Sunrise));
+        let set = event_result_unix(day, pos, SunEvent::new(zenith, Event::Sunset));
+        let interval = match (rise, set) {
+            (EventResult::At(rise), EventResult::At(set)) => (rise, set),
+            (EventResult::At(rise), EventResult::PolarDay) => (rise, day_end),
+            (EventResult::PolarDay, EventResult::At(set)) => (day_start, set),
+            (EventResult::PolarDay, EventResult::PolarDay) => (day_start, day_end),
+            // On the days into and out of the polar night, the sunrise and
+            // the sunset are approximated at different times of day, so
+            // that only one of them may be found. The sun then stays up
+            // as long after the solar noon as it rose before it, and the
+            // other way around.
+            (EventResult::At(rise), EventResult::PolarNight) => (rise, 2 * noon_of(day, pos) - rise),
+            (EventResult::PolarNight, EventResult::At(set)) => (2 * noon_of(day, pos) - set, set),
+            _ => continue
+        };
+        if interval.0 >= interval.1 {
+            continue;
+        }
+        match intervals.last_mut() {
+            Some(last) if interval.0 <= last.1 => last.1 = last.1.max(interval.1),
+            _ => intervals.push(interval)
+        }
+    }
+    intervals
+}
+
+/// The solar noon of the local day `day`, in Unix seconds.
+fn noon_of(day: i64, pos: &GlobalPosition) -> i64 {
+    event_result_unix(day, pos, SunEvent::SOLAR_NOON)
+        .at()
+        .expect("the sun should transit every day")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{ Datelike, TimeZone };
+    use crate::adapter::chrono::time_of_event_on;
+
+    #[test]
+    fn daylight_should_span_multiple_days() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2019, 6, 20, 0, 0, 0).unwrap();
+        let one_day = daylight_within(start..start + Duration::days(1), &pos);
+        let three_days = daylight_within(start..start + Duration::days(3), &pos);
+        // Around 16h38m of daylight at the summer solstice in London.
+        assert!((one_day.num_minutes() - 998).abs() < 5, "{}", one_day);
+        assert!((three_days - one_day * 3).num_minutes().abs() < 5);
+    }
+
+    #[test]
+    fn day_length_should_count_the_days_into_and_out_of_the_polar_night() {
+        // Only the sunrise is found on 2023-12-09 at 68°N, and only the
+        // sunset on 2024-01-05 at 68.2°N.
+        for &(lat, date) in &[(68.0, (2023, 12, 9)), (68.2, (2024, 1, 5))] {
+            let pos = GlobalPosition::at(lat, 18.9553);
+            let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+            let length = day_length(date, &pos);
+            let before = day_length(date.pred_opt().unwrap(), &pos);
+            let after = day_length(date.succ_opt().unwrap(), &pos);
+            assert!(length > Duration::zero() && length < Duration::hours(2), "{} {}", date, length);
+            assert!(length < before.max(after) + Duration::minutes(30), "{} {} {} {}", date, before, length, after);
+        }
+    }
+
+    #[test]
+    fn day_length_should_span_sunrise_to_sunset() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let tz = pos.lng_timezone();
+        let sunrise = time_of_event_on(date, &tz, &pos, SunEvent::SUNRISE).unwrap();
+        let sunset = time_of_event_on(date, &tz, &pos, SunEvent::SUNSET).unwrap();
+        assert_eq!(day_length(date, &pos), sunset - sunrise);
+        assert!(day_length_zenith(date, &pos, Zenith::Civil) > sunset - sunrise);
+    }
+
+    #[test]
+    fn day_length_should_handle_polar_conditions() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let summer = NaiveDate::from_ymd_opt(2019, 6, 21).unwrap();
+        let winter = NaiveDate::from_ymd_opt(2019, 12, 21).unwrap();
+        assert_eq!(day_length(summer, &pos), Duration::days(1));
+        assert_eq!(day_length(winter, &pos), Duration::zero());
+    }
+
+    #[test]
+    fn polar_predicates_should_depend_on_the_zenith() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let summer = NaiveDate::from_ymd_opt(2019, 6, 21).unwrap();
+        let winter = NaiveDate::from_ymd_opt(2019, 12, 21).unwrap();
+        assert!(is_polar_day(summer, &pos));
+        assert!(!is_polar_night(summer, &pos));
+        assert!(is_polar_night(winter, &pos));
+        assert!(!is_polar_day(winter, &pos));
+        // The sun doesn't reach the astronomical zenith in Tromsø
+        // in midwinter, so twilight comes every day.
+        let tromso = GlobalPosition::at(69.6492, 18.9553);
+        assert!(is_polar_night(winter, &tromso));
+        assert!(!is_polar_night_zenith(winter, &tromso, Zenith::Astronomical));
+        assert!(!is_polar_day(NaiveDate::from_ymd_opt(2019, 3, 21).unwrap(), &tromso));
+    }
+
+    #[test]
+    fn night_length_should_span_sunset_to_sunrise() {
+        let pos = GlobalPosition::at(40.60710285372043, -111.85515699873065);
+        let date = NaiveDate::from_ymd_opt(2020, 3, 1).unwrap();
+        let tz = pos.lng_timezone();
+        let sunset = time_of_event_on(date, &tz, &pos, SunEvent::SUNSET).unwrap();
+        let sunrise = time_of_event_on(date.succ_opt().unwrap(), &tz, &pos, SunEvent::SUNRISE).unwrap();
+        assert_eq!(night_length(date, &pos), sunrise - sunset);
+        let darkness = astronomical_darkness_duration(date, &pos);
+        assert!(darkness < sunrise - sunset && darkness > Duration::hours(8), "{}", darkness);
+    }
+
+    #[test]
+    fn darkness_should_vanish_in_the_summer_far_north() {
+        // The sun doesn't go below the astronomical zenith
+        // in Edinburgh around the summer solstice.
+        let pos = GlobalPosition::at(55.9533, -3.1883);
+        let date = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        assert_eq!(astronomical_darkness_duration(date, &pos), Duration::zero());
+        assert!(night_length(date, &pos) > Duration::hours(6));
+    }
+
+    #[test]
+    fn daylight_changes_should_add_up_across_the_polar_night() {
+        // The sun sets for the last time in Tromsø in late November.
+        let pos = GlobalPosition::at(69.6492, 18.9553);
+        let start = NaiveDate::from_ymd_opt(2020, 11, 1).unwrap();
+        let changes: Vec<_> = DaylightChanges::starting_from(start, pos.clone()).take(60).collect();
+        let before = day_length(start.pred_opt().unwrap(), &pos);
+        let total = changes.iter().fold(Duration::zero(), |total, change| total + change.change);
+        assert_eq!(before + total, changes.last().unwrap().length);
+        assert!(changes.iter().all(|change| change.change <= Duration::zero()));
+        assert!(changes.iter().any(|change| change.length == Duration::zero()));
+        assert_eq!(changes[0].length, day_length(start, &pos));
+    }
+
+    #[test]
+    fn longest_day_should_fall_around_the_solstice() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let longest = longest_day(2021, &pos);
+        let solstice = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        assert!((*longest.dates.start() - solstice).num_days().abs() <= 2, "{:?}", longest);
+        assert!((longest.length.num_minutes() - 998).abs() < 5, "{}", longest.length);
+        let shortest = shortest_day(2021, &pos);
+        let december = NaiveDate::from_ymd_opt(2021, 12, 21).unwrap();
+        assert!((*shortest.dates.start() - december).num_days().abs() <= 2, "{:?}", shortest);
+    }
+
+    #[test]
+    fn extreme_days_should_span_polar_periods() {
+        // The midnight sun lasts from late April to late August in
+        // Longyearbyen.
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let longest = longest_day(2021, &pos);
+        assert_eq!(longest.length, Duration::days(1));
+        assert_eq!(longest.dates.start().month(), 4);
+        assert_eq!(longest.dates.end().month(), 8);
+        let shortest = shortest_day(2021, &pos);
+        assert_eq!(shortest.length, Duration::zero());
+        assert!(shortest.dates.contains(&NaiveDate::from_ymd_opt(2021, 12, 21).unwrap()));
+    }
+
+    #[test]
+    fn daylight_should_handle_polar_conditions() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let summer = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap();
+        let winter = Utc.with_ymd_and_hms(2019, 12, 21, 0, 0, 0).unwrap();
+        let week = Duration::weeks(1);
+        assert_eq!(daylight_within(summer..summer + week, &pos), week);
+        assert_eq!(daylight_within(winter..winter + week, &pos), Duration::zero());
+    }
+
+}
@@ -0,0 +1,317 @@
+//! This module provides a time-library agnostic interface to the
+//! sunrise/sunset computations, operating on Unix seconds and
+//! Julian days.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use super::algorithm::ut_of_events;
+use super::algorithm::{ refined_ut_of_event, ut_of_event };
+use super::core::{ self, EventResult, SUPPORTED_YEARS };
+use super::error::{ check_position, check_year, CircadiaError, DateOutOfRange };
+use super::event::{ Crossing, Event, SunEvent };
+use super::pos::GlobalPosition;
+use crate::math::Float;
+
+/// The number of seconds in a day.
+pub const SECS_IN_DAY: i64 = 86_400;
+
+/// The Julian day of the Unix epoch (1970-01-01T00:00:00Z).
+pub const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+
+const SECS_IN_HOUR: f64 = 3600.0;
+
+/// Converts a Unix timestamp (in seconds) to a Julian day.
+pub fn julian_day_from_unix(secs: f64) -> f64 {
+    UNIX_EPOCH_JULIAN_DAY + secs / SECS_IN_DAY as f64
+}
+
+/// Converts a Julian day to a Unix timestamp (in seconds).
+pub fn unix_from_julian_day(jd: f64) -> f64 {
+    (jd - UNIX_EPOCH_JULIAN_DAY) * SECS_IN_DAY as f64
+}
+
+/// Calculates the time of the sunrise/sunset on the UTC day `day`,
+/// counted in days since the Unix epoch, at the given position on the globe.
+///
+/// Returns the Unix timestamp (in seconds) of the event,
+/// or None if the sun never sets/rises on that day
+/// (ie if you're in the arctic).
+pub fn time_of_event_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> Option<i64> {
+    event_result_unix(day, pos, event).at()
+}
+
+/// Like [time_of_event_unix], but tells whether the sun stays above
+/// or below the zenith of the event when it doesn't occur.
+pub fn event_result_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+    ut_of_event(ordinal_of(day), pos, event).map(|ut| unix_of_event(day, pos, event, ut))
+}
+
+/// Like [event_result_unix], evaluating the position of the sun again
+/// at the time of the sunrises and sunsets, as with
+/// [refined_event_result](core::refined_event_result).
+pub fn refined_event_result_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+    refined_ut_of_event(ordinal_of(day), pos, event).map(|ut| unix_of_event(day, pos, event, ut))
+}
+
+/// Like [event_result_unix], for several events of the same day,
+/// in the order of `events`.
+///
+/// This shares the computations common to the events of a day,
+/// which makes it faster than computing each event on its own.
+#[cfg(feature = "alloc")]
+pub fn event_results_unix(day: i64, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<EventResult<i64>> {
+    ut_of_events(ordinal_of(day), pos, events)
+        .into_iter()
+        .zip(events)
+        .map(|(result, event)| result.map(|ut| unix_of_event(day, pos, *event, ut)))
+        .collect()
+}
+
+/// The day of the year of `day`, counted in days since the Unix epoch.
+pub(crate) fn ordinal_of(day: i64) -> u32 {
+    let (year, _, _) = civil_from_days(day);
+    (day - days_from_civil(year, 1, 1) + 1) as u32
+}
+
+/// The Unix timestamp of `event` occurring at `ut` hours for the
+/// local `day` at `pos`.
+pub(crate) fn unix_of_event(day: i64, pos: &GlobalPosition, event: SunEvent, ut: f64) -> i64 {
+    let day = if event.is_transit() {
+        // The transit falls within minutes of local mean noon
+        // or midnight.
+        let hour = if event.event == Event::SolarNoon { 12.0 } else { 0.0 };
+        let expected = hour - pos.lng_hour();
+        day + Float::round((expected - ut) / 24.0) as i64
+    } else {
+        day_of_crossing(day, pos, ut, event.is_sunrise())
+    };
+    day * SECS_IN_DAY + (ut * SECS_IN_HOUR) as i64
+}
+
+/// Calculates the time at which the center of the sun crosses
+/// `altitude` degrees above the horizon on the UTC day `day`, counted
+/// in days since the Unix epoch, at the given position on the globe.
+///
+/// Returns the Unix timestamp (in seconds) of the crossing, or None
+/// if the sun stays above or below that altitude all day.
+pub fn time_at_altitude_unix(day: i64, pos: &GlobalPosition, altitude: f64, crossing: Crossing) -> Option<i64> {
+    let rising = crossing == Crossing::Rising;
+    core::ut_of_event(ordinal_of(day), pos.lat(), pos.lng_hour(), 90.0 - altitude, rising).map(|ut| {
+        let day = day_of_crossing(day, pos, ut, rising);
+        day * SECS_IN_DAY + (ut * SECS_IN_HOUR) as i64
+    })
+}
+
+/// The UTC day on which the sun crosses a zenith at `ut` hours, for
+/// the local `day` at `pos`.
+fn day_of_crossing(day: i64, pos: &GlobalPosition, ut: f64, rising: bool) -> i64 {
+    let should_be_yesterday = pos.lng_hour() > 0.0 && ut > 12.0 && rising;
+    let should_be_tomorrow = pos.lng_hour() < 0.0 && ut < 12.0 && !rising;
+    if should_be_yesterday {
+        day - 1
+    } else if should_be_tomorrow {
+        day + 1
+    } else {
+        day
+    }
+}
+
+/// Like [time_of_event_unix], but returns an error when `day` falls
+/// outside of the [supported years](crate::core::SUPPORTED_YEARS)
+/// instead of silently returning a degraded result.
+pub fn try_time_of_event_unix(
+    day: i64,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Result<Option<i64>, DateOutOfRange> {
+    let (year, _, _) = civil_from_days(day);
+    check_year(year, SUPPORTED_YEARS)?;
+    Ok(time_of_event_unix(day, pos, event))
+}
+
+/// Like [time_of_event_unix], but returns an error when `pos` doesn't
+/// lie on the globe, when `day` falls outside of the
+/// [supported years](crate::core::SUPPORTED_YEARS), or when the
+/// event doesn't happen that day.
+pub fn checked_time_of_event_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> Result<i64, CircadiaError> {
+    check_position(pos)?;
+    try_time_of_event_unix(day, pos, event)?.ok_or(CircadiaError::NoEvent(event))
+}
+
+/// Calculates the time of the sunrise/sunset on the UTC day containing
+/// the Julian day `jd` at the given position on the globe.
+///
+/// Returns the Julian day of the event, or None if the sun never
+/// sets/rises on that day (ie if you're in the arctic).
+pub fn time_of_event_julian(jd: f64, pos: &GlobalPosition, event: SunEvent) -> Option<f64> {
+    let day = Float::floor(unix_from_julian_day(jd) / SECS_IN_DAY as f64) as i64;
+    time_of_event_unix(day, pos, event)
+        .map(|secs| julian_day_from_unix(secs as f64))
+}
+
+/// Returns the number of days since the Unix epoch of the given
+/// proleptic Gregorian date.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Returns the proleptic Gregorian (year, month, day) of the given
+/// number of days since the Unix epoch.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y as i32, m, d)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::pos::Atmosphere;
+
+    #[test]
+    fn civil_dates_should_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in -800_000..800_000 {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn julian_days_should_round_trip() {
+        assert_eq!(julian_day_from_unix(0.0), UNIX_EPOCH_JULIAN_DAY);
+        // J2000.0
+        assert_eq!(julian_day_from_unix(946_728_000.0), 2_451_545.0);
+        assert_eq!(unix_from_julian_day(2_451_545.0), 946_728_000.0);
+    }
+
+    #[test]
+    fn dates_outside_the_supported_years_should_be_rejected() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let day = days_from_civil(3000, 1, 1);
+        let err = try_time_of_event_unix(day, &pos, SunEvent::SUNRISE).unwrap_err();
+        assert_eq!(err.year, 3000);
+        let day = days_from_civil(2100, 12, 31);
+        assert!(try_time_of_event_unix(day, &pos, SunEvent::SUNRISE).is_ok());
+    }
+
+    #[test]
+    fn checked_events_should_report_each_failure() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let day = days_from_civil(2019, 6, 21);
+        let err = checked_time_of_event_unix(day, &pos, SunEvent::SUNSET).unwrap_err();
+        assert_eq!(err, CircadiaError::NoEvent(SunEvent::SUNSET));
+        let err = checked_time_of_event_unix(days_from_civil(3000, 1, 1), &pos, SunEvent::DAWN).unwrap_err();
+        assert!(matches!(err, CircadiaError::DateOutOfRange(DateOutOfRange { year: 3000, .. })));
+        let nowhere = GlobalPosition::at(f64::NAN, 0.0);
+        let err = checked_time_of_event_unix(day, &nowhere, SunEvent::DAWN).unwrap_err();
+        assert!(matches!(err, CircadiaError::InvalidPosition { .. }));
+        assert!(checked_time_of_event_unix(day, &pos, SunEvent::SOLAR_NOON).is_ok());
+    }
+
+    #[test]
+    fn sun_should_rise_earlier_from_a_mountain() {
+        let pos = GlobalPosition::at(46.5580, 7.8354);
+        let summit = pos.clone().with_elevation(3000.0);
+        let day = days_from_civil(2021, 6, 21);
+        let sunrise = time_of_event_unix(day, &pos, SunEvent::SUNRISE).unwrap();
+        let early = time_of_event_unix(day, &summit, SunEvent::SUNRISE).unwrap();
+        assert!(sunrise - early > 5 * 60, "{}", sunrise - early);
+        let dawn = time_of_event_unix(day, &pos, SunEvent::DAWN);
+        assert_eq!(time_of_event_unix(day, &summit, SunEvent::DAWN), dawn);
+    }
+
+    #[test]
+    fn cold_dense_air_should_bring_sunrise_forward() {
+        let pos = GlobalPosition::at(64.1466, -21.9426);
+        let day = days_from_civil(2021, 1, 15);
+        let sunrise = time_of_event_unix(day, &pos, SunEvent::SUNRISE).unwrap();
+        let standard = pos.clone().with_atmosphere(Atmosphere::STANDARD);
+        assert_eq!(time_of_event_unix(day, &standard, SunEvent::SUNRISE), Some(sunrise));
+        let cold = pos.with_atmosphere(Atmosphere { pressure_hpa: 1040.0, temperature_c: -25.0 });
+        let early = time_of_event_unix(day, &cold, SunEvent::SUNRISE).unwrap();
+        assert!(sunrise - early > 60, "{}", sunrise - early);
+    }
+
+    #[test]
+    fn sunrise_should_fall_on_the_requested_day() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let day = days_from_civil(2019, 6, 21);
+        let sunrise = time_of_event_unix(day, &pos, SunEvent::SUNRISE).unwrap();
+        assert_eq!(sunrise.div_euclid(SECS_IN_DAY), day);
+        // Roughly 03:43 UTC in Greenwich at the summer solstice.
+        let hour = sunrise.rem_euclid(SECS_IN_DAY) as f64 / SECS_IN_HOUR;
+        assert!((hour - 3.72).abs() < 0.1, "{}", hour);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn events_of_a_day_should_match_those_computed_alone() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let events = [
+            SunEvent::SUNSET, SunEvent::DAWN, SunEvent::SOLAR_NOON,
+            SunEvent::SUNRISE, SunEvent::DUSK, SunEvent::SOLAR_MIDNIGHT
+        ];
+        for &day in &[days_from_civil(2019, 6, 21), days_from_civil(2020, 1, 1)] {
+            let results = event_results_unix(day, &pos, &events);
+            for (event, result) in events.iter().zip(results) {
+                assert_eq!(result, event_result_unix(day, &pos, *event));
+            }
+        }
+    }
+
+    #[test]
+    fn solar_noon_should_fall_on_the_local_date() {
+        let day = days_from_civil(2019, 6, 21);
+        // Roughly 12:02 UTC in Greenwich at the summer solstice.
+        let greenwich = GlobalPosition::at(51.4810066, 0.0081805);
+        let noon = time_of_event_unix(day, &greenwich, SunEvent::SOLAR_NOON).unwrap();
+        assert_eq!(noon.div_euclid(SECS_IN_DAY), day);
+        let hour = noon.rem_euclid(SECS_IN_DAY) as f64 / SECS_IN_HOUR;
+        assert!((hour - 12.03).abs() < 0.02, "{}", hour);
+        // Near the date line, noon may fall on the neighbouring UTC days
+        // but always on the requested local mean date.
+        for &day in &[day, days_from_civil(2019, 11, 3)] {
+            for &lng in &[179.9, 90.0, -90.0, -179.9] {
+                let pos = GlobalPosition::at(0.0, lng);
+                let noon = time_of_event_unix(day, &pos, SunEvent::SOLAR_NOON).unwrap();
+                let local = noon + (lng * 240.0) as i64;
+                assert_eq!(local.div_euclid(SECS_IN_DAY), day, "{}", lng);
+            }
+        }
+        for &day in &[day, days_from_civil(2019, 11, 3)] {
+            for &lng in &[179.9, 90.0, -90.0, -179.9] {
+                let pos = GlobalPosition::at(0.0, lng);
+                let noon = time_of_event_unix(day, &pos, SunEvent::SOLAR_NOON).unwrap();
+                let midnight = time_of_event_unix(day, &pos, SunEvent::SOLAR_MIDNIGHT).unwrap();
+                let gap = noon - midnight - SECS_IN_DAY / 2;
+                assert!(gap.abs() < 60, "{} {}", lng, gap);
+            }
+        }
+        // The sun transits even during the polar night.
+        let pole = GlobalPosition::at(89.0, 0.0);
+        assert!(time_of_event_unix(days_from_civil(2019, 12, 21), &pole, SunEvent::SOLAR_NOON).is_some());
+    }
+
+}
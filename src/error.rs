@@ -0,0 +1,155 @@
+//! Error types returned by the fallible APIs of this crate.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::error::Error;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// Returned when a date falls outside the range of years over which
+/// the algorithm is known to be accurate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateOutOfRange {
+    /// The requested year.
+    pub year: i32,
+    /// The years supported by the algorithm.
+    pub supported: RangeInclusive<i32>
+}
+
+impl fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "year {} is outside the supported range {}..={}",
+            self.year,
+            self.supported.start(),
+            self.supported.end()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for DateOutOfRange {}
+
+/// Returned when constructing a [GlobalPosition] from coordinates
+/// that don't lie on the globe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionError {
+    /// The latitude isn't a finite number of degrees within [-90, 90].
+    InvalidLatitude(f64),
+    /// The longitude isn't a finite number of degrees within [-180, 180].
+    InvalidLongitude(f64)
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::InvalidLatitude(lat) => write!(f, "latitude {} is not within [-90, 90]", lat),
+            PositionError::InvalidLongitude(lng) => write!(f, "longitude {} is not within [-180, 180]", lng),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for PositionError {}
+
+/// Checks that `lat` and `lng` lie on the globe.
+pub(crate) fn check_coordinates(lat: f64, lng: f64) -> Result<(), PositionError> {
+    if !(-90.0..=90.0).contains(&lat) {
+        Err(PositionError::InvalidLatitude(lat))
+    } else if !(-180.0..=180.0).contains(&lng) {
+        Err(PositionError::InvalidLongitude(lng))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returned when parsing a string into one of this crate's types fails.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What the string should have described.
+    pub expected: &'static str,
+    /// The string that failed to parse.
+    pub input: String
+}
+
+#[cfg(feature = "alloc")]
+impl ParseError {
+
+    pub(crate) fn new(expected: &'static str, input: &str) -> Self {
+        ParseError { expected, input: String::from(input) }
+    }
+
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}, found {:?}", self.expected, self.input)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseError {}
+
+/// The errors returned by the fallible APIs of this crate.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CircadiaError {
+    /// The latitude or longitude isn't a finite number of degrees within
+    /// [-90, 90] or [-180, 180] respectively.
+    InvalidPosition { lat: f64, lng: f64 },
+    /// No events were given to list.
+    EmptyWhitelist,
+    /// The date falls outside of the supported years.
+    DateOutOfRange(DateOutOfRange),
+    /// The event doesn't happen on the requested date, as the sun
+    /// stays above or below its zenith all day.
+    NoEvent(SunEvent)
+}
+
+impl fmt::Display for CircadiaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircadiaError::InvalidPosition { lat, lng } => write!(f, "invalid position ({}, {})", lat, lng),
+            CircadiaError::EmptyWhitelist => write!(f, "the event whitelist is empty"),
+            CircadiaError::DateOutOfRange(err) => err.fmt(f),
+            CircadiaError::NoEvent(event) => write!(f, "there is no {} on that date", event),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for CircadiaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CircadiaError::DateOutOfRange(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<DateOutOfRange> for CircadiaError {
+    fn from(err: DateOutOfRange) -> Self {
+        CircadiaError::DateOutOfRange(err)
+    }
+}
+
+/// Checks that `pos` lies on the globe.
+pub(crate) fn check_position(pos: &GlobalPosition) -> Result<(), CircadiaError> {
+    let (lat, lng) = (pos.lat(), pos.lng());
+    check_coordinates(lat, lng).map_err(|_| CircadiaError::InvalidPosition { lat, lng })
+}
+
+/// Checks that `year` lies within `supported`.
+pub(crate) fn check_year(year: i32, supported: RangeInclusive<i32>) -> Result<(), DateOutOfRange> {
+    if supported.contains(&year) {
+        Ok(())
+    } else {
+        Err(DateOutOfRange { year, supported })
+    }
+}
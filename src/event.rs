@@ -1,13 +1,28 @@
 
-use std::fmt;
-use std::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use alloc::{ string::ToString, vec, vec::Vec };
+use core::fmt;
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+#[cfg(feature = "alloc")]
+use super::error::ParseError;
 
 /// Defines how the sunset/sunrise is measured in relation to the horizon.
 /// See https://www.timeanddate.com/astronomy/different-types-twilight.html
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Zenith {
     Golden,
+    /// The upper limb of the sun on the horizon, at the standard 90°50':
+    /// the center of the sun 16' (its radius) plus 34' (the refraction
+    /// of a [standard atmosphere](crate::Atmosphere::STANDARD)) below
+    /// the horizontal. The refraction and the dip of the horizon follow
+    /// the [GlobalPosition](crate::GlobalPosition) of the observer.
     Official,
+    /// The sun 4° below the horizon, where the blue hour
+    /// meets the brighter part of civil twilight.
+    BlueHour,
     Civil,
     Nautical,
     Astronomical
@@ -15,11 +30,27 @@ pub enum Zenith {
 
 impl Zenith {
 
+    /// Every zenith, from the highest to the lowest.
+    pub const ALL: [Zenith; 6] = [
+        Zenith::Golden,
+        Zenith::Official,
+        Zenith::BlueHour,
+        Zenith::Civil,
+        Zenith::Nautical,
+        Zenith::Astronomical
+    ];
+
+    /// Iterates over every zenith, from the highest to the lowest.
+    pub fn iter() -> impl DoubleEndedIterator<Item = Zenith> {
+        Zenith::ALL.iter().copied()
+    }
+
     pub(crate) fn angle(self) -> f64 {
         use Zenith::*;
         match self {
             Golden => 80.0,
             Official => 90.8333333333333, // 90° 50'
+            BlueHour => 94.0,
             Civil => 96.0,
             Nautical => 102.0,
             Astronomical => 108.0
@@ -33,6 +64,7 @@ impl fmt::Display for Zenith {
         match *self {
             Zenith::Golden => write!(f, "golden"),
             Zenith::Official => write!(f, "official"),
+            Zenith::BlueHour => write!(f, "blue hour"),
             Zenith::Civil => write!(f, "civil"),
             Zenith::Nautical => write!(f, "nautical"),
             Zenith::Astronomical => write!(f, "astronomical"),
@@ -40,21 +72,43 @@ impl fmt::Display for Zenith {
     }
 }
 
-/// Represents either the sunset or the sunrise.
+/// Parses the names given by `Display`, ignoring case.
+#[cfg(feature = "alloc")]
+impl FromStr for Zenith {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Zenith::iter()
+            .find(|zenith| zenith.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseError::new("a zenith", s))
+    }
+}
+
+/// Represents the sunrise, the sunset, or the transits of the sun
+/// across the meridian, in order of occurrence within a local day.
+///
+/// More events may be added, so matches over them need a wildcard arm.
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum Event {
+    /// The sun crosses the meridian below the horizon, at its lowest,
+    /// starting the local day.
+    SolarMidnight,
     Sunrise,
+    /// The sun crosses the meridian, at its highest for the day.
+    SolarNoon,
     Sunset
 }
 
 impl Event {
 
-    pub(crate) fn hour(self) -> f64 {
-        use Event::*;
-        match self {
-            Sunrise => 6.0,
-            Sunset => 18.0
-        }
+    /// Every event, in order of occurrence within a local day.
+    pub const ALL: [Event; 4] = [Event::SolarMidnight, Event::Sunrise, Event::SolarNoon, Event::Sunset];
+
+    /// Iterates over every event, in order of occurrence within a local day.
+    pub fn iter() -> impl DoubleEndedIterator<Item = Event> {
+        Event::ALL.iter().copied()
     }
 
 }
@@ -62,14 +116,109 @@ impl Event {
 impl fmt::Display for Event {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
+            Event::SolarMidnight => write!(f, "solar midnight"),
             Event::Sunrise => write!(f, "sunrise"),
+            Event::SolarNoon => write!(f, "solar noon"),
             Event::Sunset => write!(f, "sunset"),
         }
     }
 }
 
-/// Defines a sunset or sunrise at some angle above the horizon (the zenith).
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+/// Parses the names given by `Display`, ignoring case.
+#[cfg(feature = "alloc")]
+impl FromStr for Event {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Event::iter()
+            .find(|event| event.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseError::new("an event", s))
+    }
+}
+
+/// Whether the sun crosses an altitude on its way up or down.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Crossing {
+    Rising,
+    Setting
+}
+
+impl fmt::Display for Crossing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Crossing::Rising => write!(f, "rising"),
+            Crossing::Setting => write!(f, "setting"),
+        }
+    }
+}
+
+/// The phases of the day, from brightest to darkest, delimited by
+/// the sun crossing each [Zenith].
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum TwilightPhase {
+    /// The sun is higher than the golden zenith.
+    Day,
+    /// The sun is between the golden zenith and the horizon.
+    GoldenHour,
+    /// The sun is between the horizon and the civil zenith.
+    CivilTwilight,
+    /// The sun is between the civil and nautical zeniths.
+    NauticalTwilight,
+    /// The sun is between the nautical and astronomical zeniths.
+    AstronomicalTwilight,
+    /// The sun is lower than the astronomical zenith.
+    Night
+}
+
+impl TwilightPhase {
+
+    /// The phase of the day with the center of the sun at `altitude`
+    /// degrees above the horizon, without atmospheric refraction.
+    pub fn at_altitude(altitude: f64) -> Self {
+        use TwilightPhase::*;
+        let zenith = 90.0 - altitude;
+        let below = |boundary: Zenith| zenith > boundary.angle();
+        if below(Zenith::Astronomical) {
+            Night
+        } else if below(Zenith::Nautical) {
+            AstronomicalTwilight
+        } else if below(Zenith::Civil) {
+            NauticalTwilight
+        } else if below(Zenith::Official) {
+            CivilTwilight
+        } else if below(Zenith::Golden) {
+            GoldenHour
+        } else {
+            Day
+        }
+    }
+
+}
+
+/// The lighting state of the day, another name for [TwilightPhase].
+pub type DayPhase = TwilightPhase;
+
+impl fmt::Display for TwilightPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TwilightPhase::Day => write!(f, "day"),
+            TwilightPhase::GoldenHour => write!(f, "golden hour"),
+            TwilightPhase::CivilTwilight => write!(f, "civil twilight"),
+            TwilightPhase::NauticalTwilight => write!(f, "nautical twilight"),
+            TwilightPhase::AstronomicalTwilight => write!(f, "astronomical twilight"),
+            TwilightPhase::Night => write!(f, "night"),
+        }
+    }
+}
+
+/// Defines a sunset or sunrise at some angle above the horizon (the zenith),
+/// or the solar noon or midnight, for which the zenith is irrelevant.
+///
+/// The transits take [Zenith::Official] through [SunEvent::new], and
+/// compare equal whatever their zenith, so that
+/// `SunEvent::new(Zenith::Civil, Event::SolarNoon) == SunEvent::SOLAR_NOON`.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SunEvent {
     pub zenith: Zenith,
     pub event: Event
@@ -81,31 +230,116 @@ impl SunEvent {
     pub const DUSK: SunEvent = SunEvent::new(Zenith::Civil, Event::Sunset);
     pub const SUNRISE: SunEvent = SunEvent::new(Zenith::Official, Event::Sunrise);
     pub const SUNSET: SunEvent = SunEvent::new(Zenith::Official, Event::Sunset);
+    pub const SOLAR_NOON: SunEvent = SunEvent::new(Zenith::Official, Event::SolarNoon);
+    pub const SOLAR_MIDNIGHT: SunEvent = SunEvent::new(Zenith::Official, Event::SolarMidnight);
+
+    /// The blue hour spans the sun between 6° and 4° below the horizon,
+    /// before sunrise and after sunset.
+    pub const MORNING_BLUE_HOUR_START: SunEvent = SunEvent::new(Zenith::Civil, Event::Sunrise);
+    pub const MORNING_BLUE_HOUR_END: SunEvent = SunEvent::new(Zenith::BlueHour, Event::Sunrise);
+    pub const EVENING_BLUE_HOUR_START: SunEvent = SunEvent::new(Zenith::BlueHour, Event::Sunset);
+    pub const EVENING_BLUE_HOUR_END: SunEvent = SunEvent::new(Zenith::Civil, Event::Sunset);
 
+    /// The `event` at `zenith`, or at [Zenith::Official] for the
+    /// transits, whose zenith is irrelevant.
     pub const fn new(zenith: Zenith, event: Event) -> Self {
+        let zenith = match event {
+            Event::SolarNoon | Event::SolarMidnight => Zenith::Official,
+            _ => zenith
+        };
         SunEvent { zenith, event }
     }
 
+    /// Every distinct SunEvent, in order of occurrence within a local
+    /// day: the sunrise and sunset at every zenith, and the solar noon
+    /// and midnight.
+    #[cfg(feature = "alloc")]
+    pub fn all() -> Vec<SunEvent> {
+        let mut events: Vec<_> = Event::iter()
+            .flat_map(|event| {
+                let zeniths: Vec<_> = match event {
+                    Event::Sunrise | Event::Sunset => Zenith::iter().collect(),
+                    Event::SolarNoon | Event::SolarMidnight => vec![Zenith::Official]
+                };
+                zeniths.into_iter().map(move |zenith| SunEvent::new(zenith, event))
+            })
+            .collect();
+        events.sort();
+        events
+    }
+
     pub fn is_sunrise(self) -> bool {
-        use Event::*;
-        match self.event {
-            Sunrise => true,
-            Sunset => false
-        }
+        self.event == Event::Sunrise
     }
 
     pub fn is_sunset(self) -> bool {
-        !self.is_sunrise()
+        self.event == Event::Sunset
     }
 
+    /// Whether this event is the sun crossing the meridian
+    /// rather than some zenith.
+    pub fn is_transit(self) -> bool {
+        self.event == Event::SolarNoon || self.event == Event::SolarMidnight
+    }
+
+    /// Whether this event occurs with the sun below the horizon,
+    /// ie it begins or ends a civil, nautical or astronomical twilight.
+    pub fn is_twilight(self) -> bool {
+        use Zenith::*;
+        match self.zenith {
+            BlueHour | Civil | Nautical | Astronomical => !self.is_transit(),
+            Golden | Official => false
+        }
+    }
+
+    /// Whether this event is the sun crossing the horizon,
+    /// separating daylight from twilight.
+    pub fn is_daylight_boundary(self) -> bool {
+        self.zenith == Zenith::Official && !self.is_transit()
+    }
+
+    /// The phases of the day this event separates, as
+    /// `(phase before, phase after)`, or None for a transit and for
+    /// the blue hour zenith, which lies within civil twilight.
+    pub fn phase_boundary(self) -> Option<(TwilightPhase, TwilightPhase)> {
+        use TwilightPhase::*;
+        if self.is_transit() {
+            return None;
+        }
+        let (brighter, darker) = match self.zenith {
+            Zenith::Golden => (Day, GoldenHour),
+            Zenith::Official => (GoldenHour, CivilTwilight),
+            Zenith::BlueHour => return None,
+            Zenith::Civil => (CivilTwilight, NauticalTwilight),
+            Zenith::Nautical => (NauticalTwilight, AstronomicalTwilight),
+            Zenith::Astronomical => (AstronomicalTwilight, Night)
+        };
+        if self.is_sunrise() {
+            Some((darker, brighter))
+        } else {
+            Some((brighter, darker))
+        }
+    }
+
+}
+
+/// Compares the transits whatever their zenith, as [SunEvent::new]
+/// gives them all the same one.
+impl PartialEq for SunEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
+impl Eq for SunEvent {}
+
 impl Ord for SunEvent {
     fn cmp(&self, other: &Self) -> Ordering {
         use Event::*;
-        match (self.event, other.event) {
-            (Sunrise, Sunrise) => self.zenith.cmp(&other.zenith).reverse(),
-            (Sunset, Sunset) => self.zenith.cmp(&other.zenith),
+        let (this, other) = (SunEvent::new(self.zenith, self.event), SunEvent::new(other.zenith, other.event));
+        match (this.event, other.event) {
+            (Sunrise, Sunrise) => this.zenith.cmp(&other.zenith).reverse(),
+            (a, b) if a == b => this.zenith.cmp(&other.zenith),
             (a, b) => a.cmp(&b)
         }
     }
@@ -126,11 +360,36 @@ impl fmt::Display for SunEvent {
             (Civil, Sunset) => write!(f, "dusk"),
             (Official, Sunrise) => write!(f, "sunrise"),
             (Official, Sunset) => write!(f, "sunset"),
+            (_, SolarNoon) => write!(f, "solar noon"),
+            (_, SolarMidnight) => write!(f, "solar midnight"),
             (z, e) => write!(f, "{} {}", z, e)
         }
     }
 }
 
+/// Parses the names given by `Display`, such as "dawn" or "nautical
+/// sunset", ignoring case. A sunrise or sunset may also be prefixed by
+/// any zenith, as in "civil sunrise" or "official sunset".
+#[cfg(feature = "alloc")]
+impl FromStr for SunEvent {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        if let Some(event) = SunEvent::all().into_iter().find(|event| event.to_string() == name) {
+            return Ok(event);
+        }
+        let (zenith, event) = name.rsplit_once(' ')
+            .ok_or_else(|| ParseError::new("a sun event", s))?;
+        match (zenith.parse(), event.parse()) {
+            (Ok(zenith), Ok(event @ Event::Sunrise)) | (Ok(zenith), Ok(event @ Event::Sunset)) => {
+                Ok(SunEvent::new(zenith, event))
+            },
+            _ => Err(ParseError::new("a sun event", s))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -147,16 +406,98 @@ mod test {
     #[test]
     fn zenith_should_sort_in_order_of_angle() {
         use Zenith::*;
-        let mut zeniths = vec![Golden, Official, Golden, Civil, Astronomical, Nautical, Astronomical, Official];
+        let mut zeniths = vec![Golden, Official, Golden, Civil, Astronomical, BlueHour, Nautical, Astronomical, Official];
         zeniths.sort();
-        assert_eq!(zeniths, vec![Golden, Golden, Official, Official, Civil, Nautical, Astronomical, Astronomical]);
+        assert_eq!(zeniths, vec![Golden, Golden, Official, Official, BlueHour, Civil, Nautical, Astronomical, Astronomical]);
+    }
+
+    #[test]
+    fn sun_events_should_separate_phases_in_order() {
+        use TwilightPhase::*;
+        assert_eq!(SunEvent::DAWN.phase_boundary(), Some((NauticalTwilight, CivilTwilight)));
+        assert_eq!(SunEvent::SUNSET.phase_boundary(), Some((GoldenHour, CivilTwilight)));
+        assert_eq!(SunEvent::SOLAR_NOON.phase_boundary(), None);
+        assert!(!SunEvent::SOLAR_MIDNIGHT.is_daylight_boundary());
+        assert!(SunEvent::DUSK.is_twilight());
+        assert!(!SunEvent::SUNRISE.is_twilight());
+        assert!(SunEvent::SUNRISE.is_daylight_boundary());
+        assert!(!SunEvent::DAWN.is_daylight_boundary());
+    }
+
+    #[test]
+    fn phases_should_follow_the_altitude_of_the_sun() {
+        use TwilightPhase::*;
+        assert_eq!(TwilightPhase::at_altitude(45.0), Day);
+        assert_eq!(TwilightPhase::at_altitude(5.0), GoldenHour);
+        assert_eq!(TwilightPhase::at_altitude(-0.5), GoldenHour);
+        assert_eq!(TwilightPhase::at_altitude(-3.0), CivilTwilight);
+        assert_eq!(TwilightPhase::at_altitude(-9.0), NauticalTwilight);
+        assert_eq!(TwilightPhase::at_altitude(-15.0), AstronomicalTwilight);
+        assert_eq!(TwilightPhase::at_altitude(-60.0), Night);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn all_should_list_each_event_once() {
+        let all = SunEvent::all();
+        assert_eq!(all.len(), Zenith::iter().count() * 2 + 2);
+        assert_eq!(all[0], SunEvent::SOLAR_MIDNIGHT);
+        assert_eq!(all[1], SunEvent::new(Zenith::Astronomical, Event::Sunrise));
+        assert_eq!(all.last(), Some(&SunEvent::new(Zenith::Astronomical, Event::Sunset)));
+        assert!(all.contains(&SunEvent::DUSK));
+        assert_eq!(Event::iter().collect::<Vec<_>>(), Event::ALL.to_vec());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn names_should_parse_back_to_their_values() {
+        for zenith in Zenith::iter() {
+            assert_eq!(zenith.to_string().parse(), Ok(zenith));
+        }
+        for event in Event::iter() {
+            assert_eq!(event.to_string().parse(), Ok(event));
+        }
+        for event in SunEvent::all() {
+            assert_eq!(event.to_string().parse(), Ok(event));
+        }
+        assert_eq!("Nautical Sunset".parse(), Ok(SunEvent::new(Zenith::Nautical, Event::Sunset)));
+        assert_eq!("civil sunrise".parse(), Ok(SunEvent::DAWN));
+        assert_eq!(" blue hour ".parse(), Ok(Zenith::BlueHour));
+        assert!("civil solar noon".parse::<SunEvent>().is_err());
+        assert!("moonrise".parse::<SunEvent>().is_err());
+        assert!("sunrize".parse::<Event>().is_err());
+    }
+
+    #[test]
+    fn blue_hours_should_sort_around_sunrise_and_sunset() {
+        let mut events = vec![
+            SunEvent::EVENING_BLUE_HOUR_END, SunEvent::SUNRISE, SunEvent::MORNING_BLUE_HOUR_END,
+            SunEvent::SUNSET, SunEvent::EVENING_BLUE_HOUR_START, SunEvent::MORNING_BLUE_HOUR_START
+        ];
+        events.sort();
+        assert_eq!(events, vec![
+            SunEvent::MORNING_BLUE_HOUR_START, SunEvent::MORNING_BLUE_HOUR_END, SunEvent::SUNRISE,
+            SunEvent::SUNSET, SunEvent::EVENING_BLUE_HOUR_START, SunEvent::EVENING_BLUE_HOUR_END
+        ]);
+        assert!(SunEvent::EVENING_BLUE_HOUR_START.is_twilight());
+        assert_eq!(SunEvent::EVENING_BLUE_HOUR_START.phase_boundary(), None);
+    }
+
+    #[test]
+    fn transits_should_ignore_their_zenith() {
+        assert_eq!(SunEvent::new(Zenith::Civil, Event::SolarNoon), SunEvent::SOLAR_NOON);
+        assert_eq!(SunEvent::new(Zenith::Civil, Event::SolarNoon).zenith, Zenith::Official);
+        let raw = SunEvent { zenith: Zenith::Nautical, event: Event::SolarMidnight };
+        assert_eq!(raw, SunEvent::SOLAR_MIDNIGHT);
+        assert_eq!(raw.cmp(&SunEvent::SOLAR_MIDNIGHT), Ordering::Equal);
+        assert_ne!(SunEvent::new(Zenith::Civil, Event::Sunrise), SunEvent::SUNRISE);
     }
 
     #[test]
     fn sun_event_should_sort_in_order_of_occurence() {
-        let mut events = vec![SunEvent::DAWN, SunEvent::DUSK, SunEvent::SUNRISE, SunEvent::SUNSET];
+        let mut events = vec![SunEvent::DAWN, SunEvent::DUSK, SunEvent::SOLAR_NOON, SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::SOLAR_MIDNIGHT];
         events.sort();
-        assert_eq!(events, vec![SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::DUSK]);
+        assert_eq!(events, vec![SunEvent::SOLAR_MIDNIGHT, SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET, SunEvent::DUSK]);
     }
 
 }
@@ -0,0 +1,199 @@
+//! Exports the events of a range of dates as CSV or JSON tables, with
+//! a choice of columns.
+
+use std::fmt::{ self, Display };
+use std::ops::RangeInclusive;
+use chrono::{ DateTime, NaiveDate, SecondsFormat, TimeZone, Utc };
+use serde_json::{ Map, Value };
+use crate::adapter::chrono::time_of_event_on;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+use crate::solar::sun_position;
+
+/// A column of an exported table.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Column {
+    /// The calendar date of the event, in the timezone of the export.
+    Date,
+    /// The name of the event, such as "civil sunrise".
+    Event,
+    /// The time of the event in UTC, in RFC 3339.
+    UtcTime,
+    /// The time of the event in the timezone of the export, in RFC 3339.
+    LocalTime,
+    /// The azimuth of the sun at the event, in degrees clockwise from
+    /// true north.
+    Azimuth
+}
+
+impl Column {
+
+    /// Every column, in their default order.
+    pub const ALL: [Column; 5] = [Column::Date, Column::Event, Column::UtcTime, Column::LocalTime, Column::Azimuth];
+
+    /// The name of the column, heading CSV tables and keying JSON
+    /// objects.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Date => "date",
+            Column::Event => "event",
+            Column::UtcTime => "utc_time",
+            Column::LocalTime => "local_time",
+            Column::Azimuth => "azimuth"
+        }
+    }
+
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Exports the events falling on a range of calendar dates of a
+/// timezone, one row per event in chronological order.
+#[derive(Debug, Clone)]
+pub struct Export<Tz: TimeZone> {
+    tz: Tz,
+    columns: Vec<Column>
+}
+
+impl<Tz: TimeZone> Export<Tz>
+where
+    Tz::Offset: Display
+{
+
+    /// Exports every [Column] for the dates of the timezone `tz`.
+    pub fn new(tz: Tz) -> Self {
+        Export { tz, columns: Column::ALL.to_vec() }
+    }
+
+    /// Exports only `columns`, in that order.
+    pub fn with_columns(self, columns: &[Column]) -> Self {
+        Export { columns: columns.to_vec(), ..self }
+    }
+
+    /// Formats the `events` at `pos` falling on `dates` as CSV, with a
+    /// header line naming the columns.
+    pub fn to_csv(&self, dates: RangeInclusive<NaiveDate>, pos: &GlobalPosition, events: &[SunEvent]) -> String {
+        let mut csv = self.columns.iter()
+            .map(|column| column.name())
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push('\n');
+        for (date, event, time) in self.rows(dates, pos, events) {
+            let fields: Vec<_> = self.columns.iter()
+                .map(|column| match self.value(*column, date, event, &time, pos) {
+                    Value::String(text) => quote(&text),
+                    value => value.to_string()
+                })
+                .collect();
+            csv += &fields.join(",");
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Formats the `events` at `pos` falling on `dates` as a JSON array
+    /// of objects keyed by the names of the columns.
+    pub fn to_json(&self, dates: RangeInclusive<NaiveDate>, pos: &GlobalPosition, events: &[SunEvent]) -> String {
+        let rows: Vec<_> = self.rows(dates, pos, events)
+            .into_iter()
+            .map(|(date, event, time)| {
+                let row: Map<_, _> = self.columns.iter()
+                    .map(|column| (column.name().to_string(), self.value(*column, date, event, &time, pos)))
+                    .collect();
+                Value::Object(row)
+            })
+            .collect();
+        Value::Array(rows).to_string()
+    }
+
+    fn rows(&self, dates: RangeInclusive<NaiveDate>, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<(NaiveDate, SunEvent, DateTime<Tz>)> {
+        let (first, last) = dates.into_inner();
+        first.iter_days()
+            .take_while(|date| *date <= last)
+            .flat_map(|date| {
+                let mut day: Vec<_> = events.iter()
+                    .filter_map(|&event| time_of_event_on(date, &self.tz, pos, event).map(|time| (date, event, time)))
+                    .collect();
+                day.sort_by_key(|(_, _, time)| time.clone());
+                day
+            })
+            .collect()
+    }
+
+    fn value(&self, column: Column, date: NaiveDate, event: SunEvent, time: &DateTime<Tz>, pos: &GlobalPosition) -> Value {
+        match column {
+            Column::Date => Value::String(date.to_string()),
+            Column::Event => Value::String(event.to_string()),
+            Column::UtcTime => Value::String(time.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Secs, true)),
+            Column::LocalTime => Value::String(time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            Column::Azimuth => {
+                let azimuth = sun_position(pos, time.clone()).azimuth;
+                Value::from((azimuth * 100.0).round() / 100.0)
+            }
+        }
+    }
+
+}
+
+/// Quotes a CSV field when it holds a comma, a quote or a line break.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn june() -> RangeInclusive<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, 20).unwrap()..=NaiveDate::from_ymd_opt(2024, 6, 21).unwrap()
+    }
+
+    #[test]
+    fn csv_should_list_the_events_of_each_date_in_order() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        let csv = Export::new(tz).to_csv(june(), &pos, &[SunEvent::SUNSET, SunEvent::SUNRISE]);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "date,event,utc_time,local_time,azimuth");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("2024-06-20,sunrise,2024-06-20T03:4"), "{}", lines[1]);
+        assert!(lines[2].starts_with("2024-06-20,sunset,2024-06-20T20:2"), "{}", lines[2]);
+        let fields: Vec<_> = lines[2].split(',').collect();
+        assert!(fields[3].starts_with("2024-06-20T21:2") && fields[3].ends_with("+01:00"), "{}", fields[3]);
+        let azimuth: f64 = fields[4].parse().unwrap();
+        assert!((azimuth - 310.0).abs() < 2.0, "{}", azimuth);
+    }
+
+    #[test]
+    fn json_should_key_the_chosen_columns() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let json = Export::new(Utc)
+            .with_columns(&[Column::Event, Column::UtcTime])
+            .to_json(june(), &pos, &[SunEvent::SOLAR_NOON, SunEvent::SUNSET]);
+        let rows: Value = serde_json::from_str(&json).unwrap();
+        let rows = rows.as_array().unwrap();
+        // The sun doesn't set at Svalbard in June.
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].as_object().unwrap().len(), 2);
+        assert_eq!(rows[0]["event"], "solar noon");
+        assert!(rows[1]["utc_time"].as_str().unwrap().starts_with("2024-06-21T"));
+    }
+
+    #[test]
+    fn csv_fields_should_be_quoted_when_needed() {
+        assert_eq!(quote("sunrise"), "sunrise");
+        assert_eq!(quote("a,b"), "\"a,b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+}
@@ -0,0 +1,355 @@
+//! A C ABI over the crate, declared in `include/circadia.h`.
+//!
+//! Positions and iterators are opaque handles, created and freed by the
+//! functions of this module, while the events and their times pass by
+//! value. Times are Unix timestamps, in seconds. Build the library with
+//! `cargo rustc --release --features ffi --crate-type staticlib` (or
+//! `cdylib`), and regenerate the header after changing this module with
+//! `cbindgen --config cbindgen.toml --output include/circadia.h src/ffi.rs`.
+
+use std::ptr;
+use chrono::{ DateTime, NaiveDate, TimeZone, Utc };
+use crate::adapter::chrono::unix_day;
+use crate::core::{ EventResult, SUPPORTED_YEARS };
+use crate::epoch::event_result_unix;
+use crate::event::{ Event, SunEvent, Zenith };
+use crate::iter::SunEvents;
+use crate::pos::GlobalPosition;
+
+/// A position on the globe.
+pub struct CircadiaPosition(GlobalPosition);
+
+/// An iterator over the sun events following or preceding a time.
+pub struct CircadiaIterator(Box<dyn Iterator<Item = (SunEvent, DateTime<Utc>)>>);
+
+/// The angle of the sun at which a sunrise or sunset occurs, as
+/// [Zenith].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircadiaZenith {
+    CircadiaZenithGolden,
+    CircadiaZenithOfficial,
+    CircadiaZenithBlueHour,
+    CircadiaZenithCivil,
+    CircadiaZenithNautical,
+    CircadiaZenithAstronomical
+}
+
+/// The crossing of the sun, as [Event].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircadiaEvent {
+    CircadiaEventSolarMidnight,
+    CircadiaEventSunrise,
+    CircadiaEventSolarNoon,
+    CircadiaEventSunset
+}
+
+/// A sun event, as [SunEvent]. The zenith of a transit is ignored,
+/// and always given as [CircadiaZenith::CircadiaZenithOfficial].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircadiaSunEvent {
+    pub zenith: CircadiaZenith,
+    pub event: CircadiaEvent
+}
+
+/// A sun event and the time it occurs at.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircadiaOccurrence {
+    pub event: CircadiaSunEvent,
+    /// The Unix timestamp of the event, in seconds.
+    pub time: i64
+}
+
+/// The outcome of [circadia_time_of_event].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircadiaStatus {
+    /// The event occurs, at the time written out.
+    CircadiaStatusOk,
+    /// The sun stays above the zenith of the event all day.
+    CircadiaStatusPolarDay,
+    /// The sun stays below the zenith of the event all day.
+    CircadiaStatusPolarNight,
+    /// The date falls outside of the supported years.
+    CircadiaStatusDateOutOfRange,
+    /// A pointer is null or the date doesn't exist.
+    CircadiaStatusInvalidArgument
+}
+
+impl From<CircadiaSunEvent> for SunEvent {
+    fn from(event: CircadiaSunEvent) -> Self {
+        use CircadiaZenith::*;
+        use CircadiaEvent::*;
+        let zenith = match event.zenith {
+            CircadiaZenithGolden => Zenith::Golden,
+            CircadiaZenithOfficial => Zenith::Official,
+            CircadiaZenithBlueHour => Zenith::BlueHour,
+            CircadiaZenithCivil => Zenith::Civil,
+            CircadiaZenithNautical => Zenith::Nautical,
+            CircadiaZenithAstronomical => Zenith::Astronomical
+        };
+        let event = match event.event {
+            CircadiaEventSolarMidnight => Event::SolarMidnight,
+            CircadiaEventSunrise => Event::Sunrise,
+            CircadiaEventSolarNoon => Event::SolarNoon,
+            CircadiaEventSunset => Event::Sunset
+        };
+        SunEvent::new(zenith, event)
+    }
+}
+
+impl From<SunEvent> for CircadiaSunEvent {
+    fn from(event: SunEvent) -> Self {
+        use CircadiaZenith::*;
+        use CircadiaEvent::*;
+        let event = SunEvent::new(event.zenith, event.event);
+        let zenith = match event.zenith {
+            Zenith::Golden => CircadiaZenithGolden,
+            Zenith::Official => CircadiaZenithOfficial,
+            Zenith::BlueHour => CircadiaZenithBlueHour,
+            Zenith::Civil => CircadiaZenithCivil,
+            Zenith::Nautical => CircadiaZenithNautical,
+            Zenith::Astronomical => CircadiaZenithAstronomical
+        };
+        let event = match event.event {
+            Event::SolarMidnight => CircadiaEventSolarMidnight,
+            Event::Sunrise => CircadiaEventSunrise,
+            Event::SolarNoon => CircadiaEventSolarNoon,
+            Event::Sunset => CircadiaEventSunset
+        };
+        CircadiaSunEvent { zenith, event }
+    }
+}
+
+/// Creates the position at the latitude `lat` and the longitude `lng`,
+/// in degrees, to be freed with [circadia_position_free], or returns
+/// null when it doesn't lie on the globe.
+#[no_mangle]
+pub extern "C" fn circadia_position_new(lat: f64, lng: f64) -> *mut CircadiaPosition {
+    match GlobalPosition::try_at(lat, lng) {
+        Ok(pos) => Box::into_raw(Box::new(CircadiaPosition(pos))),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Places `pos` `meters` above sea level.
+///
+/// # Safety
+/// `pos` must be null or returned by [circadia_position_new] and not
+/// yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_position_set_elevation(pos: *mut CircadiaPosition, meters: f64) {
+    if let Some(pos) = pos.as_mut() {
+        pos.0 = pos.0.clone().with_elevation(meters);
+    }
+}
+
+/// Frees `pos`.
+///
+/// # Safety
+/// `pos` must be null or returned by [circadia_position_new] and not
+/// yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_position_free(pos: *mut CircadiaPosition) {
+    if !pos.is_null() {
+        drop(Box::from_raw(pos));
+    }
+}
+
+/// Calculates the time of `event` on the UTC date `year`-`month`-`day`
+/// at `pos`, writing it to `time` when the event occurs.
+///
+/// # Safety
+/// `pos` must be null or a live position, and `time` null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_time_of_event(
+    pos: *const CircadiaPosition,
+    year: i32,
+    month: u32,
+    day: u32,
+    event: CircadiaSunEvent,
+    time: *mut i64,
+) -> CircadiaStatus {
+    let (pos, time) = match (pos.as_ref(), time.as_mut()) {
+        (Some(pos), Some(time)) => (pos, time),
+        _ => return CircadiaStatus::CircadiaStatusInvalidArgument
+    };
+    let date = match NaiveDate::from_ymd_opt(year, month, day) {
+        Some(date) => date,
+        None => return CircadiaStatus::CircadiaStatusInvalidArgument
+    };
+    if !SUPPORTED_YEARS.contains(&year) {
+        return CircadiaStatus::CircadiaStatusDateOutOfRange;
+    }
+    match event_result_unix(unix_day(date), &pos.0, event.into()) {
+        EventResult::At(secs) => {
+            *time = secs;
+            CircadiaStatus::CircadiaStatusOk
+        },
+        EventResult::PolarDay => CircadiaStatus::CircadiaStatusPolarDay,
+        EventResult::PolarNight => CircadiaStatus::CircadiaStatusPolarNight,
+    }
+}
+
+/// Lists the `len` events of `events` occurring after the Unix
+/// timestamp `start` at `pos`, to be freed with
+/// [circadia_iterator_free], or returns null when the arguments are
+/// invalid.
+///
+/// # Safety
+/// `pos` must be null or a live position, and `events` null or valid
+/// for reads of `len` events.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_forecast_new(
+    pos: *const CircadiaPosition,
+    start: i64,
+    events: *const CircadiaSunEvent,
+    len: usize,
+) -> *mut CircadiaIterator {
+    match sun_events(pos, start, events, len) {
+        Some(events) => Box::into_raw(Box::new(CircadiaIterator(Box::new(events.forecast())))),
+        None => ptr::null_mut()
+    }
+}
+
+/// Like [circadia_forecast_new], for the events occurring before
+/// `start`, latest first.
+///
+/// # Safety
+/// As for [circadia_forecast_new].
+#[no_mangle]
+pub unsafe extern "C" fn circadia_history_new(
+    pos: *const CircadiaPosition,
+    start: i64,
+    events: *const CircadiaSunEvent,
+    len: usize,
+) -> *mut CircadiaIterator {
+    match sun_events(pos, start, events, len) {
+        Some(events) => Box::into_raw(Box::new(CircadiaIterator(Box::new(events.history())))),
+        None => ptr::null_mut()
+    }
+}
+
+/// Writes the next event of `iter` to `occurrence`, returning false
+/// when there is none.
+///
+/// # Safety
+/// `iter` must be null or returned by [circadia_forecast_new] or
+/// [circadia_history_new] and not yet freed, and `occurrence` null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_iterator_next(iter: *mut CircadiaIterator, occurrence: *mut CircadiaOccurrence) -> bool {
+    let (iter, occurrence) = match (iter.as_mut(), occurrence.as_mut()) {
+        (Some(iter), Some(occurrence)) => (iter, occurrence),
+        _ => return false
+    };
+    match iter.0.next() {
+        Some((event, time)) => {
+            *occurrence = CircadiaOccurrence { event: event.into(), time: time.timestamp() };
+            true
+        },
+        None => false
+    }
+}
+
+/// Frees `iter`.
+///
+/// # Safety
+/// `iter` must be null or returned by [circadia_forecast_new] or
+/// [circadia_history_new] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn circadia_iterator_free(iter: *mut CircadiaIterator) {
+    if !iter.is_null() {
+        drop(Box::from_raw(iter));
+    }
+}
+
+unsafe fn sun_events(
+    pos: *const CircadiaPosition,
+    start: i64,
+    events: *const CircadiaSunEvent,
+    len: usize,
+) -> Option<SunEvents> {
+    let pos = pos.as_ref()?;
+    if events.is_null() {
+        return None;
+    }
+    let events: Vec<SunEvent> = std::slice::from_raw_parts(events, len)
+        .iter()
+        .map(|&event| event.into())
+        .collect();
+    let start = Utc.timestamp_opt(start, 0).single()?;
+    SunEvents::try_starting_from(start, pos.0.clone(), &events).ok()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn events_should_round_trip_through_the_c_types() {
+        for event in SunEvent::all() {
+            assert_eq!(SunEvent::from(CircadiaSunEvent::from(event)), event);
+        }
+    }
+
+    #[test]
+    fn transits_should_cross_with_the_official_zenith() {
+        let noon = SunEvent { zenith: Zenith::Civil, event: Event::SolarNoon };
+        assert_eq!(CircadiaSunEvent::from(noon), CircadiaSunEvent::from(SunEvent::SOLAR_NOON));
+    }
+
+    #[test]
+    fn times_should_match_the_epoch_api() {
+        let pos = circadia_position_new(51.4810066, 0.0081805);
+        let sunrise = CircadiaSunEvent::from(SunEvent::SUNRISE);
+        let mut time = 0;
+        unsafe {
+            let status = circadia_time_of_event(pos, 2024, 6, 21, sunrise, &mut time);
+            assert_eq!(status, CircadiaStatus::CircadiaStatusOk);
+            assert_eq!(Some(time), crate::epoch::time_of_event_unix(19_895, &(*pos).0, SunEvent::SUNRISE));
+            let status = circadia_time_of_event(pos, 2024, 2, 30, sunrise, &mut time);
+            assert_eq!(status, CircadiaStatus::CircadiaStatusInvalidArgument);
+            let status = circadia_time_of_event(pos, 1800, 6, 21, sunrise, &mut time);
+            assert_eq!(status, CircadiaStatus::CircadiaStatusDateOutOfRange);
+            circadia_position_free(pos);
+        }
+        assert!(circadia_position_new(91.0, 0.0).is_null());
+        let svalbard = circadia_position_new(78.2232, 15.6267);
+        unsafe {
+            let sunset = CircadiaSunEvent::from(SunEvent::SUNSET);
+            let status = circadia_time_of_event(svalbard, 2024, 6, 21, sunset, &mut time);
+            assert_eq!(status, CircadiaStatus::CircadiaStatusPolarDay);
+            circadia_position_free(svalbard);
+        }
+    }
+
+    #[test]
+    fn iterators_should_yield_the_events_in_order() {
+        let pos = circadia_position_new(-33.8688, 151.2093);
+        let events = [CircadiaSunEvent::from(SunEvent::SUNRISE), CircadiaSunEvent::from(SunEvent::SUNSET)];
+        let start = 1_704_067_200;
+        unsafe {
+            let iter = circadia_forecast_new(pos, start, events.as_ptr(), events.len());
+            let mut occurrence = CircadiaOccurrence { event: events[0], time: 0 };
+            let mut last = start;
+            let mut previous = None;
+            for _ in 0..4 {
+                assert!(circadia_iterator_next(iter, &mut occurrence));
+                assert_ne!(Some(occurrence.event), previous);
+                assert!(occurrence.time > last);
+                previous = Some(occurrence.event);
+                last = occurrence.time;
+            }
+            circadia_iterator_free(iter);
+            assert!(circadia_history_new(pos, start, events.as_ptr(), 0).is_null());
+            circadia_position_free(pos);
+        }
+    }
+
+}
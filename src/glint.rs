@@ -0,0 +1,89 @@
+//! Sun glint, the mirror reflection of the sun off a calm water surface,
+//! which saturates cameras and other sensors looking down at it.
+
+use chrono::{ DateTime, Utc };
+use crate::adapter::chrono::julian_day;
+use crate::pos::GlobalPosition;
+use crate::solar;
+
+/// A direction in the sky. Angles in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Direction {
+    /// Degrees above the horizon.
+    pub altitude: f64,
+    /// Degrees clockwise from true north.
+    pub azimuth: f64
+}
+
+impl Direction {
+
+    /// The angle between this direction and `other`, in degrees.
+    pub fn angle_to(&self, other: &Direction) -> f64 {
+        let (alt1, alt2) = (self.altitude.to_radians(), other.altitude.to_radians());
+        let daz = (self.azimuth - other.azimuth).to_radians();
+        (alt1.sin() * alt2.sin() + alt1.cos() * alt2.cos() * daz.cos())
+            .clamp(-1.0, 1.0)
+            .acos()
+            .to_degrees()
+    }
+
+}
+
+/// Calculates the direction in which a horizontal water surface at `pos`
+/// reflects the sun at `time`: as high above the horizon as the sun,
+/// on the opposite side of the sky.
+///
+/// Returns None when the sun is below the horizon.
+pub fn glint_direction(time: DateTime<Utc>, pos: &GlobalPosition) -> Option<Direction> {
+    let (altitude, azimuth) = solar::horizontal(julian_day(time), pos);
+    if altitude <= 0.0 {
+        return None;
+    }
+    Some(Direction { altitude, azimuth: (azimuth + 180.0) % 360.0 })
+}
+
+/// Calculates the glint angle of a sensor viewing the water at `pos`
+/// at `time`, that is the angle between the specular reflection of the
+/// sun and `view`, the direction from the water to the sensor.
+///
+/// Returns None when the sun is below the horizon.
+pub fn glint_angle(time: DateTime<Utc>, pos: &GlobalPosition, view: &Direction) -> Option<f64> {
+    glint_direction(time, pos).map(|glint| glint.angle_to(view))
+}
+
+/// Whether a sensor in direction `view` from the water at `pos` falls
+/// within the cone of `half_angle` degrees around the glint at `time`.
+/// Waves spread the glint, so cones of 10 to 30 degrees are typical.
+pub fn is_within_glint_cone(time: DateTime<Utc>, pos: &GlobalPosition, view: &Direction, half_angle: f64) -> bool {
+    glint_angle(time, pos, view).is_some_and(|angle| angle <= half_angle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn glint_should_mirror_the_sun() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let time = Utc.with_ymd_and_hms(2021, 6, 21, 15, 0, 0).unwrap();
+        let (altitude, azimuth) = solar::horizontal(julian_day(time), &pos);
+        let glint = glint_direction(time, &pos).unwrap();
+        assert!((glint.altitude - altitude).abs() < 1e-9);
+        assert!(((glint.azimuth - azimuth).rem_euclid(360.0) - 180.0).abs() < 1e-9);
+        let night = Utc.with_ymd_and_hms(2021, 6, 21, 23, 0, 0).unwrap();
+        assert!(glint_direction(night, &pos).is_none());
+    }
+
+    #[test]
+    fn nadir_view_should_see_glint_under_an_overhead_sun() {
+        // Singapore at noon near the equinox.
+        let pos = GlobalPosition::at(1.3521, 103.8198);
+        let noon = Utc.with_ymd_and_hms(2021, 3, 21, 5, 7, 0).unwrap();
+        let nadir = Direction { altitude: 90.0, azimuth: 0.0 };
+        assert!(is_within_glint_cone(noon, &pos, &nadir, 10.0));
+        let morning = Utc.with_ymd_and_hms(2021, 3, 21, 1, 0, 0).unwrap();
+        assert!(!is_within_glint_cone(morning, &pos, &nadir, 10.0));
+    }
+
+}
@@ -0,0 +1,141 @@
+//! Renders sun events as an iCalendar (RFC 5545) calendar, which
+//! calendar apps can import or subscribe to.
+
+use chrono::{ DateTime, Duration, Utc };
+use crate::event::SunEvent;
+use crate::iter::SunEvents;
+use crate::pos::GlobalPosition;
+
+/// The longest line of a calendar, in octets, before it gets folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders `events` at `pos` as a calendar, one instantaneous VEVENT
+/// per event, summarized by the name of the event.
+pub fn to_icalendar<I>(pos: &GlobalPosition, events: I) -> String
+where
+    I: IntoIterator<Item = (SunEvent, DateTime<Utc>)>
+{
+    render(pos, events, Utc::now())
+}
+
+/// Renders the `events` at `pos` occurring from `start` until before
+/// `end` as a calendar, like [to_icalendar].
+/// # Panics
+/// Panics when `events` is empty.
+pub fn icalendar_between(start: DateTime<Utc>, end: DateTime<Utc>, pos: &GlobalPosition, events: &[SunEvent]) -> String {
+    // The forecast only yields the events strictly after its start.
+    let events = SunEvents::starting_from(start - Duration::nanoseconds(1), pos.clone(), events)
+        .forecast()
+        .take_while(|(_, time)| *time < end);
+    to_icalendar(pos, events)
+}
+
+fn render<I>(pos: &GlobalPosition, events: I, stamp: DateTime<Utc>) -> String
+where
+    I: IntoIterator<Item = (SunEvent, DateTime<Utc>)>
+{
+    let mut calendar = String::new();
+    let mut line = |text: &str| push_folded(&mut calendar, text);
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line(&format!("PRODID:-//circadia//circadia {}//EN", env!("CARGO_PKG_VERSION")));
+    line("CALSCALE:GREGORIAN");
+    line(&format!("X-WR-CALNAME:{}", escape(&format!("Sun events at {}", pos))));
+    for (event, time) in events {
+        let name = event.to_string();
+        line("BEGIN:VEVENT");
+        line(&format!("UID:{}-{}@circadia", format_time(time), name.replace(' ', "-")));
+        line(&format!("DTSTAMP:{}", format_time(stamp)));
+        line(&format!("DTSTART:{}", format_time(time)));
+        line(&format!("SUMMARY:{}", escape(&capitalize(&name))));
+        line(&format!("GEO:{};{}", pos.lat(), pos.lng()));
+        line("TRANSP:TRANSPARENT");
+        line("END:VEVENT");
+    }
+    line("END:VCALENDAR");
+    calendar
+}
+
+/// Formats `time` as an RFC 5545 date-time in UTC.
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes the characters with a meaning in RFC 5545 text values.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new()
+    }
+}
+
+/// Appends the content line `text` to `calendar`, folded onto
+/// continuation lines of at most [MAX_LINE_OCTETS] octets, without
+/// splitting a character.
+fn push_folded(calendar: &mut String, text: &str) {
+    let mut octets = 0;
+    for c in text.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            calendar.push_str("\r\n ");
+            // The leading space of the continuation counts.
+            octets = 1;
+        }
+        calendar.push(c);
+        octets += c.len_utf8();
+    }
+    calendar.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn events_should_render_as_vevents() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let sunset = Utc.with_ymd_and_hms(2024, 6, 21, 20, 21, 30).unwrap();
+        let stamp = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let calendar = render(&pos, vec![(SunEvent::SUNSET, sunset), (SunEvent::DUSK, sunset)], stamp);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nX-WR-CALNAME:Sun events at 51.4810066\\, 0.0081805\r\n"), "{}", calendar);
+        assert!(calendar.contains(concat!(
+            "BEGIN:VEVENT\r\nUID:20240621T202130Z-sunset@circadia\r\nDTSTAMP:20240601T000000Z\r\n",
+            "DTSTART:20240621T202130Z\r\nSUMMARY:Sunset\r\nGEO:51.4810066;0.0081805\r\n"
+        )));
+        assert!(calendar.contains("SUMMARY:Dusk\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+    }
+
+    #[test]
+    fn long_lines_should_fold() {
+        let mut calendar = String::new();
+        let text = format!("SUMMARY:{}", "é".repeat(60));
+        push_folded(&mut calendar, &text);
+        let lines: Vec<_> = calendar.split("\r\n").collect();
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines.concat().replace(" é", "é"), text);
+    }
+
+    #[test]
+    fn ranges_should_include_the_start_and_exclude_the_end() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 8, 0, 0, 0).unwrap();
+        let calendar = icalendar_between(start, end, &pos, &[SunEvent::SUNRISE, SunEvent::SUNSET]);
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 14);
+        assert_eq!(calendar.matches("SUMMARY:Sunrise").count(), 7);
+    }
+
+}
@@ -1,36 +1,27 @@
-use chrono::{ DateTime, Utc };
-use std::iter::Cycle;
-use std::vec::IntoIter as VecIter;
+// `chrono::Date` is deprecated upstream but remains the date type
+// of this crate's public API.
+#![allow(deprecated)]
+
+use chrono::{ DateTime, Duration, Local, NaiveDate, TimeZone, Utc };
+use std::collections::{ BTreeMap, VecDeque };
+use std::iter::{ FusedIterator, Rev };
+use std::sync::Arc;
+use super::adapter::chrono::{ from_unix, unix_day };
+use super::algorithm::{ SolarAlgorithm, Usno };
+use super::error::CircadiaError;
+#[cfg(feature = "chrono-tz")]
+use super::error::ParseError;
 use super::event::SunEvent;
-use super::time_of_event;
 use super::pos::GlobalPosition;
 
-#[derive(Debug, Clone)]
-enum CycleState<T> {
-    Next(T),
-    Restarting
-}
-
-fn cycled(events: &[SunEvent]) -> Cycle<VecIter<CycleState<SunEvent>>> {
-    assert!(!events.is_empty());
-    let mut events = events.to_owned();
-    events.sort();
-    events.dedup();
-    let mut cycled_events = vec![];
-    for event in events {
-        cycled_events.push(CycleState::Next(event));
-    }
-    cycled_events.push(CycleState::Restarting);
-    cycled_events.into_iter().cycle()
-}
-
 /// This struct allows one to create iterators over sun events moving
 /// forward or backward in time.
 #[derive(Debug, Clone)]
 pub struct SunEvents {
     pos: GlobalPosition,
     current_time: DateTime<Utc>,
-    event_whitelist_iter: Cycle<VecIter<CycleState<SunEvent>>>
+    event_whitelist: Vec<SunEvent>,
+    algorithm: Arc<dyn SolarAlgorithm>
 }
 
 impl SunEvents {
@@ -40,80 +31,712 @@ impl SunEvents {
     /// # Panics
     /// Panics when `event_whitelist` is empty.
     pub fn starting_from(start_date: DateTime<Utc>, position: GlobalPosition, event_whitelist: &[SunEvent]) -> Self {
+        assert!(!event_whitelist.is_empty());
+        let mut event_whitelist: Vec<_> = event_whitelist.iter()
+            .map(|event| SunEvent::new(event.zenith, event.event))
+            .collect();
+        event_whitelist.sort();
+        event_whitelist.dedup();
         SunEvents {
             pos: position,
             current_time: start_date,
-            event_whitelist_iter: cycled(event_whitelist)
+            event_whitelist,
+            algorithm: Arc::new(Usno::default())
+        }
+    }
+
+    /// List every SunEvent starting from the `start_date`, computed
+    /// at `position`: the sunrise and sunset at every zenith, and the
+    /// solar noon and midnight.
+    pub fn all_events_from(start_date: DateTime<Utc>, position: GlobalPosition) -> Self {
+        SunEvents::starting_from(start_date, position, &SunEvent::all())
+    }
+
+    /// Like [SunEvents::starting_from], but returns an error rather than
+    /// panicking when `event_whitelist` is empty.
+    pub fn try_starting_from(start_date: DateTime<Utc>, position: GlobalPosition, event_whitelist: &[SunEvent]) -> Result<Self, CircadiaError> {
+        if event_whitelist.is_empty() {
+            return Err(CircadiaError::EmptyWhitelist);
         }
+        Ok(SunEvents::starting_from(start_date, position, event_whitelist))
+    }
+
+    /// Computes the events with `algorithm` rather than with the
+    /// default [Usno] approximation.
+    pub fn with_algorithm<A: SolarAlgorithm + 'static>(self, algorithm: A) -> Self {
+        SunEvents { algorithm: Arc::new(algorithm), ..self }
     }
 
     /// List SunEvents occurring after the start_date.
     pub fn forecast(self) -> ForecastedSunEvents {
-        ForecastedSunEvents(self)
+        ForecastedSunEvents(DayWalker::new(self, 1))
     }
 
     /// List SunEvents occurring before the start_date.
     pub fn history(self) -> HistoricSunEvents {
-        HistoricSunEvents(self)
+        HistoricSunEvents(DayWalker::new(self, -1))
+    }
+
+    /// List the SunEvents occurring from the start_date until before
+    /// `end`, which can be taken from either end, as in
+    /// `.until(end).rev().take(3)` for the last three.
+    pub fn until(self, end: DateTime<Utc>) -> SunEventRange {
+        // The forecast only yields the events strictly after its start.
+        let start = self.current_time - Duration::nanoseconds(1);
+        let back = DayWalker::new(SunEvents { current_time: end, ..self.clone() }, -1);
+        let front = DayWalker::new(SunEvents { current_time: start, ..self }, 1);
+        SunEventRange { front, back }
+    }
+
+    /// List the SunEvents of each calendar date of the timezone `tz`,
+    /// starting from the date of the start_date there.
+    ///
+    /// Far from the meridian of `tz`, the events computed for a UTC
+    /// day spill over onto the neighbouring dates, so that a date may
+    /// seem to have two sunrises and the next none. Grouping them by
+    /// the dates of `tz` gives each date the events that happen on it.
+    pub fn by_civil_day<Tz: TimeZone>(self, tz: Tz) -> CivilDays<Tz> {
+        let next_date = self.current_time.with_timezone(&tz).date_naive();
+        CivilDays { events: self, tz, next_date, computed: BTreeMap::new() }
+    }
+
+    /// Like [SunEvents::by_civil_day], in the timezone of the IANA
+    /// database named `name`, such as "Europe/Oslo".
+    #[cfg(feature = "chrono-tz")]
+    pub fn by_civil_day_in(self, name: &str) -> Result<CivilDays<chrono_tz::Tz>, ParseError> {
+        let tz = name.parse().map_err(|_| ParseError::new("an IANA timezone name", name))?;
+        Ok(self.by_civil_day(tz))
     }
 
 }
 
-/// An iterator that yields SunEvents that occur after
-/// a specified start date.
-pub struct ForecastedSunEvents(SunEvents);
+/// Events found UTC day by UTC day from a current time, which a
+/// [DayWalker] steps through in either direction.
+pub(crate) trait DayEvents {
+    type Event: Copy;
+    /// How many days away from its UTC day the computations of a day
+    /// may find an event, as the events of a date spill over onto the
+    /// neighbouring UTC days.
+    const SPILL: i64;
+    /// The time the walk starts at, and then the last yielded one.
+    fn current_time(&self) -> DateTime<Utc>;
+    fn set_current_time(&mut self, time: DateTime<Utc>);
+    /// The most events found for a day.
+    fn most_per_day(&self) -> usize;
+    /// The events found for the UTC day `day`, counted in days since
+    /// the Unix epoch, in chronological order.
+    fn events_on(&self, day: i64) -> Vec<(Self::Event, DateTime<Utc>)>;
+}
 
-impl Iterator for ForecastedSunEvents {
+impl DayEvents for SunEvents {
 
-    type Item = (SunEvent, DateTime<Utc>);
+    type Event = SunEvent;
+    const SPILL: i64 = 1;
+
+    fn current_time(&self) -> DateTime<Utc> {
+        self.current_time
+    }
+
+    fn set_current_time(&mut self, time: DateTime<Utc>) {
+        self.current_time = time;
+    }
+
+    fn most_per_day(&self) -> usize {
+        self.event_whitelist.len()
+    }
+
+    fn events_on(&self, day: i64) -> Vec<(SunEvent, DateTime<Utc>)> {
+        let whitelist = &self.event_whitelist;
+        let results = self.algorithm.event_results_unix(day, &self.pos, whitelist);
+        let mut events: Vec<_> = whitelist.iter()
+            .zip(results)
+            .filter_map(|(event, result)| {
+                result.at()
+                    .and_then(from_unix)
+                    .map(|time| (*event, time))
+            })
+            .collect();
+        events.sort_by_key(|(_, time)| *time);
+        events
+    }
+
+}
+
+/// Walks the events of consecutive dates in one direction,
+/// yielding those strictly beyond the last yielded time.
+#[derive(Debug, Clone)]
+pub(crate) struct DayWalker<E: DayEvents = SunEvents> {
+    events: E,
+    step: i64,
+    next_day: i64,
+    pending: VecDeque<(E::Event, DateTime<Utc>)>
+}
+
+impl<E: DayEvents> DayWalker<E> {
+
+    /// Walks forward from the current time of `events` when `step` is 1,
+    /// and back when it's -1.
+    pub(crate) fn new(events: E, step: i64) -> Self {
+        let next_day = unix_day(events.current_time().date_naive()) - step * E::SPILL;
+        DayWalker { events, step, next_day, pending: VecDeque::new() }
+    }
+
+    fn is_beyond(&self, time: DateTime<Utc>, limit: DateTime<Utc>) -> bool {
+        if self.step > 0 {
+            time >= limit
+        } else {
+            time <= limit
+        }
+    }
+
+    /// Like [Iterator::next], returning None instead of an event at or
+    /// beyond `limit`, without computing the days past it.
+    fn next_before(&mut self, limit: DateTime<Utc>) -> Option<(E::Event, DateTime<Utc>)> {
+        let last_day = unix_day(limit.date_naive()) + self.step * E::SPILL;
+        loop {
+            match self.pending.pop_front() {
+                Some((event, time)) if self.is_beyond(time, limit) => {
+                    self.pending.push_front((event, time));
+                    return None;
+                },
+                Some((event, time)) => {
+                    if self.take(time) {
+                        return Some((event, time));
+                    }
+                },
+                None if (self.next_day - last_day) * self.step > 0 => return None,
+                None => self.fill()
+            }
+        }
+    }
+
+    /// The most events [DayWalker::next_before] yields before `limit`:
+    /// those pending and those of the dates left up to the limit, or
+    /// none of the dates once a pending event reaches the limit.
+    fn most_before(&self, limit: DateTime<Utc>) -> usize {
+        let before = self.pending.iter()
+            .take_while(|(_, time)| !self.is_beyond(*time, limit))
+            .count();
+        if before < self.pending.len() {
+            return before;
+        }
+        let last_day = unix_day(limit.date_naive()) + self.step * E::SPILL;
+        let days = ((last_day - self.next_day) * self.step + 1).max(0) as usize;
+        before + days * self.events.most_per_day()
+    }
+
+    /// Moves to `time` if it's strictly beyond the last yielded time.
+    fn take(&mut self, time: DateTime<Utc>) -> bool {
+        let current = self.events.current_time();
+        let is_ahead = if self.step > 0 { time > current } else { time < current };
+        if is_ahead {
+            self.events.set_current_time(time);
+        }
+        is_ahead
+    }
+
+    fn fill(&mut self) {
+        let mut events = self.events.events_on(self.next_day);
+        if self.step < 0 {
+            events.reverse();
+        }
+        self.pending.extend(events);
+        self.next_day += self.step;
+    }
+
+}
+
+impl<E: DayEvents> Iterator for DayWalker<E> {
+
+    type Item = (E::Event, DateTime<Utc>);
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if let CycleState::Next(event) = self.0.event_whitelist_iter.next().unwrap() {
-                if let Some(event_time) = time_of_event(self.0.current_time.date(), &self.0.pos, event) {
-                    if event_time > self.0.current_time {
-                        self.0.current_time = event_time;
-                        return Some((event, event_time));
+            match self.pending.pop_front() {
+                Some((event, time)) => {
+                    if self.take(time) {
+                        return Some((event, time));
                     }
-                }
-            } else {
-                let tomorrow = self.0.current_time.date().succ();
-                self.0.current_time = tomorrow.and_hms(0, 0, 0);
+                },
+                None => self.fill()
             }
         }
     }
 
 }
 
+/// Iterating over SunEvents directly lists the events occurring
+/// after the start_date, as with [SunEvents::forecast].
+impl IntoIterator for SunEvents {
+
+    type Item = (SunEvent, DateTime<Utc>);
+    type IntoIter = ForecastedSunEvents;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.forecast()
+    }
+
+}
+
+/// An iterator that yields SunEvents that occur after
+/// a specified start date.
+#[derive(Debug, Clone)]
+pub struct ForecastedSunEvents(DayWalker);
+
+impl ForecastedSunEvents {
+
+    /// Stop before `end`: the events left are those occurring after the
+    /// last yielded one, or the start_date, and strictly before `end`.
+    /// An event falling exactly on `end` is left out, as with the end of
+    /// a [Range](std::ops::Range).
+    pub fn until(self, end: DateTime<Utc>) -> SunEventRange {
+        let front = self.0;
+        let back = DayWalker::new(SunEvents { current_time: end, ..front.events.clone() }, -1);
+        SunEventRange { front, back }
+    }
+
+    /// Yield the event times in the timezone `tz`.
+    pub fn in_timezone<Tz: TimeZone>(self, tz: Tz) -> InTimezone<Self, Tz> {
+        InTimezone { iter: self, tz }
+    }
+
+    /// Yield the event times in the timezone of the host system.
+    pub fn in_local_time(self) -> InTimezone<Self, Local> {
+        self.in_timezone(Local)
+    }
+
+}
+
+impl Iterator for ForecastedSunEvents {
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The events never run out.
+        (usize::MAX, None)
+    }
+
+}
+
+impl FusedIterator for ForecastedSunEvents {}
+
 /// An iterator that yields SunEvents that occur before
 /// a specified start date.
-pub struct HistoricSunEvents(SunEvents);
+#[derive(Debug, Clone)]
+pub struct HistoricSunEvents(DayWalker);
+
+impl HistoricSunEvents {
+
+    /// List the SunEvents occurring after `since` and before the
+    /// start_date in chronological order, oldest first, rather than
+    /// walking back from the start_date.
+    pub fn chronological_since(self, since: DateTime<Utc>) -> ChronologicalSunEvents {
+        let walker = self.0;
+        let end = walker.events.current_time;
+        let events = SunEvents { current_time: since, ..walker.events };
+        ChronologicalSunEvents { forecast: events.forecast(), end }
+    }
+
+    /// Stop at `start`: the events left are those occurring before the
+    /// last yielded one, or the start_date, down to `start` included,
+    /// latest first. An event falling exactly on `start` is yielded, so
+    /// that `since(start)` mirrors [SunEvents::until] walked back.
+    pub fn since(self, start: DateTime<Utc>) -> Rev<SunEventRange> {
+        let back = self.0;
+        // The forecast only yields the events strictly after its start.
+        let start = start - Duration::nanoseconds(1);
+        let front = DayWalker::new(SunEvents { current_time: start, ..back.events.clone() }, 1);
+        SunEventRange { front, back }.rev()
+    }
+
+    /// Yield the event times in the timezone `tz`.
+    pub fn in_timezone<Tz: TimeZone>(self, tz: Tz) -> InTimezone<Self, Tz> {
+        InTimezone { iter: self, tz }
+    }
+
+    /// Yield the event times in the timezone of the host system.
+    pub fn in_local_time(self) -> InTimezone<Self, Local> {
+        self.in_timezone(Local)
+    }
+
+}
 
 impl Iterator for HistoricSunEvents {
 
     type Item = (SunEvent, DateTime<Utc>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let CycleState::Next(event) = self.0.event_whitelist_iter.next().unwrap() {
-                if let Some(event_time) = time_of_event(self.0.current_time.date(), &self.0.pos, event) {
-                    if event_time < self.0.current_time {
-                        self.0.current_time = event_time;
-                        return Some((event, event_time));
-                    }
-                }
-            } else {
-                let yesterday = self.0.current_time.date().pred();
-                self.0.current_time = yesterday.and_hms(23, 59, 59);
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The events never run out.
+        (usize::MAX, None)
+    }
+
+}
+
+impl FusedIterator for HistoricSunEvents {}
+
+/// An iterator that yields the SunEvents occurring between two dates,
+/// in chronological order from its front and in reverse from its back.
+#[derive(Debug, Clone)]
+pub struct SunEventRange {
+    /// Walks forward from the start, up to the last event taken from
+    /// the back.
+    front: DayWalker,
+    /// Walks back from the end, down to the last event taken from the
+    /// front.
+    back: DayWalker
+}
+
+impl Iterator for SunEventRange {
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let limit = self.back.events.current_time;
+        self.front.next_before(limit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Either walker alone would yield every event left.
+        let front = self.front.most_before(self.back.events.current_time);
+        let back = self.back.most_before(self.front.events.current_time);
+        (0, Some(front.min(back)))
+    }
+
+}
+
+impl DoubleEndedIterator for SunEventRange {
+
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let limit = self.front.events.current_time;
+        self.back.next_before(limit)
+    }
+
+}
+
+impl FusedIterator for SunEventRange {}
+
+/// An iterator that yields, in chronological order, the SunEvents
+/// occurring between two dates.
+#[derive(Debug, Clone)]
+pub struct ChronologicalSunEvents {
+    forecast: ForecastedSunEvents,
+    end: DateTime<Utc>
+}
+
+impl Iterator for ChronologicalSunEvents {
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.forecast.0.next_before(self.end)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.forecast.0.most_before(self.end)))
+    }
+
+}
+
+impl FusedIterator for ChronologicalSunEvents {}
+
+/// An iterator adaptor converting the times of sun events
+/// into the timezone `Tz`.
+#[derive(Debug, Clone)]
+pub struct InTimezone<I, Tz> {
+    iter: I,
+    tz: Tz
+}
+
+impl<I, Tz> Iterator for InTimezone<I, Tz>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)>,
+    Tz: TimeZone
+{
+
+    type Item = (SunEvent, DateTime<Tz>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tz = &self.tz;
+        self.iter.next().map(|(event, time)| (event, time.with_timezone(tz)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+}
+
+impl<I, Tz> FusedIterator for InTimezone<I, Tz>
+where
+    I: FusedIterator<Item = (SunEvent, DateTime<Utc>)>,
+    Tz: TimeZone
+{}
+
+/// The SunEvents happening on a calendar date of the timezone `Tz`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CivilDay<Tz: TimeZone> {
+    pub date: NaiveDate,
+    /// The events of the date, in chronological order.
+    pub events: Vec<(SunEvent, DateTime<Tz>)>
+}
+
+/// An iterator that yields the SunEvents of consecutive calendar
+/// dates of the timezone `Tz`.
+#[derive(Debug, Clone)]
+pub struct CivilDays<Tz: TimeZone> {
+    events: SunEvents,
+    tz: Tz,
+    next_date: NaiveDate,
+    /// The events computed for the UTC days around the next date.
+    computed: BTreeMap<i64, Vec<(SunEvent, DateTime<Utc>)>>
+}
+
+impl<Tz: TimeZone> Iterator for CivilDays<Tz> {
+
+    type Item = CivilDay<Tz>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let date = self.next_date;
+        let day = unix_day(date);
+        // Any timezone offset is less than a day.
+        self.computed = self.computed.split_off(&(day - 1));
+        for day in day - 1..=day + 1 {
+            if !self.computed.contains_key(&day) {
+                let whitelist = &self.events.event_whitelist;
+                let results = self.events.algorithm.event_results_unix(day, &self.events.pos, whitelist);
+                let events = whitelist.iter()
+                    .zip(results)
+                    .filter_map(|(event, result)| Some((*event, result.at().and_then(from_unix)?)))
+                    .collect();
+                self.computed.insert(day, events);
             }
         }
+        let tz = &self.tz;
+        let mut events: Vec<_> = self.computed.values()
+            .flatten()
+            .map(|(event, time)| (*event, time.with_timezone(tz)))
+            .filter(|(_, time)| time.date_naive() == date)
+            .collect();
+        events.sort_by_key(|(_, time)| time.clone());
+        self.next_date = date + Duration::days(1);
+        Some(CivilDay { date, events })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The dates never run out.
+        (usize::MAX, None)
     }
 
 }
 
+impl<Tz: TimeZone> FusedIterator for CivilDays<Tz> {}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::event::{ Event, Zenith };
+    use chrono::{ Duration, TimeZone };
+
+    #[test]
+    fn all_events_should_cover_every_zenith_in_order() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2020, 3, 1, 12, 30, 0).unwrap();
+        let events: Vec<_> = SunEvents::all_events_from(start, pos)
+            .forecast()
+            .take(14)
+            .map(|(event, _)| event)
+            .collect();
+        let expected: Vec<_> = Zenith::iter()
+            .map(|zenith| SunEvent::new(zenith, Event::Sunset))
+            .chain(vec![SunEvent::SOLAR_MIDNIGHT])
+            .chain(Zenith::iter().rev().map(|zenith| SunEvent::new(zenith, Event::Sunrise)))
+            .chain(vec![SunEvent::SOLAR_NOON])
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn empty_whitelist_should_be_an_error() {
+        let pos = GlobalPosition::at(70.0, 34.0);
+        let result = SunEvents::try_starting_from(Utc::now(), pos.clone(), &[]);
+        assert_eq!(result.err(), Some(CircadiaError::EmptyWhitelist));
+        assert!(SunEvents::try_starting_from(Utc::now(), pos, &[SunEvent::DAWN]).is_ok());
+    }
+
+    #[test]
+    fn events_should_follow_the_chosen_algorithm() {
+        use crate::core::EventResult;
+        use crate::epoch::SECS_IN_DAY;
+
+        /// Puts every event at noon UTC.
+        #[derive(Debug)]
+        struct Noon;
+
+        impl SolarAlgorithm for Noon {
+            fn event_result_unix(&self, day: i64, _: &GlobalPosition, _: SunEvent) -> EventResult<i64> {
+                EventResult::At(day * SECS_IN_DAY + SECS_IN_DAY / 2)
+            }
+        }
+
+        let pos = GlobalPosition::at(40.7831, -73.9712);
+        let start = Utc.with_ymd_and_hms(2021, 5, 1, 0, 0, 0).unwrap();
+        let times: Vec<_> = SunEvents::starting_from(start, pos.clone(), &[SunEvent::SUNRISE])
+            .with_algorithm(Noon)
+            .forecast()
+            .take(2)
+            .map(|(_, time)| time)
+            .collect();
+        assert_eq!(times, vec![start + Duration::hours(12), start + Duration::hours(36)]);
+        let usno = SunEvents::starting_from(start, pos.clone(), &[SunEvent::SUNSET]).forecast();
+        let meeus = SunEvents::starting_from(start, pos, &[SunEvent::SUNSET])
+            .with_algorithm(crate::meeus::Meeus::default())
+            .forecast();
+        for ((_, usno), (_, meeus)) in usno.zip(meeus).take(30) {
+            assert!((usno - meeus).num_seconds().abs() < 120);
+        }
+    }
+
+    #[test]
+    fn iterators_should_compute_each_day_once() {
+        use std::sync::atomic::{ AtomicUsize, Ordering };
+        use crate::core::EventResult;
+
+        /// Counts the days computed, panicking on the events computed
+        /// one at a time.
+        #[derive(Debug, Default)]
+        struct Counting(Arc<AtomicUsize>);
+
+        impl SolarAlgorithm for Counting {
+            fn event_result_unix(&self, _: i64, _: &GlobalPosition, _: SunEvent) -> EventResult<i64> {
+                panic!("the events of a day should be computed together")
+            }
+
+            fn event_results_unix(&self, day: i64, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<EventResult<i64>> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Usno::default().event_results_unix(day, pos, events)
+            }
+        }
+
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2021, 5, 1, 0, 0, 0).unwrap();
+        let days = Arc::new(AtomicUsize::new(0));
+        let events = SunEvents::all_events_from(start, pos)
+            .with_algorithm(Counting(days.clone()));
+        assert_eq!(events.clone().forecast().take(SunEvent::all().len() * 10).count(), SunEvent::all().len() * 10);
+        // The 10 days, the one before them and the next one.
+        assert!(days.swap(0, Ordering::Relaxed) <= 12);
+        let civil_days: Vec<_> = events.by_civil_day(Utc).take(10).collect();
+        assert_eq!(civil_days.len(), 10);
+        assert!(days.load(Ordering::Relaxed) <= 12);
+    }
+
+    #[test]
+    fn ranges_should_be_taken_from_either_end() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+        let range = SunEvents::starting_from(start, pos.clone(), &[SunEvent::SUNRISE, SunEvent::SUNSET]).until(end);
+        let forward: Vec<_> = range.clone().collect();
+        assert_eq!(forward.len(), 60);
+        assert!(forward.iter().all(|(_, time)| start <= *time && *time < end));
+        let mut backward: Vec<_> = range.clone().rev().collect();
+        backward.reverse();
+        assert_eq!(backward, forward);
+        let last_sunsets: Vec<_> = SunEvents::starting_from(start, pos.clone(), &[SunEvent::SUNSET])
+            .until(end)
+            .rev()
+            .take(3)
+            .collect();
+        assert_eq!(last_sunsets[0].1.date_naive(), NaiveDate::from_ymd_opt(2024, 6, 30).unwrap());
+        assert_eq!(last_sunsets[2].1.date_naive(), NaiveDate::from_ymd_opt(2024, 6, 28).unwrap());
+        // Taking from both ends meets in the middle without repeating.
+        let mut range = range;
+        let mut events = Vec::new();
+        while let (Some(first), Some(last)) = (range.next(), range.next_back()) {
+            events.push(first);
+            events.push(last);
+        }
+        events.extend(range);
+        events.sort_by_key(|(_, time)| *time);
+        assert_eq!(events, forward);
+        // A start at an event includes it.
+        let first = forward[0].1;
+        assert_eq!(SunEvents::starting_from(first, pos, &[SunEvent::SUNRISE, SunEvent::SUNSET]).until(end).next(), Some(forward[0]));
+    }
+
+    #[test]
+    fn size_hints_should_bound_the_events() {
+        fn assert_fused_and_bounded<I: FusedIterator>(mut iter: I) {
+            // The upper bound before each event, which should cover the
+            // events left.
+            let mut uppers = vec![iter.size_hint().1.unwrap()];
+            while iter.next().is_some() {
+                uppers.push(iter.size_hint().1.unwrap());
+            }
+            let count = uppers.len() - 1;
+            for (i, &upper) in uppers.iter().enumerate() {
+                assert!(upper >= count - i, "{} {}", i, upper);
+            }
+            assert!(iter.next().is_none());
+            assert_eq!(iter.size_hint(), (0, Some(0)));
+        }
+
+        let pos = GlobalPosition::at(69.6492, 18.9553);
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+        let end = start + Duration::days(60);
+        let events = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::DUSK]);
+        let range = events.clone().until(end);
+        let (_, upper) = range.size_hint();
+        assert!(upper.unwrap() >= range.clone().count() && upper.unwrap() <= 65 * 3, "{:?}", upper);
+        assert_fused_and_bounded(range.clone());
+        assert_fused_and_bounded(range.rev());
+        assert_fused_and_bounded(events.clone().history().chronological_since(start - Duration::days(10)));
+        assert_eq!(events.forecast().in_local_time().size_hint(), (usize::MAX, None));
+    }
+
+    #[test]
+    fn forecasts_and_histories_should_stop_at_their_bounds() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let whitelist = &[SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::DUSK];
+        let events = SunEvents::starting_from(start, pos.clone(), whitelist);
+        let all: Vec<_> = events.clone().forecast().take(40).collect();
+        // An end exactly at an event leaves it out.
+        let end = all[30].1;
+        let mut forecast = events.clone().forecast();
+        let skipped: Vec<_> = forecast.by_ref().take(5).collect();
+        let bounded: Vec<_> = forecast.until(end).collect();
+        assert_eq!(skipped, all[..5]);
+        assert_eq!(bounded, all[5..30]);
+        // A start exactly at an event includes it.
+        let since = all[3].1;
+        let mut history = SunEvents::starting_from(all[30].1, pos, whitelist).history();
+        let skipped: Vec<_> = history.by_ref().take(2).collect();
+        let bounded: Vec<_> = history.since(since).collect();
+        let mut expected = all[3..28].to_vec();
+        expected.reverse();
+        assert_eq!(skipped, [all[29], all[28]]);
+        assert_eq!(bounded, expected);
+        assert_eq!(events.clone().forecast().until(start).next(), None);
+        assert_eq!(events.history().since(start).next(), None);
+    }
+
+    #[test]
+    fn ranges_should_end_without_events() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let mut range = SunEvents::starting_from(start, pos, &[SunEvent::SUNSET]).until(start + Duration::days(30));
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+    }
 
     #[test]
     fn should_only_produce_events_in_the_whitelist() {
@@ -125,6 +748,103 @@ mod test {
         }
     }
 
+    #[test]
+    fn into_iter_should_forecast() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let whitelist = &[SunEvent::SUNRISE, SunEvent::SUNSET];
+        let start = Utc::now();
+        let events = SunEvents::starting_from(start, pos, whitelist);
+        let forecast: Vec<_> = events.clone().forecast().take(10).collect();
+        let mut looped = vec![];
+        for event in events {
+            if looped.len() == 10 {
+                break;
+            }
+            looped.push(event);
+        }
+        assert_eq!(forecast, looped);
+        assert!(looped.iter().all(|(_, time)| *time > start));
+    }
+
+    #[test]
+    fn forecast_should_alternate_sunrise_and_sunset() {
+        for &lng in &[-111.85515699873065, 0.0081805, 151.2093] {
+            let pos = GlobalPosition::at(40.0, lng);
+            let whitelist = &[SunEvent::SUNRISE, SunEvent::SUNSET];
+            let events: Vec<_> = SunEvents::starting_from(Utc::now(), pos, whitelist)
+                .forecast()
+                .take(100)
+                .collect();
+            for pair in events.windows(2) {
+                assert_ne!(pair[0].0, pair[1].0, "{:?}", pair);
+                assert!(pair[1].1 - pair[0].1 < Duration::hours(24), "{:?}", pair);
+            }
+        }
+    }
+
+    #[test]
+    fn history_should_alternate_sunrise_and_sunset() {
+        for &lng in &[-111.85515699873065, 0.0081805, 151.2093] {
+            let pos = GlobalPosition::at(40.0, lng);
+            let whitelist = &[SunEvent::SUNRISE, SunEvent::SUNSET];
+            let events: Vec<_> = SunEvents::starting_from(Utc::now(), pos, whitelist)
+                .history()
+                .take(100)
+                .collect();
+            for pair in events.windows(2) {
+                assert_ne!(pair[0].0, pair[1].0, "{:?}", pair);
+                assert!(pair[0].1 - pair[1].1 < Duration::hours(24), "{:?}", pair);
+            }
+        }
+    }
+
+    #[test]
+    fn solar_noon_should_fall_between_sunrise_and_sunset() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let whitelist = &[SunEvent::SUNSET, SunEvent::SOLAR_NOON, SunEvent::SUNRISE];
+        let events: Vec<_> = SunEvents::starting_from(Utc::now(), pos, whitelist)
+            .forecast()
+            .skip_while(|(event, _)| *event != SunEvent::SUNRISE)
+            .take(30)
+            .collect();
+        for triple in events.chunks(3) {
+            let kinds: Vec<_> = triple.iter().map(|(event, _)| *event).collect();
+            assert_eq!(kinds, vec![SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET]);
+        }
+    }
+
+    #[test]
+    fn solar_midnight_should_alternate_with_solar_noon() {
+        for &lng in &[-111.85515699873065, 0.0081805, 151.2093] {
+            let pos = GlobalPosition::at(78.0, lng);
+            let whitelist = &[SunEvent::SOLAR_MIDNIGHT, SunEvent::SOLAR_NOON];
+            let events: Vec<_> = SunEvents::starting_from(Utc::now(), pos, whitelist)
+                .forecast()
+                .take(100)
+                .collect();
+            for pair in events.windows(2) {
+                assert_ne!(pair[0].0, pair[1].0, "{:?}", pair);
+                assert!((pair[1].1 - pair[0].1 - Duration::hours(12)).num_minutes().abs() < 2);
+            }
+        }
+    }
+
+    #[test]
+    fn chronological_history_should_reverse_history() {
+        let pos = GlobalPosition::at(40.60710285372043, -111.85515699873065);
+        let whitelist = &[SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::DUSK];
+        let now = Utc::now();
+        let since = now - Duration::days(30);
+        let events = SunEvents::starting_from(now, pos, whitelist);
+        let mut history: Vec<_> = events.clone()
+            .history()
+            .take_while(|(_, time)| *time > since)
+            .collect();
+        history.reverse();
+        let chronological: Vec<_> = events.history().chronological_since(since).collect();
+        assert_eq!(history, chronological);
+    }
+
     #[test]
     fn forecast_should_never_skip_a_day() {
         let pos = GlobalPosition::at(40.60710285372043, -111.85515699873065);
@@ -153,4 +873,43 @@ mod test {
         }
     }
 
+    #[test]
+    fn civil_days_should_hold_the_events_of_their_date() {
+        use chrono::FixedOffset;
+
+        // Auckland, far from the meridian of UTC.
+        let pos = GlobalPosition::at(-36.8485, 174.7633);
+        let tz = FixedOffset::east_opt(13 * 3600).unwrap();
+        let start = Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+        let events = [SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET];
+        let days: Vec<_> = SunEvents::starting_from(start, pos, &events)
+            .by_civil_day(tz)
+            .take(60)
+            .collect();
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        for (day, next) in days.iter().zip(&days[1..]) {
+            assert_eq!(day.date.succ_opt(), Some(next.date));
+            let events: Vec<_> = day.events.iter().map(|(event, _)| *event).collect();
+            assert_eq!(events, vec![SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET]);
+            assert!(day.events.iter().all(|(_, time)| time.date_naive() == day.date));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "chrono-tz")]
+    fn civil_days_should_follow_named_timezones() {
+        let pos = GlobalPosition::at(59.9139, 10.7522);
+        let start = Utc.with_ymd_and_hms(2021, 3, 27, 12, 0, 0).unwrap();
+        let days: Vec<_> = SunEvents::starting_from(start, pos.clone(), &[SunEvent::SUNRISE])
+            .by_civil_day_in("Europe/Oslo")
+            .unwrap()
+            .take(2)
+            .collect();
+        // The clocks skip forward an hour on the night of March 28.
+        assert_eq!(days[0].events[0].1.format("%z").to_string(), "+0100");
+        assert_eq!(days[1].events[0].1.format("%z").to_string(), "+0200");
+        let err = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE]).by_civil_day_in("Mars/Olympus").err();
+        assert_eq!(err.map(|err| err.input), Some("Mars/Olympus".to_owned()));
+    }
+
 }
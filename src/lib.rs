@@ -2,13 +2,125 @@
 //! This crate provides a simple interface to compute information about
 //! the sunrise and sunset on arbitrary dates at any position
 //! on the earth.
+//!
+//! The `chrono` feature (enabled by default) provides the `chrono`-based
+//! API. The [epoch] module exposes the same computations in terms of
+//! Unix seconds and Julian days for builds without `chrono`, and the
+//! `core` module holds the underlying numerics. The [batch] module
+//! computes an event over many days or positions at once.
+//!
+//! The `std` feature (enabled by default) can be turned off to build the
+//! [epoch] API for `no_std` targets, computing with `libm`. The `alloc`
+//! feature then brings back the parsing and the functions computing
+//! several events at once.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 mod event;
 mod pos;
+pub mod core;
 mod algorithm;
+mod adapter;
+mod error;
+mod compass;
+mod math;
+pub mod epoch;
+pub mod batch;
+#[cfg(feature = "chrono")]
 mod iter;
+#[cfg(feature = "chrono")]
+mod clock;
+#[cfg(feature = "chrono")]
+mod playback;
+#[cfg(feature = "chrono")]
+mod local;
+#[cfg(feature = "chrono")]
+mod daylight;
+#[cfg(feature = "chrono")]
+mod twilight;
+#[cfg(feature = "chrono")]
+mod summary;
+#[cfg(feature = "chrono")]
+mod report;
+#[cfg(feature = "chrono")]
+mod schedule;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "chrono")]
+mod astro;
+#[cfg(feature = "chrono")]
+mod solar;
+#[cfg(feature = "chrono")]
+pub mod lunar;
+#[cfg(feature = "chrono")]
+pub mod solunar;
+#[cfg(feature = "chrono")]
+pub mod satellite;
+#[cfg(feature = "chrono")]
+pub mod glint;
+#[cfg(feature = "chrono")]
+pub mod shadow;
+#[cfg(feature = "chrono")]
+pub mod alignment;
+#[cfg(feature = "chrono")]
+pub mod almanac;
+#[cfg(feature = "chrono")]
+pub mod seasons;
+#[cfg(feature = "chrono")]
+pub mod meeus;
+#[cfg(feature = "spa")]
+pub mod spa;
+#[cfg(feature = "verify")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "ical")]
+pub mod ical;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 
-pub use event::{ Event, Zenith, SunEvent };
-pub use pos::GlobalPosition;
-pub use algorithm::time_of_event;
-pub use iter::{ SunEvents, ForecastedSunEvents, HistoricSunEvents };
+pub use event::{ Event, Zenith, SunEvent, TwilightPhase, DayPhase, Crossing };
+pub use pos::{ Atmosphere, GlobalPosition };
+pub use error::{ CircadiaError, DateOutOfRange, PositionError };
+#[cfg(feature = "alloc")]
+pub use error::ParseError;
+pub use compass::{ CompassPoint, Language };
+pub use crate::core::EventResult;
+pub use algorithm::{ SolarAlgorithm, Usno };
+#[cfg(feature = "time")]
+pub use adapter::time;
+#[cfg(feature = "chrono")]
+pub use adapter::chrono::{ time_of_event, events_for_positions, try_time_of_event, checked_time_of_event, event_result, time_at_altitude, time_of_event_on, time_of_event_with };
+#[cfg(feature = "chrono")]
+pub use iter::{ SunEvents, ForecastedSunEvents, HistoricSunEvents, SunEventRange, ChronologicalSunEvents, InTimezone, CivilDay, CivilDays };
+#[cfg(feature = "chrono")]
+pub use clock::{ Clock, SystemClock, ManualClock, next_event_from, next_event_from_now };
+#[cfg(feature = "chrono")]
+pub use playback::{ Playback, SimulatedClock };
+#[cfg(feature = "chrono")]
+pub use local::time_of_event_local_system;
+#[cfg(feature = "chrono")]
+pub use twilight::{ day_phase, twilight_periods, DayPhases, Twilight, TwilightPeriods };
+#[cfg(feature = "chrono")]
+pub use summary::DaySummary;
+#[cfg(feature = "chrono")]
+pub use report::{ DayReport, ReportedEvent, ReportedTwilight, ReportedTwilights, Span };
+#[cfg(feature = "chrono")]
+pub use schedule::Schedule;
+#[cfg(feature = "async")]
+pub use stream::{ SunEventStream, PlaybackStream };
+#[cfg(feature = "chrono")]
+pub use solar::{ analemma, solar_declination, subsolar_point, sun_position, terminator, SolarPosition };
+#[cfg(feature = "chrono")]
+pub use daylight::{ daylight_within, daylight_within_zenith, day_length, day_length_zenith, night_length, night_length_zenith, is_polar_day, is_polar_day_zenith, is_polar_night, is_polar_night_zenith, astronomical_darkness_duration, longest_day, shortest_day, DaylightChange, DaylightChanges, ExtremeDays };
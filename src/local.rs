@@ -0,0 +1,20 @@
+//! Conveniences for working in the timezone of the host system.
+
+use chrono::{ DateTime, Local, NaiveDate };
+use super::adapter::chrono::time_of_event_on;
+use super::event::SunEvent;
+use super::pos::GlobalPosition;
+
+/// Calculates the time of the sunrise/sunset falling on the given
+/// calendar date of the host system's timezone, at the given position
+/// on the globe.
+///
+/// Returns None if the sun never sets/rises on that day
+/// (ie if you're in the arctic).
+pub fn time_of_event_local_system(
+    date: NaiveDate,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Local>> {
+    time_of_event_on(date, &Local, pos, event)
+}
@@ -0,0 +1,184 @@
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, Datelike, Utc };
+use std::fmt;
+use crate::adapter::chrono::{ from_julian_day, julian_day };
+use crate::astro::ephemeris_to_universal;
+use super::phase::{ lunation_before, phase_instant, year_start, MoonPhase };
+use super::position::Geocentric;
+
+/// Full moons closer to the earth than this distance, in km,
+/// are called supermoons.
+pub const SUPERMOON_DISTANCE_KM: f64 = 360_000.0;
+
+/// The mean length of an anomalistic month, in days.
+const ANOMALISTIC_MONTH: f64 = 27.55454989;
+
+/// The points of the moon's orbit closest to and farthest from the earth.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Apsis {
+    Perigee,
+    Apogee
+}
+
+impl fmt::Display for Apsis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Apsis::Perigee => write!(f, "perigee"),
+            Apsis::Apogee => write!(f, "apogee"),
+        }
+    }
+}
+
+/// A passage of the moon through perigee or apogee.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApsisPassage {
+    pub apsis: Apsis,
+    pub time: DateTime<Utc>,
+    /// Distance between the centers of the earth and the moon, in km.
+    pub distance: f64
+}
+
+/// A full moon and its distance from the earth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FullMoon {
+    pub time: DateTime<Utc>,
+    /// Distance between the centers of the earth and the moon, in km.
+    pub distance: f64
+}
+
+impl FullMoon {
+
+    /// Whether this full moon is closer than [SUPERMOON_DISTANCE_KM].
+    pub fn is_supermoon(&self) -> bool {
+        self.distance < SUPERMOON_DISTANCE_KM
+    }
+
+}
+
+/// Lists the perigees and apogees of the moon occurring in `year`,
+/// in chronological order.
+pub fn apsides(year: i32) -> Vec<ApsisPassage> {
+    let first = ((year as f64 - 1999.97) * 13.2555).floor() - 1.0;
+    let mut passages: Vec<_> = (0..30)
+        .map(|i| first + i as f64 * 0.5)
+        .filter_map(apsis_passage)
+        .filter(|passage| passage.time.year() == year)
+        .collect();
+    passages.sort_by_key(|passage| passage.time);
+    passages
+}
+
+/// Lists the full moons occurring in `year`, with their distances.
+pub fn full_moons(year: i32) -> Vec<FullMoon> {
+    let start = julian_day(year_start(year));
+    let first = lunation_before(start, MoonPhase::Full);
+    (0..14)
+        .filter_map(|i| {
+            let jde = phase_instant(first + i as f64, MoonPhase::Full);
+            let time = from_julian_day(ephemeris_to_universal(jde))?;
+            Some(FullMoon { time, distance: Geocentric::at(jde).distance })
+        })
+        .filter(|full_moon| full_moon.time.year() == year)
+        .collect()
+}
+
+/// Lists the full moons of `year` that qualify as supermoons.
+pub fn supermoons(year: i32) -> Vec<FullMoon> {
+    full_moons(year)
+        .into_iter()
+        .filter(FullMoon::is_supermoon)
+        .collect()
+}
+
+/// Finds the passage through perigee (whole `k`) or apogee (`k` plus one
+/// half) nearest to the mean one, counted from the perigee of
+/// December 22, 1999.
+fn apsis_passage(k: f64) -> Option<ApsisPassage> {
+    let T = k / 1325.55;
+    let mean = 2451534.6698 + ANOMALISTIC_MONTH * k - 0.0006691 * T * T
+        - 0.000001098 * T * T * T + 0.0000000052 * T * T * T * T;
+    let apsis = if k.fract() == 0.0 { Apsis::Perigee } else { Apsis::Apogee };
+    // Perigee is a minimum of distance; search for the maximum of its opposite.
+    let sign = if apsis == Apsis::Perigee { -1.0 } else { 1.0 };
+    let objective = |jde: f64| sign * Geocentric::at(jde).distance;
+
+    // The true passage lies within a couple of days of the mean one.
+    let step = 0.125;
+    let (mut best, mut best_value) = (mean, objective(mean));
+    let mut jde = mean - 3.0;
+    while jde <= mean + 3.0 {
+        let value = objective(jde);
+        if value > best_value {
+            best = jde;
+            best_value = value;
+        }
+        jde += step;
+    }
+    let jde = golden_section_max(objective, best - step, best + step, 1.0 / 1440.0);
+    Some(ApsisPassage {
+        apsis,
+        time: from_julian_day(ephemeris_to_universal(jde))?,
+        distance: Geocentric::at(jde).distance
+    })
+}
+
+/// Maximizes a unimodal function on `[a, b]` to within `tolerance`.
+fn golden_section_max<F: Fn(f64) -> f64>(f: F, mut a: f64, mut b: f64, tolerance: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut fc, mut fd) = (f(c), f(d));
+    while b - a > tolerance {
+        if fc > fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - ratio * (b - a);
+            fc = f(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + ratio * (b - a);
+            fd = f(d);
+        }
+    }
+    (a + b) / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn perigee_should_match_published_prediction() {
+        let perigee = apsides(2019)
+            .into_iter()
+            .find(|passage| passage.apsis == Apsis::Perigee && passage.time.month() == 2)
+            .unwrap();
+        let expected = Utc.with_ymd_and_hms(2019, 2, 19, 9, 6, 0).unwrap();
+        assert!((perigee.time - expected).num_minutes().abs() < 90, "{}", perigee.time);
+        assert!((perigee.distance - 356_761.0).abs() < 100.0, "{}", perigee.distance);
+    }
+
+    #[test]
+    fn apsides_should_alternate() {
+        let passages = apsides(2020);
+        assert!(passages.len() >= 26);
+        for pair in passages.windows(2) {
+            assert_ne!(pair[0].apsis, pair[1].apsis);
+        }
+    }
+
+    #[test]
+    fn supermoons_should_be_the_closest_full_moons() {
+        let supermoons = supermoons(2019);
+        let february = Utc.with_ymd_and_hms(2019, 2, 19, 15, 54, 0).unwrap();
+        assert!(supermoons.iter().any(|moon| (moon.time - february).num_minutes().abs() < 2));
+        assert_eq!(full_moons(2019).len(), 12);
+        assert!(!supermoons.iter().any(|moon| moon.time.month() == 9));
+    }
+
+}
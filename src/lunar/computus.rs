@@ -0,0 +1,68 @@
+//! The ecclesiastical moon of the Gregorian calendar, from which the
+//! date of Easter is reckoned (the computus).
+//!
+//! The ecclesiastical moon follows tables rather than the sky, and its
+//! full moons may differ from the astronomical ones by a day or two.
+
+use chrono::{ Datelike, Duration, NaiveDate };
+
+/// Returns the date of the paschal full moon of `year`, the first
+/// ecclesiastical full moon falling on or after March 21.
+///
+/// # Panics
+/// Panics when `year` is before 1583, the Gregorian calendar's
+/// first full year.
+pub fn paschal_full_moon(year: i32) -> NaiveDate {
+    assert!(year >= 1583, "the Gregorian computus starts in 1583");
+    let golden = year % 19;
+    let century = year / 100;
+    // The solar and lunar equations of the century.
+    let solar = century / 4;
+    let lunar = (century - (century + 8) / 25 + 1) / 3;
+    let mut days = (19 * golden + century - solar - lunar + 15) % 30;
+    // The adjustments of the epact keep the full moon within April 18.
+    if days == 29 || (days == 28 && golden > 10) {
+        days -= 1;
+    }
+    march_21(year) + Duration::days(days as i64)
+}
+
+/// Returns the date of Easter Sunday of `year` in the Gregorian
+/// calendar, the first Sunday after the paschal full moon.
+///
+/// # Panics
+/// Panics when `year` is before 1583.
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let full_moon = paschal_full_moon(year);
+    let to_sunday = 7 - full_moon.weekday().num_days_from_sunday();
+    full_moon + Duration::days(to_sunday as i64)
+}
+
+fn march_21(year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, 3, 21).expect("year should be representable")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn easter_should_match_known_dates() {
+        let known = [(1818, 3, 22), (1943, 4, 25), (2000, 4, 23), (2019, 4, 21), (2024, 3, 31), (2025, 4, 20), (2285, 3, 22)];
+        for &(year, month, day) in known.iter() {
+            assert_eq!(easter_sunday(year), NaiveDate::from_ymd_opt(year, month, day).unwrap(), "{}", year);
+        }
+    }
+
+    #[test]
+    fn paschal_full_moon_should_precede_easter_within_a_week() {
+        for year in 1583..3000 {
+            let full_moon = paschal_full_moon(year);
+            assert!(full_moon >= march_21(year));
+            assert!(full_moon <= NaiveDate::from_ymd_opt(year, 4, 18).unwrap());
+            let gap = easter_sunday(year) - full_moon;
+            assert!(gap >= Duration::days(1) && gap <= Duration::days(7));
+        }
+    }
+
+}
@@ -0,0 +1,200 @@
+//! The visibility of the young crescent moon in the evening sky, which
+//! marks the start of the month in lunar calendars such as the Hijri
+//! calendar.
+//!
+//! Visibility is graded with the q-test of B. D. Yallop, "A Method for
+//! Predicting the First Sighting of the New Crescent Moon" (1997).
+
+use chrono::{ DateTime, Duration, NaiveDate, Utc };
+use std::fmt;
+use crate::adapter::chrono::{ from_julian_day, julian_day, time_of_event_on };
+use crate::astro::{ equatorial_to_horizontal, ephemeris_to_universal, greenwich_sidereal_time, EARTH_RADIUS_KM };
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+use crate::solar;
+use super::phase::{ lunation_before, phase_instant, MoonPhase };
+use super::position::Geocentric;
+use super::rise::{ moon_events_between, MoonEvent };
+
+/// The visibility classes of Yallop's q-test.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum CrescentClass {
+    /// (A) Easily visible to the naked eye.
+    EasilyVisible,
+    /// (B) Visible to the naked eye under perfect conditions.
+    VisibleInPerfectConditions,
+    /// (C) May need optical aid to find the crescent.
+    MayNeedOpticalAid,
+    /// (D) Will need optical aid to find the crescent.
+    NeedsOpticalAid,
+    /// (E) Not visible with a telescope.
+    NotVisibleWithTelescope,
+    /// (F) Not visible, below the Danjon limit.
+    NotVisible
+}
+
+impl CrescentClass {
+
+    /// The class of the q-test value `q`.
+    pub fn from_q(q: f64) -> Self {
+        if q > 0.216 {
+            CrescentClass::EasilyVisible
+        } else if q > -0.014 {
+            CrescentClass::VisibleInPerfectConditions
+        } else if q > -0.160 {
+            CrescentClass::MayNeedOpticalAid
+        } else if q > -0.232 {
+            CrescentClass::NeedsOpticalAid
+        } else if q > -0.293 {
+            CrescentClass::NotVisibleWithTelescope
+        } else {
+            CrescentClass::NotVisible
+        }
+    }
+
+    /// The letter Yallop gives to this class.
+    pub fn letter(self) -> char {
+        match self {
+            CrescentClass::EasilyVisible => 'A',
+            CrescentClass::VisibleInPerfectConditions => 'B',
+            CrescentClass::MayNeedOpticalAid => 'C',
+            CrescentClass::NeedsOpticalAid => 'D',
+            CrescentClass::NotVisibleWithTelescope => 'E',
+            CrescentClass::NotVisible => 'F'
+        }
+    }
+
+    /// Whether the crescent can be seen without optical aid.
+    pub fn is_naked_eye(self) -> bool {
+        self <= CrescentClass::VisibleInPerfectConditions
+    }
+
+}
+
+impl fmt::Display for CrescentClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CrescentClass::EasilyVisible => write!(f, "easily visible"),
+            CrescentClass::VisibleInPerfectConditions => write!(f, "visible under perfect conditions"),
+            CrescentClass::MayNeedOpticalAid => write!(f, "may need optical aid"),
+            CrescentClass::NeedsOpticalAid => write!(f, "needs optical aid"),
+            CrescentClass::NotVisibleWithTelescope => write!(f, "not visible with a telescope"),
+            CrescentClass::NotVisible => write!(f, "not visible"),
+        }
+    }
+}
+
+/// The conditions of the crescent moon on one evening.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrescentVisibility {
+    pub sunset: DateTime<Utc>,
+    pub moonset: DateTime<Utc>,
+    /// The best time to look for the crescent, four ninths of the way
+    /// from sunset to moonset.
+    pub best_time: DateTime<Utc>,
+    /// The time elapsed since the new moon at the best time.
+    pub moon_age: Duration,
+    /// The elongation of the moon from the sun, in degrees.
+    pub elongation: f64,
+    /// The difference in altitude of the moon and the sun, in degrees.
+    pub altitude_difference: f64,
+    /// The width of the crescent, in arcminutes.
+    pub width: f64,
+    /// Yallop's q-test value.
+    pub q: f64,
+    pub class: CrescentClass
+}
+
+/// Evaluates the visibility of the crescent moon on the evening of
+/// `date`, in the local mean time of `pos`.
+///
+/// Returns None when the sun doesn't set that day or the moon sets
+/// before it, leaving nothing to see.
+pub fn crescent_visibility(date: NaiveDate, pos: &GlobalPosition) -> Option<CrescentVisibility> {
+    let sunset = time_of_event_on(date, &pos.lng_timezone(), pos, SunEvent::SUNSET)?
+        .with_timezone(&Utc);
+    let (_, moonset) = moon_events_between(sunset, sunset + Duration::days(1), pos)
+        .into_iter()
+        .find(|(event, _)| *event == MoonEvent::Moonset)?;
+    // The moon must be up at sunset, having risen since it last set.
+    let (last_event, _) = moon_events_between(sunset - Duration::days(1), sunset, pos)
+        .into_iter()
+        .rev()
+        .find(|(event, _)| *event == MoonEvent::Moonrise || *event == MoonEvent::Moonset)?;
+    if last_event == MoonEvent::Moonset {
+        return None;
+    }
+    let best_time = sunset + (moonset - sunset) * 4 / 9;
+    let jd = julian_day(best_time);
+
+    let sun = solar::Geocentric::at(jd);
+    let moon = Geocentric::at(jd);
+    let sidereal = greenwich_sidereal_time(jd) + pos.lng();
+    let (sun_altitude, _) = equatorial_to_horizontal(sidereal - sun.ra, sun.dec, pos.lat());
+    let (moon_altitude, _) = equatorial_to_horizontal(sidereal - moon.ra, moon.dec, pos.lat());
+    let (sun_dec, moon_dec) = (sun.dec.to_radians(), moon.dec.to_radians());
+    let elongation = (sun_dec.sin() * moon_dec.sin()
+        + sun_dec.cos() * moon_dec.cos() * (sun.ra - moon.ra).to_radians().cos())
+        .acos();
+    let altitude_difference = moon_altitude - sun_altitude;
+
+    // The topocentric semidiameter of the moon, in arcminutes.
+    let parallax = (EARTH_RADIUS_KM / moon.distance).asin();
+    let semidiameter = 0.27245 * parallax.to_degrees() * 60.0
+        * (1.0 + moon_altitude.to_radians().sin() * parallax.sin());
+    let width = semidiameter * (1.0 - elongation.cos());
+    let q = (altitude_difference
+        - (11.8371 - 6.3226 * width + 0.7319 * width.powi(2) - 0.1018 * width.powi(3))) / 10.0;
+
+    Some(CrescentVisibility {
+        sunset,
+        moonset,
+        best_time,
+        moon_age: best_time - previous_new_moon(best_time)?,
+        elongation: elongation.to_degrees(),
+        altitude_difference,
+        width,
+        q,
+        class: CrescentClass::from_q(q)
+    })
+}
+
+fn previous_new_moon(time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let k = lunation_before(julian_day(time), MoonPhase::New);
+    [k + 1.0, k, k - 1.0].iter()
+        .filter_map(|&k| from_julian_day(ephemeris_to_universal(phase_instant(k, MoonPhase::New))))
+        .find(|new_moon| *new_moon <= time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crescent_should_grow_after_the_new_moon() {
+        // The new moon of April 8, 2024 at 18:21 UT, seen from Mecca.
+        let pos = GlobalPosition::at(21.4225, 39.8262);
+        let evening = |day| crescent_visibility(NaiveDate::from_ymd_opt(2024, 4, day).unwrap(), &pos);
+        assert!(evening(8).is_none());
+        let first = evening(9).unwrap();
+        let second = evening(10).unwrap();
+        assert!(first.moon_age > Duration::hours(20) && first.moon_age < Duration::hours(23));
+        assert!(first.sunset < first.best_time && first.best_time < first.moonset);
+        // The crescent was sighted that evening across the Middle East.
+        assert!(first.class.is_naked_eye());
+        assert!(second.q > first.q);
+        assert!(second.width > first.width);
+        assert_eq!(second.class, CrescentClass::EasilyVisible);
+    }
+
+    #[test]
+    fn classes_should_follow_q() {
+        assert_eq!(CrescentClass::from_q(0.5).letter(), 'A');
+        assert_eq!(CrescentClass::from_q(0.0).letter(), 'B');
+        assert_eq!(CrescentClass::from_q(-0.2).letter(), 'D');
+        assert_eq!(CrescentClass::from_q(-1.0).letter(), 'F');
+        assert!(CrescentClass::from_q(0.0).is_naked_eye());
+        assert!(!CrescentClass::from_q(-0.1).is_naked_eye());
+    }
+
+}
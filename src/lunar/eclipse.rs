@@ -0,0 +1,262 @@
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, Datelike, Duration, Utc };
+use std::fmt;
+use crate::adapter::chrono::{ from_julian_day, julian_day };
+use crate::astro::ephemeris_to_universal;
+use crate::pos::GlobalPosition;
+use super::phase::{ lunation_before, LunationArgs, MoonPhase };
+use super::position::moon_position;
+
+/// The kinds of lunar eclipse, by the deepest shadow the moon enters.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum LunarEclipseKind {
+    Penumbral,
+    Partial,
+    Total
+}
+
+impl fmt::Display for LunarEclipseKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LunarEclipseKind::Penumbral => write!(f, "penumbral"),
+            LunarEclipseKind::Partial => write!(f, "partial"),
+            LunarEclipseKind::Total => write!(f, "total"),
+        }
+    }
+}
+
+/// The contacts of the moon with the shadow of the earth,
+/// in chronological order.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum EclipseContact {
+    /// P1, the moon enters the penumbra.
+    PenumbralBegin,
+    /// U1, the moon enters the umbra.
+    PartialBegin,
+    /// U2, the moon is entirely within the umbra.
+    TotalBegin,
+    /// The moon is closest to the axis of the shadow.
+    Greatest,
+    /// U3, the moon starts leaving the umbra.
+    TotalEnd,
+    /// U4, the moon has left the umbra.
+    PartialEnd,
+    /// P4, the moon has left the penumbra.
+    PenumbralEnd
+}
+
+/// A lunar eclipse, as seen from the whole night side of the earth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LunarEclipse {
+    /// The kind of the eclipse.
+    pub kind: LunarEclipseKind,
+    /// The instant of greatest eclipse.
+    pub greatest: DateTime<Utc>,
+    /// The fraction of the moon's diameter covered by the penumbra.
+    pub penumbral_magnitude: f64,
+    /// The fraction of the moon's diameter covered by the umbra,
+    /// negative for penumbral eclipses.
+    pub umbral_magnitude: f64,
+    penumbral_semiduration: Duration,
+    partial_semiduration: Option<Duration>,
+    total_semiduration: Option<Duration>
+}
+
+/// A contact of a lunar eclipse, along with the altitude of the moon
+/// at that instant for some observer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisibleContact {
+    pub contact: EclipseContact,
+    pub time: DateTime<Utc>,
+    /// Degrees above the horizon of the observer.
+    pub moon_altitude: f64
+}
+
+impl VisibleContact {
+
+    /// Whether the moon is above the horizon at this contact.
+    pub fn is_visible(&self) -> bool {
+        self.moon_altitude > 0.0
+    }
+
+}
+
+impl LunarEclipse {
+
+    /// The contacts of this eclipse and their times,
+    /// in chronological order.
+    pub fn contacts(&self) -> Vec<(EclipseContact, DateTime<Utc>)> {
+        use EclipseContact::*;
+        let mut contacts = vec![(PenumbralBegin, self.greatest - self.penumbral_semiduration)];
+        if let Some(semiduration) = self.partial_semiduration {
+            contacts.push((PartialBegin, self.greatest - semiduration));
+        }
+        if let Some(semiduration) = self.total_semiduration {
+            contacts.push((TotalBegin, self.greatest - semiduration));
+        }
+        contacts.push((Greatest, self.greatest));
+        if let Some(semiduration) = self.total_semiduration {
+            contacts.push((TotalEnd, self.greatest + semiduration));
+        }
+        if let Some(semiduration) = self.partial_semiduration {
+            contacts.push((PartialEnd, self.greatest + semiduration));
+        }
+        contacts.push((PenumbralEnd, self.greatest + self.penumbral_semiduration));
+        contacts
+    }
+
+    /// The contacts of this eclipse with the altitude of the moon
+    /// at each of them, as seen from `pos`.
+    pub fn visibility(&self, pos: &GlobalPosition) -> Vec<VisibleContact> {
+        self.contacts()
+            .into_iter()
+            .map(|(contact, time)| VisibleContact {
+                contact,
+                time,
+                moon_altitude: moon_position(time, pos).altitude
+            })
+            .collect()
+    }
+
+    /// Whether the moon is above the horizon of `pos` at any point
+    /// of the penumbral phase of this eclipse.
+    pub fn is_visible_from(&self, pos: &GlobalPosition) -> bool {
+        let start = self.greatest - self.penumbral_semiduration;
+        let steps = self.penumbral_semiduration.num_minutes() / 5 * 2;
+        (0..=steps)
+            .map(|step| start + Duration::minutes(step * 5))
+            .any(|time| moon_position(time, pos).altitude > 0.0)
+    }
+
+}
+
+/// Lists the lunar eclipses whose greatest phase falls within `year`.
+pub fn lunar_eclipses(year: i32) -> Vec<LunarEclipse> {
+    let first = ((year as f64 - 2000.0) * 12.3685).floor() as i64 - 1;
+    (first..first + 15)
+        .filter_map(|k| eclipse_at_full_moon(k as f64 + 0.5))
+        .filter(|eclipse| eclipse.greatest.year() == year)
+        .collect()
+}
+
+/// Returns the first lunar eclipse whose greatest phase occurs after `time`.
+pub fn next_lunar_eclipse(time: DateTime<Utc>) -> LunarEclipse {
+    let mut k = lunation_before(julian_day(time), MoonPhase::Full) - 1.0;
+    loop {
+        if let Some(eclipse) = eclipse_at_full_moon(k + 0.5) {
+            if eclipse.greatest > time {
+                return eclipse;
+            }
+        }
+        k += 1.0;
+    }
+}
+
+/// Computes the eclipse at the full moon of lunation `k` (a whole
+/// number plus one half), if any (Meeus ch. 54).
+fn eclipse_at_full_moon(k: f64) -> Option<LunarEclipse> {
+    let LunationArgs { T, M, Mp, F, omega, E } = LunationArgs::at(k);
+    if F.sin().abs() > 0.36 {
+        return None;
+    }
+    let jde = LunationArgs::mean_phase(k);
+    let F1 = F - (0.02665 * omega.sin()).to_radians();
+    let A1 = (299.77 + 0.107408 * k - 0.009173 * T * T).to_radians();
+
+    let greatest = jde - 0.4065 * Mp.sin() + 0.1727 * E * M.sin() + 0.0161 * (2.0 * Mp).sin()
+        - 0.0097 * (2.0 * F1).sin() + 0.0073 * E * (Mp - M).sin() - 0.0050 * E * (Mp + M).sin()
+        - 0.0023 * (Mp - 2.0 * F1).sin() + 0.0021 * E * (2.0 * M).sin()
+        + 0.0012 * (Mp + 2.0 * F1).sin() + 0.0006 * E * (2.0 * Mp + M).sin()
+        - 0.0004 * (3.0 * Mp).sin() - 0.0003 * E * (M + 2.0 * F1).sin() + 0.0003 * A1.sin()
+        - 0.0002 * E * (M - 2.0 * F1).sin() - 0.0002 * E * (2.0 * Mp - M).sin()
+        - 0.0002 * omega.sin();
+
+    let P = 0.2070 * E * M.sin() + 0.0024 * E * (2.0 * M).sin() - 0.0392 * Mp.sin()
+        + 0.0116 * (2.0 * Mp).sin() - 0.0073 * E * (Mp + M).sin() + 0.0067 * E * (Mp - M).sin()
+        + 0.0118 * (2.0 * F1).sin();
+    let Q = 5.2207 - 0.0048 * E * M.cos() + 0.0020 * E * (2.0 * M).cos() - 0.3299 * Mp.cos()
+        - 0.0060 * E * (Mp + M).cos() + 0.0041 * E * (Mp - M).cos();
+    let W = F1.cos().abs();
+    let gamma = ((P * F1.cos() + Q * F1.sin()) * (1.0 - 0.0048 * W)).abs();
+    let u = 0.0059 + 0.0046 * E * M.cos() - 0.0182 * Mp.cos() + 0.0004 * (2.0 * Mp).cos()
+        - 0.0005 * (M + Mp).cos();
+
+    let penumbral_magnitude = (1.5573 + u - gamma) / 0.5450;
+    if penumbral_magnitude <= 0.0 {
+        return None;
+    }
+    let umbral_magnitude = (1.0128 - u - gamma) / 0.5450;
+
+    let n = 0.5458 + 0.0400 * Mp.cos();
+    let semiduration = |radius: f64| {
+        if radius > gamma {
+            let minutes = 60.0 / n * (radius * radius - gamma * gamma).sqrt();
+            Some(Duration::milliseconds((minutes * 60_000.0) as i64))
+        } else {
+            None
+        }
+    };
+    let penumbral_semiduration = semiduration(1.5573 + u)?;
+    let partial_semiduration = semiduration(1.0128 - u);
+    let total_semiduration = semiduration(0.4678 - u);
+    let kind = if total_semiduration.is_some() {
+        LunarEclipseKind::Total
+    } else if partial_semiduration.is_some() {
+        LunarEclipseKind::Partial
+    } else {
+        LunarEclipseKind::Penumbral
+    };
+
+    Some(LunarEclipse {
+        kind,
+        greatest: from_julian_day(ephemeris_to_universal(greatest))?,
+        penumbral_magnitude,
+        umbral_magnitude,
+        penumbral_semiduration,
+        partial_semiduration,
+        total_semiduration
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn eclipses_of_2019_should_match_published_predictions() {
+        let eclipses = lunar_eclipses(2019);
+        assert_eq!(eclipses.len(), 2);
+        let january = Utc.with_ymd_and_hms(2019, 1, 21, 5, 12, 0).unwrap();
+        assert_eq!(eclipses[0].kind, LunarEclipseKind::Total);
+        assert!((eclipses[0].greatest - january).num_minutes().abs() <= 2);
+        assert!((eclipses[0].umbral_magnitude - 1.195).abs() < 0.01);
+        let july = Utc.with_ymd_and_hms(2019, 7, 16, 21, 30, 0).unwrap();
+        assert_eq!(eclipses[1].kind, LunarEclipseKind::Partial);
+        assert!((eclipses[1].greatest - july).num_minutes().abs() <= 2);
+    }
+
+    #[test]
+    fn next_eclipse_should_follow_the_given_time() {
+        let time = Utc.with_ymd_and_hms(2022, 11, 1, 0, 0, 0).unwrap();
+        let eclipse = next_lunar_eclipse(time);
+        let expected = Utc.with_ymd_and_hms(2022, 11, 8, 10, 59, 0).unwrap();
+        assert_eq!(eclipse.kind, LunarEclipseKind::Total);
+        assert!((eclipse.greatest - expected).num_minutes().abs() <= 2);
+    }
+
+    #[test]
+    fn visibility_should_depend_on_the_observer() {
+        let eclipse = next_lunar_eclipse(Utc.with_ymd_and_hms(2022, 11, 1, 0, 0, 0).unwrap());
+        // Totality was seen across the Pacific but not from Europe.
+        let honolulu = GlobalPosition::at(21.3069, -157.8583);
+        let paris = GlobalPosition::at(48.8566, 2.3522);
+        assert!(eclipse.is_visible_from(&honolulu));
+        assert!(!eclipse.is_visible_from(&paris));
+        let contacts = eclipse.visibility(&honolulu);
+        assert_eq!(contacts.len(), 7);
+        assert!(contacts.iter().all(|contact| contact.is_visible()));
+    }
+
+}
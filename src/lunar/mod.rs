@@ -0,0 +1,19 @@
+//! This module provides computations about the moon, as seen
+//! from a position on the globe.
+
+mod position;
+mod rise;
+mod eclipse;
+mod phase;
+mod apsis;
+mod computus;
+mod crescent;
+
+pub use position::{ moon_position, MoonPosition };
+pub use rise::{ time_of_moon_event, MoonEvent, MoonEvents, ForecastedMoonEvents, HistoricMoonEvents };
+pub use eclipse::{ lunar_eclipses, next_lunar_eclipse, EclipseContact, LunarEclipse, LunarEclipseKind, VisibleContact };
+pub use phase::{ moon_illumination, moon_phases, new_moons, next_moon_phase, MoonIllumination, MoonPhase, MoonPhases };
+pub use apsis::{ apsides, full_moons, supermoons, Apsis, ApsisPassage, FullMoon, SUPERMOON_DISTANCE_KM };
+pub use crescent::{ crescent_visibility, CrescentClass, CrescentVisibility };
+pub use computus::{ easter_sunday, paschal_full_moon };
+pub(crate) use rise::moon_events_between;
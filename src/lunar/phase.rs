@@ -0,0 +1,379 @@
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, Datelike, TimeZone, Utc };
+use std::fmt;
+use crate::adapter::chrono::{ from_julian_day, julian_day };
+use crate::astro::{ ephemeris_to_universal, julian_centuries };
+use super::position::Arguments;
+
+/// The mean length of a lunation, in days.
+pub(crate) const SYNODIC_MONTH: f64 = 29.530588861;
+
+/// The Julian ephemeris day of the mean new moon of January 6, 2000,
+/// from which lunations are counted.
+const LUNATION_EPOCH: f64 = 2451550.09766;
+
+/// The principal phases of the moon.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum MoonPhase {
+    New,
+    FirstQuarter,
+    Full,
+    LastQuarter
+}
+
+impl MoonPhase {
+
+    /// The fraction of a lunation at which this phase occurs.
+    pub(crate) fn offset(self) -> f64 {
+        match self {
+            MoonPhase::New => 0.0,
+            MoonPhase::FirstQuarter => 0.25,
+            MoonPhase::Full => 0.5,
+            MoonPhase::LastQuarter => 0.75
+        }
+    }
+
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MoonPhase::New => write!(f, "new moon"),
+            MoonPhase::FirstQuarter => write!(f, "first quarter"),
+            MoonPhase::Full => write!(f, "full moon"),
+            MoonPhase::LastQuarter => write!(f, "last quarter"),
+        }
+    }
+}
+
+/// Lists the principal phases of the moon occurring in `year`,
+/// in chronological order.
+pub fn moon_phases(year: i32) -> Vec<(MoonPhase, DateTime<Utc>)> {
+    let first = lunation_before(julian_day(year_start(year)), MoonPhase::New) - 1.0;
+    let phases = [MoonPhase::New, MoonPhase::FirstQuarter, MoonPhase::Full, MoonPhase::LastQuarter];
+    (0..15)
+        .flat_map(|i| phases.iter().map(move |&phase| (first + i as f64, phase)))
+        .filter_map(|(k, phase)| Some((phase, from_julian_day(ephemeris_to_universal(phase_instant(k, phase)))?)))
+        .filter(|(_, time)| time.year() == year)
+        .collect()
+}
+
+/// Lists the new moons occurring in `year`, e.g. to find the
+/// astronomical starts of lunisolar months.
+pub fn new_moons(year: i32) -> Vec<DateTime<Utc>> {
+    moon_phases(year)
+        .into_iter()
+        .filter(|(phase, _)| *phase == MoonPhase::New)
+        .map(|(_, time)| time)
+        .collect()
+}
+
+/// Returns the first instant of `phase` occurring after `time`.
+pub fn next_moon_phase(time: DateTime<Utc>, phase: MoonPhase) -> DateTime<Utc> {
+    let mut k = lunation_before(julian_day(time), phase) - 1.0;
+    loop {
+        if let Some(instant) = from_julian_day(ephemeris_to_universal(phase_instant(k, phase))) {
+            if instant > time {
+                return instant;
+            }
+        }
+        k += 1.0;
+    }
+}
+
+/// The illuminated part of the moon at some instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonIllumination {
+    /// The illuminated fraction of the disk, from 0 at new moon
+    /// to 1 at full moon.
+    pub fraction: f64,
+    /// How far the moon is through its lunation, from 0 at new moon
+    /// through 0.5 at full moon, back to 1 at the next new moon.
+    pub phase: f64
+}
+
+impl MoonIllumination {
+
+    /// Whether the illuminated part of the moon is growing.
+    pub fn is_waxing(&self) -> bool {
+        self.phase < 0.5
+    }
+
+}
+
+/// Calculates the illumination of the moon at `time`.
+///
+/// The illuminated fraction is accurate to about 0.001
+/// (Meeus ch. 48).
+pub fn moon_illumination(time: DateTime<Utc>) -> MoonIllumination {
+    let jd = julian_day(time);
+    let args = Arguments::at(julian_centuries(jd));
+    let (D, M, Mp) = (args.D.to_radians(), args.M.to_radians(), args.Mp.to_radians());
+    let i = 180.0 - args.D - 6.289 * Mp.sin() + 2.100 * M.sin() - 1.274 * (2.0 * D - Mp).sin()
+        - 0.658 * (2.0 * D).sin() - 0.214 * (2.0 * Mp).sin() - 0.110 * D.sin();
+    let fraction = (1.0 + i.to_radians().cos()) / 2.0;
+
+    let k = lunation_before(jd, MoonPhase::New);
+    let new_moon = |k: f64| ephemeris_to_universal(phase_instant(k, MoonPhase::New));
+    // The mean phase may fall on the other side of the true one.
+    let k = if new_moon(k) > jd { k - 1.0 } else if new_moon(k + 1.0) <= jd { k + 1.0 } else { k };
+    let (previous, next) = (new_moon(k), new_moon(k + 1.0));
+    MoonIllumination { fraction, phase: (jd - previous) / (next - previous) }
+}
+
+/// An iterator that yields the principal phases of the moon,
+/// in chronological order, after a specified start date.
+#[derive(Debug, Clone)]
+pub struct MoonPhases {
+    after: DateTime<Utc>,
+    k: f64,
+    phase: MoonPhase
+}
+
+impl MoonPhases {
+
+    /// List the phases of the moon occurring after `start_date`.
+    pub fn starting_from(start_date: DateTime<Utc>) -> Self {
+        let k = lunation_before(julian_day(start_date), MoonPhase::New) - 1.0;
+        MoonPhases { after: start_date, k, phase: MoonPhase::New }
+    }
+
+}
+
+impl Iterator for MoonPhases {
+
+    type Item = (MoonPhase, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let phase = self.phase;
+            let time = from_julian_day(ephemeris_to_universal(phase_instant(self.k, phase)));
+            self.phase = match phase {
+                MoonPhase::New => MoonPhase::FirstQuarter,
+                MoonPhase::FirstQuarter => MoonPhase::Full,
+                MoonPhase::Full => MoonPhase::LastQuarter,
+                MoonPhase::LastQuarter => {
+                    self.k += 1.0;
+                    MoonPhase::New
+                }
+            };
+            match time {
+                Some(time) if time > self.after => {
+                    self.after = time;
+                    return Some((phase, time));
+                },
+                Some(_) => continue,
+                None => return None
+            }
+        }
+    }
+
+}
+
+pub(crate) fn year_start(year: i32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).single()
+        .expect("year should be representable")
+}
+
+/// The fundamental arguments of a lunation, in radians (Meeus ch. 49).
+pub(crate) struct LunationArgs {
+    pub(crate) T: f64,
+    /// Mean anomaly of the sun.
+    pub(crate) M: f64,
+    /// Mean anomaly of the moon.
+    pub(crate) Mp: f64,
+    /// Argument of latitude of the moon.
+    pub(crate) F: f64,
+    /// Longitude of the ascending node of the lunar orbit.
+    pub(crate) omega: f64,
+    /// Eccentricity correction of the earth's orbit.
+    pub(crate) E: f64
+}
+
+impl LunationArgs {
+
+    /// The arguments at lunation `k`, counted from the new moon
+    /// of January 6, 2000, with fractions denoting other phases.
+    pub(crate) fn at(k: f64) -> Self {
+        let T = k / 1236.85;
+        let (T2, T3, T4) = (T * T, T * T * T, T * T * T * T);
+        LunationArgs {
+            T,
+            M: (2.5534 + 29.10535670 * k - 0.0000014 * T2 - 0.00000011 * T3).to_radians(),
+            Mp: (201.5643 + 385.81693528 * k + 0.0107582 * T2 + 0.00001238 * T3
+                - 0.000000058 * T4).to_radians(),
+            F: (160.7108 + 390.67050284 * k - 0.0016118 * T2 - 0.00000227 * T3
+                + 0.000000011 * T4).to_radians(),
+            omega: (124.7746 - 1.56375588 * k + 0.0020672 * T2 + 0.00000215 * T3).to_radians(),
+            E: 1.0 - 0.002516 * T - 0.0000074 * T2
+        }
+    }
+
+    /// The Julian ephemeris day of the mean phase of lunation `k`.
+    pub(crate) fn mean_phase(k: f64) -> f64 {
+        let T = k / 1236.85;
+        LUNATION_EPOCH + SYNODIC_MONTH * k + 0.00015437 * T * T - 0.000000150 * T * T * T
+            + 0.00000000073 * T * T * T * T
+    }
+
+}
+
+/// The lunation number of the phase at or before the Julian day `jd`.
+pub(crate) fn lunation_before(jd: f64, phase: MoonPhase) -> f64 {
+    ((jd - LUNATION_EPOCH) / SYNODIC_MONTH - phase.offset()).floor()
+}
+
+/// Computes the Julian ephemeris day of `phase` in lunation `k`
+/// (a whole number), accurate to well under a minute (Meeus ch. 49).
+pub(crate) fn phase_instant(k: f64, phase: MoonPhase) -> f64 {
+    let k = k + phase.offset();
+    let a = LunationArgs::at(k);
+    let (M, Mp, F, omega, E) = (a.M, a.Mp, a.F, a.omega, a.E);
+    let correction = match phase {
+        MoonPhase::New => {
+            -0.40720 * Mp.sin() + 0.17241 * E * M.sin() + 0.01608 * (2.0 * Mp).sin()
+                + 0.01039 * (2.0 * F).sin() + 0.00739 * E * (Mp - M).sin()
+                - 0.00514 * E * (Mp + M).sin() + 0.00208 * E * E * (2.0 * M).sin()
+                - 0.00111 * (Mp - 2.0 * F).sin() - 0.00057 * (Mp + 2.0 * F).sin()
+                + 0.00056 * E * (2.0 * Mp + M).sin() - 0.00042 * (3.0 * Mp).sin()
+                + 0.00042 * E * (M + 2.0 * F).sin() + 0.00038 * E * (M - 2.0 * F).sin()
+                - 0.00024 * E * (2.0 * Mp - M).sin() - 0.00017 * omega.sin()
+                - 0.00007 * (Mp + 2.0 * M).sin() + small_terms(M, Mp, F)
+        },
+        MoonPhase::Full => {
+            -0.40614 * Mp.sin() + 0.17302 * E * M.sin() + 0.01614 * (2.0 * Mp).sin()
+                + 0.01043 * (2.0 * F).sin() + 0.00734 * E * (Mp - M).sin()
+                - 0.00515 * E * (Mp + M).sin() + 0.00209 * E * E * (2.0 * M).sin()
+                - 0.00111 * (Mp - 2.0 * F).sin() - 0.00057 * (Mp + 2.0 * F).sin()
+                + 0.00056 * E * (2.0 * Mp + M).sin() - 0.00042 * (3.0 * Mp).sin()
+                + 0.00042 * E * (M + 2.0 * F).sin() + 0.00038 * E * (M - 2.0 * F).sin()
+                - 0.00024 * E * (2.0 * Mp - M).sin() - 0.00017 * omega.sin()
+                - 0.00007 * (Mp + 2.0 * M).sin() + small_terms(M, Mp, F)
+        },
+        MoonPhase::FirstQuarter | MoonPhase::LastQuarter => {
+            let quarter = -0.62801 * Mp.sin() + 0.17172 * E * M.sin()
+                - 0.01183 * E * (Mp + M).sin() + 0.00862 * (2.0 * Mp).sin()
+                + 0.00804 * (2.0 * F).sin() + 0.00454 * E * (Mp - M).sin()
+                + 0.00204 * E * E * (2.0 * M).sin() - 0.00180 * (Mp - 2.0 * F).sin()
+                - 0.00070 * (Mp + 2.0 * F).sin() - 0.00040 * (3.0 * Mp).sin()
+                - 0.00034 * E * (2.0 * Mp - M).sin() + 0.00032 * E * (M + 2.0 * F).sin()
+                + 0.00032 * E * (M - 2.0 * F).sin() - 0.00028 * E * E * (Mp + 2.0 * M).sin()
+                + 0.00027 * E * (2.0 * Mp + M).sin() - 0.00017 * omega.sin()
+                - 0.00005 * (Mp - M - 2.0 * F).sin() + 0.00004 * (2.0 * Mp + 2.0 * F).sin()
+                - 0.00004 * (Mp + M + 2.0 * F).sin() + 0.00004 * (Mp - 2.0 * M).sin()
+                + 0.00003 * (Mp + M - 2.0 * F).sin() + 0.00003 * (3.0 * M).sin()
+                + 0.00002 * (2.0 * Mp - 2.0 * F).sin() + 0.00002 * (Mp - M + 2.0 * F).sin()
+                - 0.00002 * (3.0 * Mp + M).sin();
+            let W = 0.00306 - 0.00038 * E * M.cos() + 0.00026 * Mp.cos()
+                - 0.00002 * (Mp - M).cos() + 0.00002 * (Mp + M).cos() + 0.00002 * (2.0 * F).cos();
+            if phase == MoonPhase::FirstQuarter {
+                quarter + W
+            } else {
+                quarter - W
+            }
+        }
+    };
+    LunationArgs::mean_phase(k) + correction + planetary_terms(k, a.T)
+}
+
+fn small_terms(M: f64, Mp: f64, F: f64) -> f64 {
+    0.00004 * (2.0 * Mp - 2.0 * F).sin() + 0.00004 * (3.0 * M).sin()
+        + 0.00003 * (Mp + M - 2.0 * F).sin() + 0.00003 * (2.0 * Mp + 2.0 * F).sin()
+        - 0.00003 * (Mp + M + 2.0 * F).sin() + 0.00003 * (Mp - M + 2.0 * F).sin()
+        - 0.00002 * (Mp - M - 2.0 * F).sin() - 0.00002 * (3.0 * Mp + M).sin()
+        + 0.00002 * (4.0 * Mp).sin()
+}
+
+// The constant, rate and coefficient (1e-6 days) of each planetary argument.
+const PLANETARY_ARGUMENTS: [(f64, f64, f64); 13] = [
+    (251.88, 0.016321, 165.0),
+    (251.83, 26.651886, 164.0),
+    (349.42, 36.412478, 126.0),
+    (84.66, 18.206239, 110.0),
+    (141.74, 53.303771, 62.0),
+    (207.14, 2.453732, 60.0),
+    (154.84, 7.306860, 56.0),
+    (34.52, 27.261239, 47.0),
+    (207.19, 0.121824, 42.0),
+    (291.34, 1.844379, 40.0),
+    (161.72, 24.198154, 37.0),
+    (239.56, 25.513099, 35.0),
+    (331.55, 3.592518, 23.0)
+];
+
+fn planetary_terms(k: f64, T: f64) -> f64 {
+    let A1 = (299.77 + 0.107408 * k - 0.009173 * T * T).to_radians();
+    let others: f64 = PLANETARY_ARGUMENTS.iter()
+        .map(|&(constant, rate, coefficient)| coefficient * (constant + rate * k).to_radians().sin())
+        .sum();
+    (325.0 * A1.sin() + others) / 1e6
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn new_moon_should_match_meeus() {
+        // Meeus example 49.a: the new moon of 1977 February.
+        let jde = phase_instant(-283.0, MoonPhase::New);
+        assert!((jde - 2443192.65118).abs() < 0.00001, "{}", jde);
+    }
+
+    #[test]
+    fn last_quarter_should_match_meeus() {
+        // Meeus example 49.b: the first last quarter of AD 2044.
+        let jde = phase_instant(544.0, MoonPhase::LastQuarter);
+        assert!((jde - 2467636.49186).abs() < 0.00001, "{}", jde);
+    }
+
+    #[test]
+    fn phases_should_cycle_through_the_year() {
+        let phases = moon_phases(2024);
+        assert!(phases.len() >= 49 && phases.len() <= 51);
+        for pair in phases.windows(2) {
+            assert!(pair[0].1 < pair[1].1);
+            assert_ne!(pair[0].0, pair[1].0);
+        }
+        // The new moon of the total solar eclipse of April 8, 2024.
+        let expected = Utc.with_ymd_and_hms(2024, 4, 8, 18, 21, 0).unwrap();
+        assert!(new_moons(2024).iter().any(|time| (*time - expected).num_minutes().abs() <= 1));
+    }
+
+    #[test]
+    fn illuminated_fraction_should_match_meeus() {
+        // Meeus example 48.a: 1992 April 12, 0h TD.
+        let time = Utc.with_ymd_and_hms(1992, 4, 11, 23, 59, 0).unwrap();
+        let illumination = moon_illumination(time);
+        assert!((illumination.fraction - 0.6786).abs() < 0.002, "{}", illumination.fraction);
+        assert!(illumination.is_waxing());
+    }
+
+    #[test]
+    fn phases_should_follow_the_start_date_in_order() {
+        let start = Utc.with_ymd_and_hms(2024, 4, 10, 0, 0, 0).unwrap();
+        let phases: Vec<_> = MoonPhases::starting_from(start).take(9).collect();
+        assert_eq!(phases[0].0, MoonPhase::FirstQuarter);
+        assert!(phases[0].1 > start);
+        for pair in phases.windows(2) {
+            assert!(pair[0].1 < pair[1].1);
+            assert_ne!(pair[0].0, pair[1].0);
+        }
+        let full = moon_illumination(phases[1].1);
+        assert!(full.fraction > 0.99 && (full.phase - 0.5).abs() < 0.02, "{:?}", full);
+        let new = moon_illumination(phases[3].1 + chrono::Duration::minutes(1));
+        assert!(new.fraction < 0.01 && new.phase < 0.001, "{:?}", new);
+    }
+
+    #[test]
+    fn next_phase_should_follow_the_given_time() {
+        let time = Utc.with_ymd_and_hms(2024, 4, 8, 18, 0, 0).unwrap();
+        let new_moon = next_moon_phase(time, MoonPhase::New);
+        assert_eq!(new_moon.date_naive(), time.date_naive());
+        let after = next_moon_phase(new_moon, MoonPhase::New);
+        assert_eq!(after.date_naive(), chrono::NaiveDate::from_ymd_opt(2024, 5, 8).unwrap());
+    }
+
+}
@@ -0,0 +1,244 @@
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, Utc };
+use crate::astro::*;
+use crate::adapter::chrono::julian_day;
+use crate::pos::GlobalPosition;
+
+/// The position of the moon in the sky of an observer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonPosition {
+    /// Degrees above the horizon, corrected for parallax
+    /// but not for atmospheric refraction.
+    pub altitude: f64,
+    /// Degrees clockwise from true north.
+    pub azimuth: f64,
+    /// Distance between the centers of the earth and the moon, in km.
+    pub distance: f64
+}
+
+/// Calculates the topocentric position of the moon at `time`
+/// seen from `pos`.
+///
+/// This uses the main periodic terms of the lunar theory given by
+/// Jean Meeus in "Astronomical Algorithms", chapter 47, which is
+/// accurate to a few arcminutes.
+pub fn moon_position(time: DateTime<Utc>, pos: &GlobalPosition) -> MoonPosition {
+    local_coordinates(julian_day(time), pos).1
+}
+
+/// The topocentric hour angle of the moon at the Julian day `jd` (UT)
+/// seen from `pos`, in degrees within [-180, 180), along with its
+/// position. The lunar theory takes terrestrial time, the sidereal
+/// time universal time.
+pub(crate) fn local_coordinates(jd: f64, pos: &GlobalPosition) -> (f64, MoonPosition) {
+    let moon = Geocentric::at(universal_to_ephemeris(jd));
+    let H = greenwich_sidereal_time(jd) + pos.lng() - moon.ra;
+    let (H, dec) = topocentric(H, moon.dec, moon.distance, pos.lat(), 0.0);
+    let (altitude, azimuth) = equatorial_to_horizontal(H, dec, pos.lat());
+    let hour_angle = normalize_degrees(H + 180.0) - 180.0;
+    (hour_angle, MoonPosition { altitude, azimuth, distance: moon.distance })
+}
+
+/// The apparent geocentric coordinates of the moon. Angles in degrees.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Geocentric {
+    pub(crate) distance: f64,
+    pub(crate) ra: f64,
+    pub(crate) dec: f64
+}
+
+// Multiples of D, M, M' and F, and the coefficients of the sine of their
+// sum in longitude (1e-6 degrees) and of the cosine in distance (1e-3 km).
+const LONGITUDE_DISTANCE_TERMS: [(i8, i8, i8, i8, f64, f64); 32] = [
+    (0, 0, 1, 0, 6288774.0, -20905355.0),
+    (2, 0, -1, 0, 1274027.0, -3699111.0),
+    (2, 0, 0, 0, 658314.0, -2955968.0),
+    (0, 0, 2, 0, 213618.0, -569925.0),
+    (0, 1, 0, 0, -185116.0, 48888.0),
+    (0, 0, 0, 2, -114332.0, -3149.0),
+    (2, 0, -2, 0, 58793.0, 246158.0),
+    (2, -1, -1, 0, 57066.0, -152138.0),
+    (2, 0, 1, 0, 53322.0, -170733.0),
+    (2, -1, 0, 0, 45758.0, -204586.0),
+    (0, 1, -1, 0, -40923.0, -129620.0),
+    (1, 0, 0, 0, -34720.0, 108743.0),
+    (0, 1, 1, 0, -30383.0, 104755.0),
+    (2, 0, 0, -2, 15327.0, 10321.0),
+    (0, 0, 1, 2, -12528.0, 0.0),
+    (0, 0, 1, -2, 10980.0, 79661.0),
+    (4, 0, -1, 0, 10675.0, -34782.0),
+    (0, 0, 3, 0, 10034.0, -23210.0),
+    (4, 0, -2, 0, 8548.0, -21636.0),
+    (2, 1, -1, 0, -7888.0, 24208.0),
+    (2, 1, 0, 0, -6766.0, 30824.0),
+    (1, 0, -1, 0, -5163.0, -8379.0),
+    (1, 1, 0, 0, 4987.0, -16675.0),
+    (2, -1, 1, 0, 4036.0, -12831.0),
+    (2, 0, 2, 0, 3994.0, -10445.0),
+    (4, 0, 0, 0, 3861.0, -11650.0),
+    (2, 0, -3, 0, 3665.0, 14403.0),
+    (0, 1, -2, 0, -2689.0, -7003.0),
+    (2, 0, -1, 2, -2602.0, 0.0),
+    (2, -1, -2, 0, 2390.0, 10056.0),
+    (1, 0, 1, 0, -2348.0, 6322.0),
+    (2, -2, 0, 0, 2236.0, -9884.0)
+];
+
+// Multiples of D, M, M' and F, and the coefficient of the sine of their
+// sum in latitude (1e-6 degrees).
+const LATITUDE_TERMS: [(i8, i8, i8, i8, f64); 28] = [
+    (0, 0, 0, 1, 5128122.0),
+    (0, 0, 1, 1, 280602.0),
+    (0, 0, 1, -1, 277693.0),
+    (2, 0, 0, -1, 173237.0),
+    (2, 0, -1, 1, 55413.0),
+    (2, 0, -1, -1, 46271.0),
+    (2, 0, 0, 1, 32573.0),
+    (0, 0, 2, 1, 17198.0),
+    (2, 0, 1, -1, 9266.0),
+    (0, 0, 2, -1, 8822.0),
+    (2, -1, 0, -1, 8216.0),
+    (2, 0, -2, -1, 4324.0),
+    (2, 0, 1, 1, 4200.0),
+    (2, 1, 0, -1, -3359.0),
+    (2, -1, -1, 1, 2463.0),
+    (2, -1, 0, 1, 2211.0),
+    (2, -1, -1, -1, 2065.0),
+    (0, 1, -1, -1, -1870.0),
+    (4, 0, -1, -1, 1828.0),
+    (0, 1, 0, 1, -1794.0),
+    (0, 0, 0, 3, -1749.0),
+    (0, 1, -1, 1, -1565.0),
+    (1, 0, 0, 1, -1491.0),
+    (0, 1, 1, 1, -1475.0),
+    (0, 1, 1, -1, -1410.0),
+    (0, 1, 0, -1, -1344.0),
+    (1, 0, 0, -1, -1335.0),
+    (0, 0, 3, 1, 1107.0)
+];
+
+impl Geocentric {
+
+    /// The coordinates of the moon at the Julian day `jd`.
+    pub(crate) fn at(jd: f64) -> Self {
+        let T = julian_centuries(jd);
+        let args = Arguments::at(T);
+        let E = 1.0 - 0.002516 * T - 0.0000074 * T * T;
+        let eccentricity = |m: i8| E.powi(m.abs() as i32);
+
+        let (mut sl, mut sr, mut sb) = (0.0, 0.0, 0.0);
+        for &(d, m, mp, f, l, r) in LONGITUDE_DISTANCE_TERMS.iter() {
+            let arg = args.combine(d, m, mp, f);
+            sl += l * eccentricity(m) * arg.sin();
+            sr += r * eccentricity(m) * arg.cos();
+        }
+        for &(d, m, mp, f, b) in LATITUDE_TERMS.iter() {
+            sb += b * eccentricity(m) * args.combine(d, m, mp, f).sin();
+        }
+
+        let A1 = (119.75 + 131.849 * T).to_radians();
+        let A2 = (53.09 + 479264.290 * T).to_radians();
+        let A3 = (313.45 + 481266.484 * T).to_radians();
+        let (Lp, Mp, F) = (args.Lp.to_radians(), args.Mp.to_radians(), args.F.to_radians());
+        sl += 3958.0 * A1.sin() + 1962.0 * (Lp - F).sin() + 318.0 * A2.sin();
+        sb += -2235.0 * Lp.sin() + 382.0 * A3.sin() + 175.0 * (A1 - F).sin()
+            + 175.0 * (A1 + F).sin() + 127.0 * (Lp - Mp).sin() - 115.0 * (Lp + Mp).sin();
+
+        let (dpsi, deps) = nutation(T);
+        let lambda = normalize_degrees(args.Lp + sl / 1e6 + dpsi);
+        let beta = sb / 1e6;
+        let distance = 385000.56 + sr / 1000.0;
+        let (ra, dec) = ecliptic_to_equatorial(lambda, beta, mean_obliquity(T) + deps);
+        Geocentric { distance, ra, dec }
+    }
+
+}
+
+/// The fundamental arguments of the lunar theory, in degrees.
+pub(crate) struct Arguments {
+    /// Mean longitude of the moon.
+    pub(crate) Lp: f64,
+    /// Mean elongation of the moon.
+    pub(crate) D: f64,
+    /// Mean anomaly of the sun.
+    pub(crate) M: f64,
+    /// Mean anomaly of the moon.
+    pub(crate) Mp: f64,
+    /// Argument of latitude of the moon.
+    pub(crate) F: f64
+}
+
+impl Arguments {
+
+    pub(crate) fn at(T: f64) -> Self {
+        let T2 = T * T;
+        let T3 = T2 * T;
+        let T4 = T3 * T;
+        Arguments {
+            Lp: normalize_degrees(218.3164477 + 481267.88123421 * T - 0.0015786 * T2
+                + T3 / 538841.0 - T4 / 65194000.0),
+            D: normalize_degrees(297.8501921 + 445267.1114034 * T - 0.0018819 * T2
+                + T3 / 545868.0 - T4 / 113065000.0),
+            M: normalize_degrees(357.5291092 + 35999.0502909 * T - 0.0001536 * T2
+                + T3 / 24490000.0),
+            Mp: normalize_degrees(134.9633964 + 477198.8675055 * T + 0.0087414 * T2
+                + T3 / 69699.0 - T4 / 14712000.0),
+            F: normalize_degrees(93.2720950 + 483202.0175233 * T - 0.0036539 * T2
+                - T3 / 3526000.0 + T4 / 863310000.0)
+        }
+    }
+
+    /// The angle `d D + m M + mp M' + f F`, in radians.
+    fn combine(&self, d: i8, m: i8, mp: i8, f: i8) -> f64 {
+        (d as f64 * self.D + m as f64 * self.M + mp as f64 * self.Mp + f as f64 * self.F)
+            .to_radians()
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn geocentric_position_should_match_meeus() {
+        // Meeus example 47.a: 1992 April 12, 0h TD.
+        let moon = Geocentric::at(2448724.5);
+        assert!((moon.distance - 368409.7).abs() < 20.0, "{}", moon.distance);
+        assert!((moon.ra - 134.688470).abs() < 0.01, "{}", moon.ra);
+        assert!((moon.dec - 13.768368).abs() < 0.01, "{}", moon.dec);
+    }
+
+    #[test]
+    fn parallax_should_lower_the_moon() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let time = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap();
+        let jd = julian_day(time);
+        let moon = Geocentric::at(universal_to_ephemeris(jd));
+        let H = greenwich_sidereal_time(jd) + pos.lng() - moon.ra;
+        let (geo_alt, _) = equatorial_to_horizontal(H, moon.dec, pos.lat());
+        let topo = moon_position(time, &pos);
+        let parallax = (EARTH_RADIUS_KM / moon.distance).asin().to_degrees();
+        let expected = parallax * geo_alt.to_radians().cos();
+        assert!((geo_alt - topo.altitude - expected).abs() < 0.05);
+    }
+
+    #[test]
+    fn positions_should_be_computed_in_terrestrial_time() {
+        // Meeus example 47.a is at 1992 April 12, 0h TD, when ΔT was
+        // about 59 seconds.
+        let pos = GlobalPosition::at(0.0, 0.0);
+        let time = Utc.with_ymd_and_hms(1992, 4, 11, 23, 59, 1).unwrap();
+        let jd = julian_day(time);
+        let moon = Geocentric::at(2448724.5);
+        let H = greenwich_sidereal_time(jd) + pos.lng() - moon.ra;
+        let (H, dec) = topocentric(H, moon.dec, moon.distance, pos.lat(), 0.0);
+        let (altitude, azimuth) = equatorial_to_horizontal(H, dec, pos.lat());
+        let topo = moon_position(time, &pos);
+        assert!((topo.altitude - altitude).abs() < 0.002, "{} {}", topo.altitude, altitude);
+        assert!((topo.azimuth - azimuth).abs() < 0.002, "{} {}", topo.azimuth, azimuth);
+    }
+
+}
@@ -0,0 +1,311 @@
+use chrono::{ DateTime, Duration, NaiveDate, Utc };
+use std::fmt;
+use std::iter::FusedIterator;
+use crate::adapter::chrono::{ from_julian_day, from_unix, julian_day };
+use crate::epoch::SECS_IN_DAY;
+use crate::iter::{ DayEvents, DayWalker };
+use crate::pos::GlobalPosition;
+use super::position::local_coordinates;
+
+/// The rising and setting of the moon, and its transits
+/// across the meridian.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum MoonEvent {
+    /// The upper limb of the moon appears above the horizon.
+    Moonrise,
+    /// The moon crosses the meridian, at its highest.
+    Transit,
+    /// The upper limb of the moon disappears below the horizon.
+    Moonset,
+    /// The moon crosses the meridian below the horizon, at its lowest.
+    LowerTransit
+}
+
+impl fmt::Display for MoonEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MoonEvent::Moonrise => write!(f, "moonrise"),
+            MoonEvent::Transit => write!(f, "moon transit"),
+            MoonEvent::Moonset => write!(f, "moonset"),
+            MoonEvent::LowerTransit => write!(f, "moon lower transit"),
+        }
+    }
+}
+
+/// Calculates the time of `event` on the given date, in the local mean
+/// time of `pos`.
+///
+/// Returns None if the moon doesn't rise, set or transit that day:
+/// since it comes back to the meridian every 24h50m on average, each
+/// event skips a day about once a month, and near the poles the moon
+/// may stay up or down for days.
+pub fn time_of_moon_event(date: NaiveDate, pos: &GlobalPosition, event: MoonEvent) -> Option<DateTime<Utc>> {
+    let midnight = date.and_hms_opt(0, 0, 0)
+        .expect("midnight should be a valid time")
+        .and_utc() - Duration::seconds((pos.lng() * 240.0) as i64);
+    moon_events_between(midnight, midnight + Duration::days(1), pos)
+        .into_iter()
+        .find(|(found, _)| *found == event)
+        .map(|(_, time)| time)
+}
+
+/// This struct allows one to create iterators over moon events moving
+/// forward or backward in time.
+#[derive(Debug, Clone)]
+pub struct MoonEvents {
+    pos: GlobalPosition,
+    current_time: DateTime<Utc>,
+    event_whitelist: Vec<MoonEvent>
+}
+
+impl MoonEvents {
+
+    /// List MoonEvents starting from the `start_date`, computed at `position`,
+    /// including only the MoonEvents listed in the `event_whitelist`.
+    /// # Panics
+    /// Panics when `event_whitelist` is empty.
+    pub fn starting_from(start_date: DateTime<Utc>, position: GlobalPosition, event_whitelist: &[MoonEvent]) -> Self {
+        assert!(!event_whitelist.is_empty());
+        let mut event_whitelist = event_whitelist.to_owned();
+        event_whitelist.sort();
+        event_whitelist.dedup();
+        MoonEvents {
+            pos: position,
+            current_time: start_date,
+            event_whitelist
+        }
+    }
+
+    /// List MoonEvents occurring after the start_date.
+    pub fn forecast(self) -> ForecastedMoonEvents {
+        ForecastedMoonEvents(DayWalker::new(self, 1))
+    }
+
+    /// List MoonEvents occurring before the start_date.
+    pub fn history(self) -> HistoricMoonEvents {
+        HistoricMoonEvents(DayWalker::new(self, -1))
+    }
+
+}
+
+impl DayEvents for MoonEvents {
+
+    type Event = MoonEvent;
+    // The events are searched within each UTC day.
+    const SPILL: i64 = 0;
+
+    fn current_time(&self) -> DateTime<Utc> {
+        self.current_time
+    }
+
+    fn set_current_time(&mut self, time: DateTime<Utc>) {
+        self.current_time = time;
+    }
+
+    fn most_per_day(&self) -> usize {
+        // Near the poles, the moon may rise or set twice within a day.
+        2 * self.event_whitelist.len()
+    }
+
+    fn events_on(&self, day: i64) -> Vec<(MoonEvent, DateTime<Utc>)> {
+        let midnight = from_unix(day * SECS_IN_DAY).expect("the day should be representable");
+        let whitelist = &self.event_whitelist;
+        moon_events_between(midnight, midnight + Duration::days(1), &self.pos)
+            .into_iter()
+            .filter(|(event, _)| whitelist.contains(event))
+            .collect()
+    }
+
+}
+
+/// An iterator that yields MoonEvents that occur after
+/// a specified start date.
+#[derive(Debug, Clone)]
+pub struct ForecastedMoonEvents(DayWalker<MoonEvents>);
+
+impl Iterator for ForecastedMoonEvents {
+
+    type Item = (MoonEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The events never run out.
+        (usize::MAX, None)
+    }
+
+}
+
+impl FusedIterator for ForecastedMoonEvents {}
+
+/// An iterator that yields MoonEvents that occur before
+/// a specified start date.
+#[derive(Debug, Clone)]
+pub struct HistoricMoonEvents(DayWalker<MoonEvents>);
+
+impl Iterator for HistoricMoonEvents {
+
+    type Item = (MoonEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The events never run out.
+        (usize::MAX, None)
+    }
+
+}
+
+impl FusedIterator for HistoricMoonEvents {}
+
+/// The step at which the position of the moon is sampled.
+const STEP_MINUTES: i64 = 10;
+
+/// Lists the events of the moon between `start` and `end` at `pos`,
+/// in chronological order, to about a second.
+pub(crate) fn moon_events_between(start: DateTime<Utc>, end: DateTime<Utc>, pos: &GlobalPosition) -> Vec<(MoonEvent, DateTime<Utc>)> {
+    let step = STEP_MINUTES as f64 / 1440.0;
+    // Sample on a fixed grid, so that overlapping spans agree exactly
+    // on the events they share.
+    let (first, last) = ((julian_day(start) / step).floor() as i64, (julian_day(end) / step).ceil() as i64);
+    let mut events = vec![];
+    let mut previous = Sample::at(first as f64 * step, pos);
+    for i in first..last {
+        let (jd, next_jd) = (i as f64 * step, (i + 1) as f64 * step);
+        let next = Sample::at(next_jd, pos);
+        for &event in &[MoonEvent::Moonrise, MoonEvent::Transit, MoonEvent::Moonset, MoonEvent::LowerTransit] {
+            if previous.value(event) < 0.0 && next.value(event) >= 0.0 {
+                if let Some(time) = from_julian_day(refine(jd, next_jd, pos, event)) {
+                    events.push((event, time));
+                }
+            }
+        }
+        previous = next;
+    }
+    events.retain(|(_, time)| *time >= start && *time < end);
+    events.sort_by_key(|(_, time)| *time);
+    events
+}
+
+/// Finds the instant within `[from, to]` at which the value of `event`
+/// changes sign, by bisection.
+fn refine(mut from: f64, mut to: f64, pos: &GlobalPosition, event: MoonEvent) -> f64 {
+    while to - from > 1.0 / 86_400.0 {
+        let middle = (from + to) / 2.0;
+        if Sample::at(middle, pos).value(event) < 0.0 {
+            from = middle;
+        } else {
+            to = middle;
+        }
+    }
+    (from + to) / 2.0
+}
+
+/// The quantities whose change of sign marks each moon event.
+struct Sample {
+    hour_angle: f64,
+    /// Altitude of the upper limb above the apparent horizon.
+    altitude: f64
+}
+
+impl Sample {
+
+    fn at(jd: f64, pos: &GlobalPosition) -> Self {
+        let (hour_angle, moon) = local_coordinates(jd, pos);
+        // The semidiameter of the moon and the standard refraction
+        // at the horizon of 34 arcminutes.
+        let semidiameter = 358_473_400.0 / moon.distance / 3600.0;
+        Sample { hour_angle, altitude: moon.altitude + semidiameter + 34.0 / 60.0 }
+    }
+
+    /// A value rising through zero at `event`. Hour angles are confined
+    /// to the quarter around the crossing so that the wrap at 180
+    /// degrees isn't mistaken for one.
+    fn value(&self, event: MoonEvent) -> f64 {
+        let quarter = |angle: f64| if angle.abs() < 90.0 { angle } else { f64::NAN };
+        match event {
+            MoonEvent::Transit => quarter(self.hour_angle),
+            MoonEvent::LowerTransit => {
+                quarter(if self.hour_angle < 0.0 { self.hour_angle + 180.0 } else { self.hour_angle - 180.0 })
+            },
+            MoonEvent::Moonrise => self.altitude,
+            MoonEvent::Moonset => -self.altitude
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::lunar::moon_position;
+
+    #[test]
+    fn moon_should_rise_and_set_at_the_horizon() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0).unwrap();
+        let events = moon_events_between(start, start + Duration::days(10), &pos);
+        let transits = events.iter().filter(|(event, _)| *event == MoonEvent::Transit).count();
+        assert!(transits == 9 || transits == 10);
+        for (event, time) in events {
+            if event == MoonEvent::Moonrise || event == MoonEvent::Moonset {
+                let altitude = moon_position(time, &pos).altitude;
+                assert!(altitude < 0.0 && altitude > -1.0, "{}", altitude);
+            }
+        }
+    }
+
+    #[test]
+    fn moonrise_should_match_published_times() {
+        // Moonrise over Greenwich on 2020-03-09, the night of the full
+        // moon, was at 17:38 UTC.
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let date = NaiveDate::from_ymd_opt(2020, 3, 9).unwrap();
+        let moonrise = time_of_moon_event(date, &pos, MoonEvent::Moonrise).unwrap();
+        let expected = Utc.with_ymd_and_hms(2020, 3, 9, 17, 38, 0).unwrap();
+        assert!((moonrise - expected).num_minutes().abs() <= 3, "{}", moonrise);
+    }
+
+    #[test]
+    fn forecast_and_history_should_mirror_each_other() {
+        let pos = GlobalPosition::at(40.60710285372043, -111.85515699873065);
+        let whitelist = &[MoonEvent::Moonrise, MoonEvent::Moonset];
+        let start = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+        let forecast: Vec<_> = MoonEvents::starting_from(start, pos.clone(), whitelist)
+            .forecast()
+            .take(20)
+            .collect();
+        for pair in forecast.windows(2) {
+            assert_ne!(pair[0].0, pair[1].0, "{:?}", pair);
+            assert!(pair[0].1 < pair[1].1);
+        }
+        let end = forecast.last().unwrap().1 + Duration::seconds(1);
+        let mut history: Vec<_> = MoonEvents::starting_from(end, pos, whitelist)
+            .history()
+            .take(20)
+            .collect();
+        history.reverse();
+        assert_eq!(forecast, history);
+    }
+
+    #[test]
+    fn repeated_events_should_be_yielded_once() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0).unwrap();
+        let once: Vec<_> = MoonEvents::starting_from(start, pos.clone(), &[MoonEvent::Moonrise])
+            .forecast()
+            .take(5)
+            .collect();
+        let repeated = MoonEvents::starting_from(start, pos, &[MoonEvent::Moonrise, MoonEvent::Moonrise]).forecast();
+        assert_eq!(repeated.size_hint(), (usize::MAX, None));
+        assert_eq!(repeated.clone().take(5).collect::<Vec<_>>(), once);
+        let history = MoonEvents::starting_from(once[4].1, GlobalPosition::at(51.4810066, 0.0081805), &[MoonEvent::Moonrise]).history();
+        assert_eq!(history.take(4).collect::<Vec<_>>(), once.iter().rev().skip(1).copied().collect::<Vec<_>>());
+    }
+
+}
@@ -0,0 +1,43 @@
+//! The floating point functions of `std`, computed with `libm` when
+//! building without it.
+//!
+//! They're called as `Float::floor(x)` rather than `x.floor()`: the
+//! inherent methods of `f64` come along whenever any crate of the build
+//! links `std`, as a dependency built with its `std` feature does, and
+//! would then shadow these.
+
+/// Defines each `$method` of an impl for `$t` as the inherent method of
+/// the same name with `std`, and as `libm::$libm` without it.
+macro_rules! std_or_libm {
+    ($t:ident, $($method:ident => $libm:ident),*) => {
+        $(
+            fn $method(self) -> Self {
+                #[cfg(any(feature = "std", test))]
+                return $t::$method(self);
+                #[cfg(not(any(feature = "std", test)))]
+                return libm::$libm(self);
+            }
+        )*
+    };
+}
+
+pub(crate) use std_or_libm;
+
+/// The methods of `f64` that `core` lacks.
+pub(crate) trait Float {
+    fn floor(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn round(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Float for f64 {
+
+    std_or_libm!(f64, floor => floor, round => round, sqrt => sqrt);
+
+    fn rem_euclid(self, rhs: f64) -> f64 {
+        let r = self % rhs;
+        if r < 0.0 { r + rhs.abs() } else { r }
+    }
+
+}
@@ -0,0 +1,235 @@
+//! A higher-precision computation of the sunrise/sunset, from the
+//! apparent position of the sun given by Jean Meeus, "Astronomical
+//! Algorithms", chapter 25, refined by iteration as in chapter 15.
+//!
+//! Where the USNO approximation of the crate root agrees with
+//! almanacs to a minute or two, this agrees to a few seconds, for
+//! about ten times the computation.
+
+// `chrono::Date` is deprecated upstream but remains the date type
+// of this crate's public API.
+#![allow(deprecated)]
+
+use chrono::{ Date, DateTime, Utc };
+use std::ops::RangeInclusive;
+use crate::adapter::chrono::time_of_event_with;
+use crate::algorithm::{ zenith_angle, SolarAlgorithm };
+use crate::astro::{ delta_t, greenwich_sidereal_time, J2000 };
+use crate::core::EventResult;
+use crate::epoch::{ julian_day_from_unix, unix_from_julian_day, SECS_IN_DAY };
+use crate::event::{ Event, SunEvent };
+use crate::pos::GlobalPosition;
+use crate::solar::Geocentric;
+
+/// The [SolarAlgorithm] of this module.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Meeus {
+    /// How to tell the terrestrial time of the motion of the sun from
+    /// the universal time of the rotation of the earth.
+    pub delta_t: DeltaT
+}
+
+impl SolarAlgorithm for Meeus {
+
+    fn event_result_unix(&self, day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+        let noon = julian_day_from_unix((day * SECS_IN_DAY) as f64) + 0.5;
+        let delta_t = self.delta_t.seconds(year_of(noon)) / SECS_IN_DAY as f64;
+        solve(day, pos, event, |jd| {
+            let sun = Geocentric::at(jd + delta_t);
+            (greenwich_sidereal_time(jd) - sun.ra, sun.dec)
+        })
+        .map(|jd| unix_from_julian_day(jd).round() as i64)
+    }
+
+    fn supported_years(&self) -> RangeInclusive<i32> {
+        SUPPORTED_YEARS
+    }
+
+}
+
+/// The years over which the position of the sun keeps to about 0.01
+/// degree and ΔT can be estimated.
+pub const SUPPORTED_YEARS: RangeInclusive<i32> = -1999..=3000;
+
+/// The difference ΔT = TT - UT between the terrestrial time, which
+/// drives the motion of the sun, and the universal time, which follows
+/// the irregular rotation of the earth.
+///
+/// ΔT is about a minute today and grows to hours in antiquity. The
+/// USNO approximation of the crate root doesn't depend on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DeltaT {
+    /// Takes universal time for terrestrial time.
+    Ignored,
+    /// Estimates ΔT from the polynomials of Espenak and Meeus.
+    #[default]
+    Estimated,
+    /// A known ΔT, in seconds, such as one published by the IERS.
+    Seconds(f64)
+}
+
+impl DeltaT {
+
+    /// The ΔT in the (fractional) `year`, in seconds.
+    pub fn seconds(self, year: f64) -> f64 {
+        match self {
+            DeltaT::Ignored => 0.0,
+            DeltaT::Estimated => delta_t(year),
+            DeltaT::Seconds(seconds) => seconds
+        }
+    }
+
+}
+
+/// The (fractional) year of the Julian day `jd`, close enough to
+/// estimate ΔT.
+pub(crate) fn year_of(jd: f64) -> f64 {
+    2000.0 + (jd - J2000) / 365.2425
+}
+
+/// The rotation of the earth relative to the stars, in degrees per day.
+const SIDEREAL_RATE: f64 = 360.98564736629;
+
+/// Like [time_of_event](crate::time_of_event), from the Meeus
+/// position of the sun.
+pub fn time_of_event(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Utc>> {
+    time_of_event_with(&Meeus::default(), date, pos, event)
+}
+
+/// Like [event_result_unix](crate::epoch::event_result_unix), from the
+/// Meeus position of the sun.
+pub fn event_result_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+    Meeus::default().event_result_unix(day, pos, event)
+}
+
+/// Finds the Julian day of `event` on the local mean `day` at `pos`,
+/// given the apparent Greenwich hour angle and declination of the sun,
+/// in degrees, at any Julian day.
+///
+/// Starting from local mean noon, or midnight for the solar midnight,
+/// this moves to where the sun reaches the hour angle of the event,
+/// until the time settles.
+pub(crate) fn solve<F: Fn(f64) -> (f64, f64)>(day: i64, pos: &GlobalPosition, event: SunEvent, position: F) -> EventResult<f64> {
+    let mean_noon = julian_day_from_unix((day * SECS_IN_DAY) as f64) + 0.5 - pos.lng() / 360.0;
+    let mut jd = match event.event {
+        Event::SolarMidnight => mean_noon - 0.5,
+        _ => mean_noon
+    };
+    let (lat, zenith) = (pos.lat().to_radians(), zenith_angle(pos, event.zenith).to_radians());
+    for _ in 0..10 {
+        let (greenwich_hour_angle, dec) = position(jd);
+        let target = match event.event {
+            Event::SolarNoon => 0.0,
+            Event::SolarMidnight => 180.0,
+            Event::Sunrise | Event::Sunset => {
+                let dec = dec.to_radians();
+                let cos_h = (zenith.cos() - lat.sin() * dec.sin()) / (lat.cos() * dec.cos());
+                if cos_h > 1.0 {
+                    return EventResult::PolarNight;
+                }
+                if cos_h < -1.0 {
+                    return EventResult::PolarDay;
+                }
+                let h = cos_h.acos().to_degrees();
+                if event.is_sunrise() { -h } else { h }
+            }
+        };
+        let hour_angle = greenwich_hour_angle + pos.lng();
+        let step = ((target - hour_angle).rem_euclid(360.0) + 180.0).rem_euclid(360.0) - 180.0;
+        jd += step / SIDEREAL_RATE;
+        // Less than a tenth of a second.
+        if step.abs() < 0.0004 {
+            break;
+        }
+    }
+    EventResult::At(jd)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::epoch::time_of_event_unix;
+    use crate::event::Zenith;
+    use crate::solar;
+
+    #[test]
+    fn sun_should_stand_at_the_zenith_of_the_event() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let day = 18_262; // 2020-01-01
+        for &event in &[SunEvent::SUNRISE, SunEvent::SUNSET, SunEvent::DAWN, SunEvent::new(Zenith::Nautical, Event::Sunset)] {
+            let time = event_result_unix(day, &pos, event).at().unwrap();
+            let (altitude, _) = solar::horizontal(julian_day_from_unix(time as f64), &pos);
+            let expected = 90.0 - zenith_angle(&pos, event.zenith);
+            assert!((altitude - expected).abs() < 0.005, "{} {}", event, altitude);
+        }
+    }
+
+    #[test]
+    fn events_should_agree_with_the_usno_approximation() {
+        let places = [(51.4810066, 0.0081805), (40.7831, -73.9712), (-33.8688, 151.2093), (64.1466, -21.9426)];
+        for &(lat, lng) in &places {
+            let pos = GlobalPosition::at(lat, lng);
+            for day in (18_262..18_628).step_by(15) {
+                for &event in &[SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET, SunEvent::SOLAR_MIDNIGHT] {
+                    let usno = time_of_event_unix(day, &pos, event).unwrap();
+                    let meeus = event_result_unix(day, &pos, event).at().unwrap();
+                    assert!((usno - meeus).abs() < 120, "{} {} {} {}", lat, day, event, usno - meeus);
+                }
+            }
+        }
+        let pole = GlobalPosition::at(78.2232, 15.6267);
+        assert_eq!(event_result_unix(18_799, &pole, SunEvent::SUNSET), EventResult::PolarDay);
+        assert_eq!(event_result_unix(18_982, &pole, SunEvent::SUNRISE), EventResult::PolarNight);
+    }
+
+    #[test]
+    fn delta_t_should_shift_events_far_from_today() {
+        assert_eq!(DeltaT::Ignored.seconds(2000.0), 0.0);
+        assert!((DeltaT::Estimated.seconds(2000.0) - 63.8).abs() < 1.0);
+        let pos = GlobalPosition::at(41.9028, 12.4964);
+        // 0500-06-21
+        let day = -536_735;
+        let estimated = DeltaT::Estimated.seconds(500.5);
+        let sunrise = |delta_t| Meeus { delta_t }.event_result_unix(day, &pos, SunEvent::SUNRISE).at().unwrap();
+        assert_eq!(sunrise(DeltaT::Estimated), sunrise(DeltaT::Seconds(estimated)));
+        let shift = (sunrise(DeltaT::Estimated) - sunrise(DeltaT::Ignored)).abs();
+        assert!(shift > 2 && shift < 60, "{}", shift);
+    }
+
+    #[test]
+    fn refinement_should_tighten_the_usno_approximation() {
+        let pos = GlobalPosition::at(64.1466, -21.9426);
+        let usno = |refine| {
+            let algorithm = crate::algorithm::Usno { refine };
+            (18_262..18_628)
+                .flat_map(|day| Zenith::iter().map(move |zenith| (day, SunEvent::new(zenith, Event::Sunrise))))
+                .filter_map(|(day, event)| {
+                    let meeus = event_result_unix(day, &pos, event).at()?;
+                    Some((algorithm.event_result_unix(day, &pos, event).at()? - meeus).abs())
+                })
+                .max()
+                .unwrap()
+        };
+        let (plain, refined) = (usno(false), usno(true));
+        assert!(refined * 3 < plain * 2, "{} {}", plain, refined);
+    }
+
+    #[test]
+    fn algorithms_should_reject_the_years_they_dont_support() {
+        use crate::algorithm::Usno;
+        use crate::epoch::days_from_civil;
+
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let day = days_from_civil(2500, 6, 21);
+        let err = Usno::default().try_event_result_unix(day, &pos, SunEvent::SUNRISE).unwrap_err();
+        assert_eq!(err.supported, crate::core::SUPPORTED_YEARS);
+        assert!(Meeus::default().try_event_result_unix(day, &pos, SunEvent::SUNRISE).is_ok());
+        let err = Meeus::default().try_event_result_unix(days_from_civil(3001, 1, 1), &pos, SunEvent::SUNRISE).unwrap_err();
+        assert_eq!(err.year, 3001);
+    }
+
+}
@@ -0,0 +1,175 @@
+//! Publishes the sun events to an MQTT broker, so that Home Assistant
+//! or Node-RED setups can react to them without a bridge service.
+//!
+//! The publisher sends the upcoming firing, retained, to `<topic>/next`
+//! and each firing as it occurs to `<topic>/event`.
+
+use chrono::{ DateTime, SecondsFormat, Utc };
+use rumqttc::{ AsyncClient, ClientError, QoS };
+use serde_json::json;
+use crate::clock::{ Clock, SystemClock };
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+use crate::schedule::Schedule;
+use crate::scheduler::{ Firing, Scheduler };
+
+/// The schema of the published messages.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Payload {
+    /// A JSON object such as `{"event": "sunset", "schedule":
+    /// "sunset-30m", "time": "2024-06-21T19:51:30Z", "timestamp":
+    /// 1718999490, "lat": 51.48, "lng": 0.0}`.
+    Json,
+    /// The time of the firing alone, in RFC 3339, which Home Assistant
+    /// reads as a timestamp sensor.
+    Time,
+    /// The schedule alone, such as "sunset-30m".
+    Schedule
+}
+
+/// Publishes the firings of the schedules registered at a position.
+#[derive(Debug, Clone)]
+pub struct Publisher<C = SystemClock> {
+    topic: String,
+    pos: GlobalPosition,
+    scheduler: Scheduler<C>,
+    payload: Payload,
+    qos: QoS
+}
+
+impl Publisher {
+
+    /// Create a publisher under `topic` at `pos` without any schedules,
+    /// following the system clock, sending [Payload::Json] at most once.
+    pub fn new(topic: &str, pos: GlobalPosition) -> Self {
+        Publisher::with_clock(topic, pos, SystemClock)
+    }
+
+}
+
+impl<C: Clock> Publisher<C> {
+
+    /// Create a publisher under `topic` at `pos` without any schedules,
+    /// following `clock`.
+    pub fn with_clock(topic: &str, pos: GlobalPosition, clock: C) -> Self {
+        Publisher {
+            topic: topic.trim_end_matches('/').to_string(),
+            scheduler: Scheduler::with_clock(pos.clone(), clock),
+            pos,
+            payload: Payload::Json,
+            qos: QoS::AtMostOnce
+        }
+    }
+
+    /// Publish every `event`.
+    pub fn at(self, event: SunEvent) -> Self {
+        Publisher { scheduler: self.scheduler.at(event), ..self }
+    }
+
+    /// Publish every occurrence of `schedule`, such as "sunset-30m".
+    pub fn schedule(self, schedule: Schedule) -> Self {
+        Publisher { scheduler: self.scheduler.schedule(schedule), ..self }
+    }
+
+    /// Publish messages following `payload`.
+    pub fn with_payload(self, payload: Payload) -> Self {
+        Publisher { payload, ..self }
+    }
+
+    /// Publish messages with the quality of service `qos`.
+    pub fn with_qos(self, qos: QoS) -> Self {
+        Publisher { qos, ..self }
+    }
+
+    /// The message published for `firing`.
+    pub fn payload(&self, firing: &Firing) -> String {
+        match self.payload {
+            Payload::Json => json!({
+                "event": firing.schedule.event().to_string(),
+                "schedule": firing.schedule.to_string(),
+                "time": format_time(firing.time),
+                "timestamp": firing.time.timestamp(),
+                "lat": self.pos.lat(),
+                "lng": self.pos.lng()
+            }).to_string(),
+            Payload::Time => format_time(firing.time),
+            Payload::Schedule => firing.schedule.to_string()
+        }
+    }
+
+    /// Publishes the firings through `client` until the schedules no
+    /// longer happen at the position. The event loop of the client must
+    /// be polled meanwhile for the messages to reach the broker.
+    pub async fn run(mut self, client: &AsyncClient) -> Result<(), ClientError> {
+        let next_topic = format!("{}/next", self.topic);
+        let event_topic = format!("{}/event", self.topic);
+        while let Some(upcoming) = self.scheduler.upcoming() {
+            client.publish(next_topic.as_str(), self.qos, true, self.payload(&upcoming)).await?;
+            if let Some(firing) = self.scheduler.next().await {
+                client.publish(event_topic.as_str(), self.qos, false, self.payload(&firing)).await?;
+            }
+        }
+        Ok(())
+    }
+
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::{ Duration, TimeZone };
+    use rumqttc::{ MqttOptions, Request };
+    use serde_json::Value;
+    use crate::scheduler::test::{ greenwich, sunset, TokioClock };
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn payloads_should_follow_the_schema() {
+        let schedule = Schedule::parse("sunset-30m").unwrap();
+        let firing = Firing { schedule, time: Utc.with_ymd_and_hms(2024, 6, 21, 19, 51, 30).unwrap() };
+        let publisher = Publisher::new("home/sun", greenwich()).schedule(schedule);
+        let json: Value = serde_json::from_str(&publisher.payload(&firing)).unwrap();
+        assert_eq!(json["event"], "sunset");
+        assert_eq!(json["schedule"], "sunset-30m");
+        assert_eq!(json["time"], "2024-06-21T19:51:30Z");
+        assert_eq!(json["timestamp"], 1_718_999_490);
+        assert_eq!(json["lat"], 51.4810066);
+        let publisher = publisher.with_payload(Payload::Time);
+        assert_eq!(publisher.payload(&firing), "2024-06-21T19:51:30Z");
+        let publisher = publisher.with_payload(Payload::Schedule);
+        assert_eq!(publisher.payload(&firing), "sunset-30m");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn firings_should_publish_to_the_topics() {
+        let (client, mut eventloop) = AsyncClient::new(MqttOptions::new("circadia", "localhost", 1883), 10);
+        let publisher = Publisher::with_clock("home/sun/", greenwich(), TokioClock::at(start()))
+            .at(SunEvent::SUNSET)
+            .with_payload(Payload::Time)
+            .with_qos(QoS::AtLeastOnce);
+        let publishing = tokio::time::timeout(std::time::Duration::from_secs(12 * 3600), publisher.run(&client));
+        assert!(publishing.await.is_err());
+        eventloop.clean();
+        let published: Vec<_> = eventloop.pending.iter()
+            .map(|request| match request {
+                Request::Publish(publish) => (publish.topic.as_str(), publish.retain, String::from_utf8_lossy(&publish.payload).into_owned()),
+                request => panic!("{:?}", request)
+            })
+            .collect();
+        let tomorrow = sunset() + Duration::days(1);
+        assert_eq!(published.len(), 3);
+        assert_eq!(published[0], ("home/sun/next", true, format_time(sunset())));
+        assert_eq!(published[1], ("home/sun/event", false, format_time(sunset())));
+        assert_eq!(published[2].0, "home/sun/next");
+        assert!(published[2].2.starts_with(&tomorrow.format("%Y-%m-%dT20:").to_string()), "{}", published[2].2);
+    }
+
+}
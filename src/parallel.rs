@@ -0,0 +1,87 @@
+//! Parallel versions of the bulk computations, spreading the days and
+//! the positions over the threads of rayon, for datasets such as the
+//! daylight of every cell of a global grid through a year.
+
+use chrono::Duration;
+use rayon::prelude::*;
+use crate::adapter::chrono::days_of_year;
+use crate::almanac::{ day_row, DayRow };
+use crate::daylight::day_length;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// Like [year_table](crate::almanac::year_table), computing the days in
+/// parallel.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate](chrono::NaiveDate).
+pub fn par_year_table(year: i32, pos: &GlobalPosition, events: &[SunEvent]) -> Vec<DayRow> {
+    days_of_year(year)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|date| day_row(date, pos, events))
+        .collect()
+}
+
+/// Computes the [year_table](crate::almanac::year_table) of every
+/// position in parallel, in the order of `positions`.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate](chrono::NaiveDate).
+pub fn par_year_tables(year: i32, positions: &[GlobalPosition], events: &[SunEvent]) -> Vec<Vec<DayRow>> {
+    let dates: Vec<_> = days_of_year(year).collect();
+    positions.par_iter()
+        .map(|pos| dates.iter().map(|&date| day_row(date, pos, events)).collect())
+        .collect()
+}
+
+/// Computes the [day_length] of every local mean date of `year` at every
+/// position in parallel, one vector of days per position, in the order
+/// of `positions`.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate](chrono::NaiveDate).
+pub fn par_day_lengths(year: i32, positions: &[GlobalPosition]) -> Vec<Vec<Duration>> {
+    let dates: Vec<_> = days_of_year(year).collect();
+    positions.par_iter()
+        .map(|pos| dates.iter().map(|&date| day_length(date, pos)).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::almanac::year_table;
+
+    fn grid() -> Vec<GlobalPosition> {
+        (-8..=8).flat_map(|lat| (-2..=2).map(move |lng| GlobalPosition::at(lat as f64 * 10.0, lng as f64 * 60.0)))
+            .collect()
+    }
+
+    #[test]
+    fn parallel_tables_should_match_the_sequential_ones() {
+        let events = [SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SUNSET];
+        let pos = GlobalPosition::at(69.6492, 18.9553);
+        assert_eq!(par_year_table(2024, &pos, &events), year_table(2024, &pos, &events));
+        let positions = grid();
+        let tables = par_year_tables(2023, &positions, &events);
+        assert_eq!(tables.len(), positions.len());
+        for (table, pos) in tables.iter().zip(&positions).step_by(7) {
+            assert_eq!(*table, year_table(2023, pos, &events));
+        }
+    }
+
+    #[test]
+    fn parallel_day_lengths_should_match_the_sequential_ones() {
+        let positions = grid();
+        let lengths = par_day_lengths(2024, &positions);
+        assert!(lengths.iter().all(|days| days.len() == 366));
+        for (days, pos) in lengths.iter().zip(&positions).step_by(11) {
+            let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+            assert_eq!(days[60], day_length(date, pos));
+        }
+    }
+
+}
@@ -0,0 +1,183 @@
+//! This module provides accelerated-time replay of sun events,
+//! for simulations and games.
+
+use chrono::{ DateTime, Duration, Utc };
+use super::clock::{ Clock, SystemClock };
+use super::event::SunEvent;
+
+/// A clock running `compression` times faster than the wrapped `clock`,
+/// which reads `simulated_start` at the moment it is created.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock<C = SystemClock> {
+    pub(crate) clock: C,
+    real_start: DateTime<Utc>,
+    simulated_start: DateTime<Utc>,
+    compression: f64
+}
+
+impl<C: Clock> SimulatedClock<C> {
+
+    /// Create a simulated clock starting at `simulated_start` that
+    /// advances `compression` simulated seconds per second of `clock`.
+    /// # Panics
+    /// Panics when `compression` is not a positive, finite number.
+    pub fn new(clock: C, simulated_start: DateTime<Utc>, compression: f64) -> Self {
+        assert!(compression.is_finite() && compression > 0.0);
+        SimulatedClock {
+            real_start: clock.now(),
+            clock,
+            simulated_start,
+            compression
+        }
+    }
+
+    /// The time of the wrapped clock at which the simulation
+    /// reaches `simulated`.
+    pub fn real_time_of(&self, simulated: DateTime<Utc>) -> DateTime<Utc> {
+        self.real_start + scale(simulated - self.simulated_start, 1.0 / self.compression)
+    }
+
+    /// The number of simulated seconds per real second.
+    pub fn compression(&self) -> f64 {
+        self.compression
+    }
+
+}
+
+impl<C: Clock> Clock for SimulatedClock<C> {
+    fn now(&self) -> DateTime<Utc> {
+        self.simulated_start + scale(self.clock.now() - self.real_start, self.compression)
+    }
+
+    /// Waits on the wrapped clock until the simulation reaches `time`.
+    fn sleep_until(&self, time: DateTime<Utc>) {
+        self.clock.sleep_until(self.real_time_of(time))
+    }
+}
+
+fn scale(duration: Duration, factor: f64) -> Duration {
+    let nanos = duration.num_nanoseconds().map(|n| n as f64)
+        .unwrap_or_else(|| duration.num_milliseconds() as f64 * 1e6);
+    Duration::nanoseconds((nanos * factor) as i64)
+}
+
+/// An iterator replaying sun events in accelerated time: each event is
+/// yielded once the simulated clock reaches it, waiting in between
+/// through [Clock::sleep_until] of the wrapped clock.
+pub struct Playback<I, C = SystemClock> {
+    pub(crate) events: I,
+    pub(crate) clock: SimulatedClock<C>
+}
+
+impl<I> Playback<I>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)>
+{
+
+    /// Replay `events` from `simulated_start` at `compression` simulated
+    /// seconds per real second, e.g. `86_400.0 / 60.0` to play a day
+    /// per minute.
+    /// # Panics
+    /// Panics when `compression` is not a positive, finite number.
+    pub fn new(events: I, simulated_start: DateTime<Utc>, compression: f64) -> Self {
+        Playback::with_clock(events, SimulatedClock::new(SystemClock, simulated_start, compression))
+    }
+
+}
+
+impl<I, C> Playback<I, C>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)>,
+    C: Clock
+{
+
+    /// Replay `events` against the given simulated clock.
+    pub fn with_clock(events: I, clock: SimulatedClock<C>) -> Self {
+        Playback { events, clock }
+    }
+
+    /// The simulated clock driving the playback.
+    pub fn clock(&self) -> &SimulatedClock<C> {
+        &self.clock
+    }
+
+    /// Replay all events, invoking `callback` as each one occurs.
+    pub fn run<F>(self, mut callback: F)
+    where
+        F: FnMut(SunEvent, DateTime<Utc>)
+    {
+        for (event, time) in self {
+            callback(event, time);
+        }
+    }
+
+}
+
+impl<I, C> Iterator for Playback<I, C>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)>,
+    C: Clock
+{
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (event, time) = self.events.next()?;
+        self.clock.sleep_until(time);
+        Some((event, time))
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::iter::SunEvents;
+    use crate::pos::GlobalPosition;
+    use chrono::TimeZone;
+
+    #[test]
+    fn simulated_clock_should_run_faster() {
+        let real = ManualClock::at(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+        let start = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap();
+        let clock = SimulatedClock::new(&real, start, 1440.0);
+        real.advance(Duration::minutes(1));
+        assert_eq!(clock.now(), start + Duration::days(1));
+        assert_eq!(clock.real_time_of(start + Duration::days(2)), real.now() + Duration::minutes(1));
+    }
+
+    #[test]
+    fn playback_should_yield_events_in_order() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap();
+        let events = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE, SunEvent::SUNSET])
+            .forecast()
+            .take(4);
+        // A simulated week per real millisecond.
+        let playback = Playback::new(events, start, 7.0 * 86_400_000.0);
+        let mut times = vec![];
+        playback.run(|_event, time| times.push(time));
+        assert_eq!(times.len(), 4);
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn playback_should_wait_on_the_wrapped_clock() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let real_start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let real = ManualClock::at(real_start);
+        let start = Utc.with_ymd_and_hms(2019, 6, 21, 0, 0, 0).unwrap();
+        let events = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE, SunEvent::SUNSET])
+            .forecast()
+            .take(4);
+        // A simulated day per real minute.
+        let mut playback = Playback::with_clock(events, SimulatedClock::new(&real, start, 1440.0));
+        while let Some((_event, time)) = playback.next() {
+            assert_eq!(real.now(), playback.clock().real_time_of(time));
+            assert!((playback.clock().now() - time).num_milliseconds().abs() <= 1);
+        }
+        assert!(real.now() > real_start + Duration::minutes(1) && real.now() < real_start + Duration::minutes(3));
+    }
+
+}
@@ -2,12 +2,58 @@
 //! This module provides types for representing
 //! a position on the globe.
 
+#[cfg(feature = "alloc")]
+use alloc::{ format, string::String, vec::Vec };
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+#[cfg(feature = "alloc")]
+use super::error::ParseError;
+use super::error::{ check_coordinates, PositionError };
+use crate::math::Float;
+
+/// The weather at the observer, which bends the light of the sun
+/// near the horizon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Atmosphere {
+    /// The air pressure, in hectopascals.
+    pub pressure_hpa: f64,
+    /// The air temperature, in degrees Celsius.
+    pub temperature_c: f64
+}
+
+impl Atmosphere {
+
+    /// The conditions under which the light of the sun on the horizon
+    /// is bent by the customary 34'.
+    pub const STANDARD: Atmosphere = Atmosphere { pressure_hpa: 1010.0, temperature_c: 10.0 };
+
+    /// How far the atmosphere lifts the sun on the horizon, in degrees,
+    /// scaling the standard 34' with the density of the air
+    /// (Meeus 16.4).
+    pub fn horizon_refraction(&self) -> f64 {
+        34.0 / 60.0 * (self.pressure_hpa / 1010.0) * (283.0 / (273.0 + self.temperature_c))
+    }
+
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere::STANDARD
+    }
+}
+
 /// Represents a position on the earth.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Coordinates", into = "Coordinates"))]
 pub struct GlobalPosition {
     latitude: f64,
     longitude: f64,
-    lng_hour: f64
+    lng_hour: f64,
+    elevation: f64,
+    atmosphere: Atmosphere
 }
 
 impl GlobalPosition {
@@ -18,8 +64,76 @@ impl GlobalPosition {
         GlobalPosition {
             latitude: lat,
             longitude: lng,
-            lng_hour: lng / 15.0
+            lng_hour: lng / 15.0,
+            elevation: 0.0,
+            atmosphere: Atmosphere::STANDARD
+        }
+    }
+
+    /// Places the observer `meters` above the surrounding terrain or
+    /// sea, from where the horizon dips and the sun rises earlier and
+    /// sets later.
+    pub fn with_elevation(self, meters: f64) -> Self {
+        GlobalPosition { elevation: meters, ..self }
+    }
+
+    /// The elevation of the observer above the horizon, in meters.
+    pub fn elevation(&self) -> f64 {
+        self.elevation
+    }
+
+    /// Sets the weather at the observer, which shifts sunrise and sunset
+    /// by changing how much the atmosphere lifts the sun on the horizon.
+    pub fn with_atmosphere(self, atmosphere: Atmosphere) -> Self {
+        GlobalPosition { atmosphere, ..self }
+    }
+
+    /// The weather at the observer, [Atmosphere::STANDARD] unless set.
+    pub fn atmosphere(&self) -> Atmosphere {
+        self.atmosphere
+    }
+
+    /// How far below the horizontal the horizon appears from the
+    /// elevation of the observer, in degrees, including the usual
+    /// atmospheric refraction (1.76' per square root meter).
+    pub fn horizon_dip(&self) -> f64 {
+        1.76 * Float::sqrt(self.elevation.max(0.0)) / 60.0
+    }
+
+    /// Like [GlobalPosition::at], but returns an error when the latitude
+    /// isn't within [-90, 90] or the longitude within [-180, 180],
+    /// including when either is NaN or infinite.
+    pub fn try_at(lat: f64, lng: f64) -> Result<Self, PositionError> {
+        check_coordinates(lat, lng)?;
+        Ok(GlobalPosition::at(lat, lng))
+    }
+
+    /// Wraps the longitude into [-180, 180), as GPS feeds may report
+    /// longitudes such as 370°, which the date adjustments of the
+    /// sunrise/sunset algorithm don't expect.
+    ///
+    /// Returns an error when the latitude isn't within [-90, 90] or
+    /// either coordinate isn't finite.
+    pub fn normalized(&self) -> Result<Self, PositionError> {
+        if !self.longitude.is_finite() {
+            return Err(PositionError::InvalidLongitude(self.longitude));
         }
+        let lng = Float::rem_euclid(self.longitude + 180.0, 360.0) - 180.0;
+        check_coordinates(self.latitude, lng)?;
+        Ok(GlobalPosition { longitude: lng, lng_hour: lng / 15.0, ..self.clone() })
+    }
+
+    /// How many degrees of longitude separate the position from the
+    /// antimeridian, the 180th meridian along which the date changes.
+    pub fn antimeridian_distance(&self) -> f64 {
+        180.0 - (Float::rem_euclid(self.longitude + 180.0, 360.0) - 180.0).abs()
+    }
+
+    /// Whether the position lies within `tolerance` degrees of longitude
+    /// of the antimeridian, where the local date may differ from the
+    /// date on the other side by a day.
+    pub fn is_near_antimeridian(&self, tolerance: f64) -> bool {
+        self.antimeridian_distance() <= tolerance
     }
 
     /// The latitude of the position
@@ -32,6 +146,18 @@ impl GlobalPosition {
         self.longitude
     }
 
+    /// Formats the position in degrees, minutes and seconds, to the
+    /// tenth of a second, e.g. `51°28'51.6"N 0°0'29.4"E`.
+    #[cfg(feature = "alloc")]
+    pub fn to_dms(&self) -> String {
+        let (lat, lng) = (self.latitude, self.longitude);
+        format!(
+            "{}{} {}{}",
+            dms(lat), if lat < 0.0 { 'S' } else { 'N' },
+            dms(lng), if lng < 0.0 { 'W' } else { 'E' }
+        )
+    }
+
     pub(crate) fn lng_hour(&self) -> f64 {
         self.lng_hour
     }
@@ -40,13 +166,235 @@ impl GlobalPosition {
     /// this location's longitude
     ///
     /// [FixedOffset]: chrono::FixedOffset
+    #[cfg(feature = "chrono")]
     pub fn lng_timezone(&self) -> chrono::FixedOffset {
         const SECS_IN_HOUR: f64 = 3600_f64;
-        if self.lng() >= 0_f64 {
-            chrono::FixedOffset::east((self.lng_hour.abs() * SECS_IN_HOUR) as i32)
+        let secs = (self.lng_hour.abs() * SECS_IN_HOUR) as i32;
+        let offset = if self.lng() >= 0_f64 {
+            chrono::FixedOffset::east_opt(secs)
         } else {
-            chrono::FixedOffset::west((self.lng_hour.abs() * SECS_IN_HOUR) as i32)
+            chrono::FixedOffset::west_opt(secs)
+        };
+        offset.expect("longitude offset should be less than a day")
+    }
+
+    /// Returns the name in the IANA database of the timezone in force
+    /// at this location, such as "Europe/Oslo", from boundaries bundled
+    /// with the crate. Locations at sea get the nautical timezone of
+    /// their longitude, such as "Etc/GMT+3".
+    ///
+    /// Unlike [GlobalPosition::lng_timezone], this follows the civil
+    /// time of the location. The boundaries are loaded on first use.
+    #[cfg(feature = "tzf")]
+    pub fn iana_timezone(&self) -> Option<&'static str> {
+        static FINDER: std::sync::OnceLock<tzf_rs::DefaultFinder> = std::sync::OnceLock::new();
+        let name = FINDER.get_or_init(tzf_rs::DefaultFinder::new).get_tz_name(self.lng(), self.lat());
+        Some(name).filter(|name| !name.is_empty())
+    }
+
+}
+
+/// The serialized form of a [GlobalPosition], leaving out the
+/// fields derived from its coordinates.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Coordinates {
+    lat: f64,
+    lng: f64,
+    #[serde(default)]
+    elevation: f64,
+    #[serde(default)]
+    atmosphere: Atmosphere
+}
+
+/// Rejects the coordinates out of range, as [GlobalPosition::try_at].
+#[cfg(feature = "serde")]
+impl ::core::convert::TryFrom<Coordinates> for GlobalPosition {
+    type Error = PositionError;
+
+    fn try_from(coordinates: Coordinates) -> Result<Self, Self::Error> {
+        let pos = GlobalPosition::try_at(coordinates.lat, coordinates.lng)?
+            .with_elevation(coordinates.elevation)
+            .with_atmosphere(coordinates.atmosphere);
+        Ok(pos)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<GlobalPosition> for Coordinates {
+    fn from(pos: GlobalPosition) -> Self {
+        Coordinates { lat: pos.latitude, lng: pos.longitude, elevation: pos.elevation, atmosphere: pos.atmosphere }
+    }
+}
+
+/// Formats the position as signed decimal degrees, latitude first,
+/// e.g. `51.4810066, 0.0081805`.
+impl fmt::Display for GlobalPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}", self.latitude, self.longitude)
+    }
+}
+
+/// Parses a latitude and a longitude, in that order, each written
+/// either as signed decimal degrees or in degrees, minutes and seconds
+/// followed by a hemisphere, e.g. `51.4810066, 0.0081805`,
+/// `51.4810066°N 0.0081805°E` or `51°28'52"N 0°0'29"E`.
+#[cfg(feature = "alloc")]
+impl FromStr for GlobalPosition {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseError::new("a position", s);
+        let text = s.trim();
+        // Split after a comma or the hemisphere of the latitude, or else
+        // between two bare numbers.
+        let (lat, lng) = match text.find([',', 'N', 'S', 'n', 's']) {
+            Some(i) if text[i..].starts_with(',') => (&text[..i], &text[i + 1..]),
+            Some(i) => text.split_at(i + 1),
+            None => match text.split_whitespace().collect::<Vec<_>>()[..] {
+                [lat, lng] => (lat, lng),
+                _ => return Err(err())
+            }
+        };
+        let lat = coordinate(lat, 'N', 'S', 90.0).ok_or_else(err)?;
+        let lng = coordinate(lng, 'E', 'W', 180.0).ok_or_else(err)?;
+        Ok(GlobalPosition::at(lat, lng))
+    }
+}
+
+/// Formats the magnitude of `degrees` as degrees, minutes and seconds.
+#[cfg(feature = "alloc")]
+fn dms(degrees: f64) -> String {
+    let tenths = Float::round(degrees.abs() * 36_000.0) as u64;
+    let (d, m, s) = (tenths / 36_000, tenths / 600 % 60, tenths % 600);
+    format!("{}°{}'{}.{}\"", d, m, s / 10, s % 10)
+}
+
+/// Parses one coordinate, in signed decimal degrees or in degrees,
+/// minutes and seconds, with an optional hemisphere letter.
+#[cfg(feature = "alloc")]
+fn coordinate(text: &str, positive: char, negative: char, limit: f64) -> Option<f64> {
+    let text = text.trim().trim_start_matches(',').trim();
+    let is_hemisphere = |c: char| c.eq_ignore_ascii_case(&positive) || c.eq_ignore_ascii_case(&negative);
+    let hemisphere = text.chars().last().filter(|c| is_hemisphere(*c))
+        .or_else(|| text.chars().next().filter(|c| is_hemisphere(*c)));
+    let text = text.trim_matches(is_hemisphere).trim();
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text))
+    };
+    if sign < 0.0 && hemisphere.is_some() {
+        return None;
+    }
+    let parts: Vec<f64> = text
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if parts.is_empty() || parts.len() > 3 || parts[1..].iter().any(|part| *part >= 60.0) {
+        return None;
+    }
+    let magnitude = parts.iter()
+        .zip(&[1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part / divisor)
+        .sum::<f64>();
+    let sign = match hemisphere {
+        Some(c) if c.eq_ignore_ascii_case(&negative) => -1.0,
+        _ => sign
+    };
+    if magnitude > limit {
+        return None;
+    }
+    Some(sign * magnitude)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_coordinates_should_be_rejected() {
+        assert!(GlobalPosition::try_at(-33.8688, 151.2093).is_ok());
+        assert!(GlobalPosition::try_at(90.0, -180.0).is_ok());
+        assert_eq!(GlobalPosition::try_at(90.5, 0.0).err(), Some(PositionError::InvalidLatitude(90.5)));
+        assert_eq!(GlobalPosition::try_at(0.0, f64::INFINITY).err(), Some(PositionError::InvalidLongitude(f64::INFINITY)));
+        assert!(matches!(GlobalPosition::try_at(f64::NAN, 0.0), Err(PositionError::InvalidLatitude(_))));
+    }
+
+    #[test]
+    fn longitudes_should_wrap_around_the_globe() {
+        let pos = GlobalPosition::at(10.0, 370.0).normalized().unwrap();
+        assert_eq!((pos.lat(), pos.lng()), (10.0, 10.0));
+        assert_eq!(GlobalPosition::at(0.0, 180.0).normalized().unwrap().lng(), -180.0);
+        assert_eq!(GlobalPosition::at(0.0, -540.5).normalized().unwrap().lng(), 179.5);
+        assert!(GlobalPosition::at(100.0, 0.0).normalized().is_err());
+        assert!(GlobalPosition::at(0.0, f64::NAN).normalized().is_err());
+        let fiji = GlobalPosition::at(-17.7134, 178.065);
+        assert!((fiji.antimeridian_distance() - 1.935).abs() < 1e-9);
+        assert!(fiji.is_near_antimeridian(2.0));
+        assert!(!GlobalPosition::at(0.0, 0.0).is_near_antimeridian(2.0));
+        assert!(GlobalPosition::at(0.0, -179.0).is_near_antimeridian(1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decimal_positions_should_round_trip() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let parsed: GlobalPosition = pos.to_string().parse().unwrap();
+        assert_eq!((parsed.lat(), parsed.lng()), (pos.lat(), pos.lng()));
+        let parsed: GlobalPosition = "40.7831 -73.9712".parse().unwrap();
+        assert_eq!((parsed.lat(), parsed.lng()), (40.7831, -73.9712));
+        let parsed: GlobalPosition = "51.4810066°N 0.0081805°E".parse().unwrap();
+        assert_eq!((parsed.lat(), parsed.lng()), (51.4810066, 0.0081805));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dms_positions_should_parse_and_format() {
+        let parsed: GlobalPosition = "51°28'52\"N 0°0'29\"E".parse().unwrap();
+        assert!((parsed.lat() - 51.481111).abs() < 1e-6, "{}", parsed.lat());
+        assert!((parsed.lng() - 0.008056).abs() < 1e-6, "{}", parsed.lng());
+        let parsed: GlobalPosition = "33° 52' 7.7\" S, 151° 12' 33.5\" E".parse().unwrap();
+        assert!((parsed.lat() + 33.8688).abs() < 1e-4 && (parsed.lng() - 151.2093).abs() < 1e-4);
+        let pos = GlobalPosition::at(-33.8688, -151.2093);
+        assert_eq!(pos.to_dms(), "33°52'7.7\"S 151°12'33.5\"W");
+        let parsed: GlobalPosition = pos.to_dms().parse().unwrap();
+        assert!((parsed.lat() - pos.lat()).abs() < 1e-4 && (parsed.lng() - pos.lng()).abs() < 1e-4);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn positions_should_serialize_as_coordinates() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let json = serde_json::to_string(&pos).unwrap();
+        assert!(json.starts_with(r#"{"lat":51.4810066,"lng":0.0081805,"elevation":0.0,"#), "{}", json);
+        let parsed: GlobalPosition = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.lng_hour(), pos.lng_hour());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn positions_out_of_range_should_not_deserialize() {
+        let error = serde_json::from_str::<GlobalPosition>(r#"{"lat":1000.0,"lng":0.0}"#).unwrap_err();
+        assert!(error.to_string().contains("latitude 1000 is not within [-90, 90]"), "{}", error);
+        assert!(serde_json::from_str::<GlobalPosition>(r#"{"lat":0.0,"lng":-180.5}"#).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn invalid_positions_should_not_parse() {
+        for text in &["", "51.5", "91 0", "0 181", "51°75'N 0°E", "-51°N 0°E", "north 0", "1 2 3"] {
+            assert!(text.parse::<GlobalPosition>().is_err(), "{}", text);
         }
     }
 
+    #[test]
+    #[cfg(feature = "tzf")]
+    fn positions_should_find_their_civil_timezone() {
+        assert_eq!(GlobalPosition::at(59.9139, 10.7522).iana_timezone(), Some("Europe/Oslo"));
+        // Kashgar keeps the time of Beijing, far to the east.
+        assert_eq!(GlobalPosition::at(39.4704, 75.9897).iana_timezone(), Some("Asia/Shanghai"));
+    }
+
 }
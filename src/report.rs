@@ -0,0 +1,184 @@
+//! This module gathers everything known about a day at a position into
+//! one report, which serializes to a stable format for HTTP APIs.
+
+use std::ops::Range;
+use chrono::{ DateTime, NaiveDate, Utc };
+use super::daylight::day_length;
+use super::event::SunEvent;
+use super::pos::GlobalPosition;
+use super::summary::DaySummary;
+use super::twilight::{ twilight_periods, Twilight };
+
+/// The events, the length of the day and the twilights of a day at a
+/// position.
+///
+/// With the `serde` feature it serializes as:
+///
+/// ```json
+/// {
+///   "date": "2024-06-21",
+///   "lat": 51.4810066,
+///   "lng": 0.0081805,
+///   "elevation": 0.0,
+///   "events": [{ "event": "solar midnight", "time": "2024-06-21T00:01:45Z" }, ...],
+///   "day_length_secs": 59891,
+///   "twilights": {
+///     "civil": { "morning": { "start": "...", "end": "..." }, "evening": null },
+///     "nautical": ...,
+///     "astronomical": ...
+///   }
+/// }
+/// ```
+///
+/// and the `schemars` feature describes it with a JSON Schema, through
+/// `schemars::schema_for!(DayReport)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DayReport {
+    /// The date, in the local mean time of the position.
+    pub date: NaiveDate,
+    /// The latitude, in degrees north.
+    pub lat: f64,
+    /// The longitude, in degrees east.
+    pub lng: f64,
+    /// The elevation above sea level, in meters.
+    pub elevation: f64,
+    /// Every sun event, in order of occurrence within the day.
+    pub events: Vec<ReportedEvent>,
+    /// The time from sunrise to sunset, in seconds.
+    pub day_length_secs: i64,
+    /// The spans of civil, nautical and astronomical twilight.
+    pub twilights: ReportedTwilights
+}
+
+/// A sun event of a [DayReport].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ReportedEvent {
+    /// The name of the event, such as "civil sunrise".
+    pub event: String,
+    /// The time of the event, or None if it doesn't happen that day.
+    pub time: Option<DateTime<Utc>>
+}
+
+/// The twilights of a [DayReport].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ReportedTwilights {
+    /// The sun between the horizon and 6° below it.
+    pub civil: ReportedTwilight,
+    /// The sun between 6° and 12° below the horizon.
+    pub nautical: ReportedTwilight,
+    /// The sun between 12° and 18° below the horizon.
+    pub astronomical: ReportedTwilight
+}
+
+/// The spans of one twilight in a [DayReport], as in [Twilight].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ReportedTwilight {
+    /// The span before noon, None if the sun doesn't enter the band.
+    pub morning: Option<Span>,
+    /// The span after noon, None if the sun doesn't enter the band.
+    pub evening: Option<Span>
+}
+
+/// A span of time, from `start` until before `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Span {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>
+}
+
+impl DayReport {
+
+    /// Reports on the local mean `date` at `pos`.
+    pub fn compute(date: NaiveDate, pos: &GlobalPosition) -> Self {
+        let all = SunEvent::all();
+        let summary = DaySummary::compute(date, pos, &all);
+        let events = all.into_iter()
+            .map(|event| ReportedEvent { event: event.to_string(), time: summary.time_of(event) })
+            .collect();
+        let twilights = twilight_periods(date, pos);
+        DayReport {
+            date,
+            lat: pos.lat(),
+            lng: pos.lng(),
+            elevation: pos.elevation(),
+            events,
+            day_length_secs: day_length(date, pos).num_seconds(),
+            twilights: ReportedTwilights {
+                civil: twilights.civil.into(),
+                nautical: twilights.nautical.into(),
+                astronomical: twilights.astronomical.into()
+            }
+        }
+    }
+
+}
+
+impl From<Twilight> for ReportedTwilight {
+    fn from(twilight: Twilight) -> Self {
+        ReportedTwilight {
+            morning: twilight.morning.map(Span::from),
+            evening: twilight.evening.map(Span::from)
+        }
+    }
+}
+
+impl From<Range<DateTime<Utc>>> for Span {
+    fn from(range: Range<DateTime<Utc>>) -> Self {
+        Span { start: range.start, end: range.end }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn reports_should_gather_the_day() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let report = DayReport::compute(date, &pos);
+        assert_eq!(report.events.len(), SunEvent::all().len());
+        let sunrise = report.events.iter().find(|event| event.event == "sunrise").unwrap();
+        let sunset = report.events.iter().find(|event| event.event == "sunset").unwrap();
+        assert_eq!(report.day_length_secs, (sunset.time.unwrap() - sunrise.time.unwrap()).num_seconds());
+        let civil = report.twilights.civil.morning.unwrap();
+        assert_eq!(civil.end, sunrise.time.unwrap());
+        // No astronomical darkness in London around the solstice.
+        let astronomical = report.events.iter().find(|event| event.event == "astronomical sunrise").unwrap();
+        assert_eq!(astronomical.time, None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn reports_should_serialize_to_the_documented_format() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let report = DayReport::compute(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), &pos);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["date"], "2024-12-21");
+        assert_eq!(json["day_length_secs"], 0);
+        assert_eq!(json["events"][0]["event"], "solar midnight");
+        assert!(json["twilights"]["civil"]["morning"].is_null());
+        assert_eq!(serde_json::from_value::<DayReport>(json).unwrap(), report);
+    }
+
+    #[test]
+    #[cfg(feature = "schemars")]
+    fn reports_should_describe_their_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(DayReport)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert!(properties.contains_key("events") && properties.contains_key("twilights"));
+        assert_eq!(schema["definitions"]["Span"]["properties"]["start"]["format"], "date-time");
+    }
+
+}
@@ -0,0 +1,180 @@
+//! This module relates the nominal crossing times of sun-synchronous
+//! orbits to the local solar time at the ground positions they overfly,
+//! to predict the illumination of satellite acquisitions.
+//!
+//! The orbital plane of a sun-synchronous satellite keeps a fixed angle
+//! to the mean sun, so it crosses the equator at the same local mean
+//! solar time on every pass: the local time of the ascending node
+//! (LTAN), and twelve hours later the descending node (LTDN). Away from
+//! the equator the pass happens earlier or later depending on the
+//! latitude, and the true (apparent) solar time further differs from
+//! the mean one by the equation of time.
+
+use chrono::{ DateTime, Duration, NaiveDate, Utc };
+use crate::adapter::chrono::julian_day;
+use crate::pos::GlobalPosition;
+use crate::solar::equation_of_time;
+
+/// The direction of a satellite pass.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Pass {
+    /// Moving north, over the ascending node.
+    Ascending,
+    /// Moving south, over the descending node.
+    Descending
+}
+
+/// A sun-synchronous orbit, described by its nominal node crossing time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunSynchronousOrbit {
+    ltan: f64,
+    inclination: f64
+}
+
+impl SunSynchronousOrbit {
+
+    /// An orbit with the local time of the ascending node `ltan`,
+    /// in hours, and the given `inclination` in degrees
+    /// (around 98 for sun-synchronous orbits).
+    pub fn with_ltan(ltan: f64, inclination: f64) -> Self {
+        SunSynchronousOrbit { ltan: wrap_hours(ltan), inclination }
+    }
+
+    /// An orbit with the local time of the descending node `ltdn`,
+    /// in hours, and the given `inclination` in degrees.
+    pub fn with_ltdn(ltdn: f64, inclination: f64) -> Self {
+        Self::with_ltan(ltdn + 12.0, inclination)
+    }
+
+    /// The local mean solar time of the ascending node, in hours.
+    pub fn ltan(&self) -> f64 {
+        self.ltan
+    }
+
+    /// The local mean solar time of the descending node, in hours.
+    pub fn ltdn(&self) -> f64 {
+        wrap_hours(self.ltan + 12.0)
+    }
+
+    /// The inclination of the orbit, in degrees.
+    pub fn inclination(&self) -> f64 {
+        self.inclination
+    }
+
+    /// The local mean solar time, in hours, at which the satellite
+    /// overflies latitude `lat` on a `pass`.
+    ///
+    /// Returns None if the ground track never reaches `lat`.
+    pub fn mean_solar_time_at(&self, lat: f64, pass: Pass) -> Option<f64> {
+        self.node_offset(lat, pass).map(|offset| wrap_hours(self.ltan + offset))
+    }
+
+    /// The local apparent solar time, in hours, at which the satellite
+    /// overflies `pos` on a `pass` on `date`, accounting for the
+    /// equation of time.
+    ///
+    /// Returns None if the ground track never reaches the latitude of `pos`.
+    pub fn apparent_solar_time_at(&self, date: NaiveDate, pos: &GlobalPosition, pass: Pass) -> Option<f64> {
+        let mean = self.mean_solar_time_at(pos.lat(), pass)?;
+        let time = mean_time_to_utc(date, pos, mean);
+        Some(wrap_hours(mean + equation_of_time(julian_day(time)) / 60.0))
+    }
+
+    /// The instant on `date` at which the local mean solar time of `pos`
+    /// matches the time of a `pass` over its latitude, that is when the
+    /// satellite would overfly `pos` if its ground track crossed it.
+    ///
+    /// Returns None if the ground track never reaches the latitude of `pos`.
+    pub fn pass_time(&self, date: NaiveDate, pos: &GlobalPosition, pass: Pass) -> Option<DateTime<Utc>> {
+        self.mean_solar_time_at(pos.lat(), pass)
+            .map(|mean| mean_time_to_utc(date, pos, mean))
+    }
+
+    /// The orbit of the given `inclination` whose `pass` over `pos` on
+    /// `date` happens at the local apparent solar time `apparent`, in
+    /// hours. This recovers the nominal LTAN from an acquisition.
+    ///
+    /// Returns None if such an orbit never reaches the latitude of `pos`.
+    pub fn from_apparent_solar_time(apparent: f64, date: NaiveDate, pos: &GlobalPosition, pass: Pass, inclination: f64) -> Option<Self> {
+        let orbit = SunSynchronousOrbit::with_ltan(0.0, inclination);
+        let offset = orbit.node_offset(pos.lat(), pass)?;
+        // The equation of time barely changes within a day, so evaluate
+        // it at the approximate time of the pass.
+        let approximate = mean_time_to_utc(date, pos, apparent);
+        let mean = apparent - equation_of_time(julian_day(approximate)) / 60.0;
+        Some(SunSynchronousOrbit::with_ltan(mean - offset, inclination))
+    }
+
+    /// The hours between the ascending node and the pass over `lat`.
+    fn node_offset(&self, lat: f64, pass: Pass) -> Option<f64> {
+        let i = self.inclination.to_radians();
+        let sin_u = lat.to_radians().sin() / i.sin();
+        if sin_u.abs() > 1.0 {
+            return None;
+        }
+        // The argument of latitude, measured from the ascending node.
+        let u = match pass {
+            Pass::Ascending => sin_u.asin(),
+            Pass::Descending => std::f64::consts::PI - sin_u.asin()
+        };
+        // The angle between the node and the satellite along the equator.
+        let right_ascension = (i.cos() * u.sin()).atan2(u.cos()).to_degrees();
+        Some(right_ascension / 15.0)
+    }
+
+}
+
+/// The instant on `date` at which the local mean solar time
+/// of `pos` is `hours`.
+fn mean_time_to_utc(date: NaiveDate, pos: &GlobalPosition, hours: f64) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0)
+        .expect("midnight should be a valid time")
+        .and_utc();
+    let utc_hours = wrap_hours(hours - pos.lng() / 15.0);
+    midnight + Duration::milliseconds((utc_hours * 3_600_000.0).round() as i64)
+}
+
+fn wrap_hours(hours: f64) -> f64 {
+    hours.rem_euclid(24.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nodes_should_be_twelve_hours_apart() {
+        let orbit = SunSynchronousOrbit::with_ltdn(10.5, 98.2);
+        assert!((orbit.ltan() - 22.5).abs() < 1e-9);
+        let ascending = orbit.mean_solar_time_at(0.0, Pass::Ascending).unwrap();
+        let descending = orbit.mean_solar_time_at(0.0, Pass::Descending).unwrap();
+        assert!((ascending - 22.5).abs() < 1e-9, "{}", ascending);
+        assert!((descending - 10.5).abs() < 1e-9, "{}", descending);
+    }
+
+    #[test]
+    fn pass_time_should_depend_on_latitude() {
+        let orbit = SunSynchronousOrbit::with_ltdn(10.5, 98.2);
+        // Descending passes run south-westward to the node, so they
+        // reach northern latitudes later in the local day.
+        let north = orbit.mean_solar_time_at(60.0, Pass::Descending).unwrap();
+        let south = orbit.mean_solar_time_at(-60.0, Pass::Descending).unwrap();
+        assert!(north > 10.5 && north < 11.5, "{}", north);
+        assert!(south < 10.5 && south > 9.5, "{}", south);
+        assert!(orbit.mean_solar_time_at(85.0, Pass::Descending).is_none());
+    }
+
+    #[test]
+    fn apparent_time_should_round_trip() {
+        let orbit = SunSynchronousOrbit::with_ltan(13.5, 98.7);
+        let pos = GlobalPosition::at(45.0, 7.0);
+        let date = NaiveDate::from_ymd_opt(2021, 11, 3).unwrap();
+        let mean = orbit.mean_solar_time_at(45.0, Pass::Ascending).unwrap();
+        let apparent = orbit.apparent_solar_time_at(date, &pos, Pass::Ascending).unwrap();
+        // The sun runs about 16 minutes ahead of the mean sun in November.
+        assert!((apparent - mean - 16.4 / 60.0).abs() < 0.01, "{}", apparent - mean);
+        let recovered = SunSynchronousOrbit::from_apparent_solar_time(apparent, date, &pos, Pass::Ascending, 98.7).unwrap();
+        assert!((recovered.ltan() - 13.5).abs() < 1e-4, "{}", recovered.ltan());
+    }
+
+}
@@ -0,0 +1,197 @@
+//! Times relative to the sun events, written as in configuration
+//! files, such as "sunset-30m" or "civil_dawn+1h".
+
+use chrono::{ DateTime, Duration, Utc };
+use std::fmt;
+use std::str::FromStr;
+use super::error::ParseError;
+use super::event::SunEvent;
+use super::iter::SunEvents;
+use super::pos::GlobalPosition;
+
+/// A sun event shifted by an offset, recurring every day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    event: SunEvent,
+    offset: Duration
+}
+
+impl Schedule {
+
+    /// The time `offset` after `event`, or before it when `offset` is
+    /// negative.
+    pub fn new(event: SunEvent, offset: Duration) -> Self {
+        Schedule { event: SunEvent::new(event.zenith, event.event), offset }
+    }
+
+    /// Parses the name of an event, with underscores or spaces between
+    /// its words, followed by an optional offset made of hours, minutes
+    /// and seconds, as in "sunset-30m", "civil_dawn+1h" or
+    /// "solar noon+1h30m". "dawn" and "dusk" stand for the sunrise and
+    /// sunset at any zenith, as in "nautical_dusk".
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let err = || ParseError::new("a schedule such as \"sunset-30m\"", text);
+        let trimmed = text.trim();
+        let (name, offset) = match trimmed.find(['+', '-']) {
+            Some(sign) => trimmed.split_at(sign),
+            None => (trimmed, "")
+        };
+        let name = name.replace('_', " ").to_ascii_lowercase();
+        let name = match name.rsplit_once(' ') {
+            Some((zenith, "dawn")) => format!("{} sunrise", zenith),
+            Some((zenith, "dusk")) => format!("{} sunset", zenith),
+            _ => name
+        };
+        let event = name.parse().map_err(|_| err())?;
+        let offset = if offset.is_empty() {
+            Duration::zero()
+        } else {
+            parse_offset(offset).ok_or_else(err)?
+        };
+        Ok(Schedule { event, offset })
+    }
+
+    /// The event the schedule is relative to.
+    pub fn event(&self) -> SunEvent {
+        self.event
+    }
+
+    /// The offset from the event.
+    pub fn offset(&self) -> Duration {
+        self.offset
+    }
+
+    /// The first time of the schedule after `start` at `pos`, or None if
+    /// the event no longer happens there.
+    pub fn next_after(&self, start: DateTime<Utc>, pos: &GlobalPosition) -> Option<DateTime<Utc>> {
+        self.occurrences_after(start, pos).next()
+    }
+
+    /// The times of the schedule after `start` at `pos`, earliest first.
+    pub fn occurrences_after(&self, start: DateTime<Utc>, pos: &GlobalPosition) -> impl Iterator<Item = DateTime<Utc>> {
+        let offset = self.offset;
+        SunEvents::starting_from(start - offset, pos.clone(), &[self.event])
+            .forecast()
+            .map(move |(_, time)| time + offset)
+    }
+
+    /// The times of the schedule before `start` at `pos`, latest first.
+    pub fn occurrences_before(&self, start: DateTime<Utc>, pos: &GlobalPosition) -> impl Iterator<Item = DateTime<Utc>> {
+        let offset = self.offset;
+        SunEvents::starting_from(start - offset, pos.clone(), &[self.event])
+            .history()
+            .map(move |(_, time)| time + offset)
+    }
+
+}
+
+/// Formats the schedule as parsed by [Schedule::parse], such as
+/// "civil_sunrise+1h30m".
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.event.to_string().replace(' ', "_"))?;
+        if self.offset.is_zero() {
+            return Ok(());
+        }
+        let sign = if self.offset < Duration::zero() { '-' } else { '+' };
+        let secs = self.offset.num_seconds().abs();
+        write!(f, "{}", sign)?;
+        for (amount, unit) in [(secs / 3600, 'h'), (secs / 60 % 60, 'm'), (secs % 60, 's')] {
+            if amount > 0 {
+                write!(f, "{}{}", amount, unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Schedule::parse(s)
+    }
+}
+
+/// Parses a signed offset made of hours, minutes and seconds, such as
+/// "+1h30m" or "-45s".
+fn parse_offset(text: &str) -> Option<Duration> {
+    let (negative, mut rest) = match text.split_at(1) {
+        ("+", rest) => (false, rest),
+        ("-", rest) => (true, rest),
+        _ => return None
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    let mut offset = Duration::zero();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let part = match unit {
+            'h' => Duration::try_hours(amount),
+            'm' => Duration::try_minutes(amount),
+            's' => Duration::try_seconds(amount),
+            _ => return None
+        };
+        offset = offset.checked_add(&part?)?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    Some(if negative { -offset } else { offset })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::{ NaiveDate, TimeZone };
+    use crate::adapter::chrono::time_of_event_on;
+    use crate::event::{ Event, Zenith };
+
+    #[test]
+    fn schedules_should_parse() {
+        assert_eq!(Schedule::parse("sunset-30m"), Ok(Schedule::new(SunEvent::SUNSET, Duration::minutes(-30))));
+        assert_eq!(Schedule::parse("civil_dawn+1h"), Ok(Schedule::new(SunEvent::DAWN, Duration::hours(1))));
+        assert_eq!(Schedule::parse(" Solar Noon+1h30m15s "), Ok(Schedule::new(SunEvent::SOLAR_NOON, Duration::seconds(5415))));
+        let nautical_dusk = SunEvent::new(Zenith::Nautical, Event::Sunset);
+        assert_eq!("nautical_dusk".parse(), Ok(Schedule::new(nautical_dusk, Duration::zero())));
+        for text in &["", "sunset-", "sunset+15", "sunset-15x", "sunset~15m", "moonrise+1h", "sunset+1h-1m", "+1h", "sunset+1é", "sunset+99999999999999h"] {
+            assert!(Schedule::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn schedules_should_give_transits_the_official_zenith() {
+        let noon = SunEvent { zenith: Zenith::Civil, event: Event::SolarNoon };
+        let schedule = Schedule::new(noon, Duration::zero());
+        assert_eq!(schedule.event().zenith, Zenith::Official);
+        assert_eq!(schedule.to_string(), "solar_noon");
+    }
+
+    #[test]
+    fn schedules_should_format_as_parsed() {
+        for text in &["sunset-30m", "dawn+1h", "nautical_sunset", "solar_noon+1h30m15s", "golden_sunrise-45s"] {
+            assert_eq!(Schedule::parse(text).unwrap().to_string(), *text);
+        }
+    }
+
+    #[test]
+    fn occurrences_should_follow_the_start() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let schedule = Schedule::parse("sunset-15m").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let sunset = time_of_event_on(date, &Utc, &pos, SunEvent::SUNSET).unwrap();
+        // Ten minutes before sunset, the schedule has passed for the day.
+        let next = schedule.next_after(sunset - Duration::minutes(10), &pos).unwrap();
+        assert!(next > sunset + Duration::hours(23));
+        assert_eq!(schedule.next_after(sunset - Duration::minutes(20), &pos), Some(sunset - Duration::minutes(15)));
+        let start = Utc.with_ymd_and_hms(2024, 6, 21, 0, 0, 0).unwrap();
+        let after: Vec<_> = schedule.occurrences_after(start, &pos).take(3).collect();
+        assert_eq!(after[0], sunset - Duration::minutes(15));
+        assert!(after.windows(2).all(|pair| pair[1] - pair[0] > Duration::hours(23)));
+        let before: Vec<_> = schedule.occurrences_before(start, &pos).take(2).collect();
+        assert!(before[0] < start && before[1] < before[0]);
+    }
+
+}
@@ -0,0 +1,194 @@
+//! Waits for sun events in async code, with tokio, as daemons that
+//! switch lights or blinds at dusk need.
+
+use chrono::{ DateTime, Duration, Utc };
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use crate::clock::{ nap_until, Clock, SystemClock };
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+use crate::schedule::Schedule;
+
+/// A schedule falling due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Firing {
+    /// The schedule that fell due.
+    pub schedule: Schedule,
+    /// The time the schedule fell due at, which can be a while before
+    /// the firing is received when the machine was suspended.
+    pub time: DateTime<Utc>
+}
+
+/// Waits for the schedules registered at a position, one firing at a
+/// time in chronological order.
+///
+/// Every occurrence after the creation of the scheduler fires once, even
+/// when the machine was suspended past it, in which case it fires late.
+#[derive(Debug, Clone)]
+pub struct Scheduler<C = SystemClock> {
+    pos: GlobalPosition,
+    schedules: Vec<Schedule>,
+    clock: C,
+    /// The time of the last firing, or the creation of the scheduler.
+    last: DateTime<Utc>,
+    /// The index of the schedule that fired last.
+    last_index: Option<usize>
+}
+
+impl Scheduler {
+
+    /// Create a scheduler at `pos` without any schedules, following the
+    /// system clock.
+    pub fn new(pos: GlobalPosition) -> Self {
+        Scheduler::with_clock(pos, SystemClock)
+    }
+
+}
+
+impl<C: Clock> Scheduler<C> {
+
+    /// Create a scheduler at `pos` without any schedules, following
+    /// `clock`.
+    pub fn with_clock(pos: GlobalPosition, clock: C) -> Self {
+        Scheduler {
+            last: clock.now(),
+            last_index: None,
+            pos,
+            schedules: Vec::new(),
+            clock
+        }
+    }
+
+    /// Fire at every `event`.
+    pub fn at(self, event: SunEvent) -> Self {
+        self.schedule(Schedule::new(event, Duration::zero()))
+    }
+
+    /// Fire at every occurrence of `schedule`, such as "sunset-30m".
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedules.push(schedule);
+        self
+    }
+
+    /// The next firing, without waiting for it, or None when none of the
+    /// schedules happen anymore at the position.
+    pub fn upcoming(&self) -> Option<Firing> {
+        self.upcoming_index().map(|(index, time)| Firing { schedule: self.schedules[index], time })
+    }
+
+    /// Waits until the next firing and returns it, or returns None when
+    /// none of the schedules happen anymore at the position.
+    pub async fn next(&mut self) -> Option<Firing> {
+        let (index, time) = self.upcoming_index()?;
+        while let Some(nap) = nap_until(&self.clock, time) {
+            tokio::time::sleep(nap).await;
+        }
+        self.last = time;
+        self.last_index = Some(index);
+        Some(Firing { schedule: self.schedules[index], time })
+    }
+
+    /// Sends every firing to the returned channel from a tokio task,
+    /// which ends when the channel is dropped or when the schedules no
+    /// longer happen.
+    /// # Panics
+    /// Panics when called outside of a tokio runtime.
+    pub fn spawn(mut self) -> (mpsc::Receiver<Firing>, JoinHandle<()>)
+    where
+        C: Send + 'static
+    {
+        let (sender, receiver) = mpsc::channel(16);
+        let task = tokio::spawn(async move {
+            while let Some(firing) = self.next().await {
+                if sender.send(firing).await.is_err() {
+                    break;
+                }
+            }
+        });
+        (receiver, task)
+    }
+
+    /// The index of the schedule firing next and its time. Schedules
+    /// falling due together fire in the order they were registered.
+    fn upcoming_index(&self) -> Option<(usize, DateTime<Utc>)> {
+        self.schedules.iter()
+            .enumerate()
+            .filter_map(|(index, schedule)| {
+                // The schedules registered after the last one to fire may
+                // still fire at the same time.
+                let start = match self.last_index {
+                    Some(last_index) if index > last_index => self.last - Duration::nanoseconds(1),
+                    _ => self.last
+                };
+                schedule.next_after(start, &self.pos).map(|time| (index, time))
+            })
+            .min_by_key(|&(index, time)| (time, index))
+    }
+
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+
+    use super::*;
+    use chrono::{ NaiveDate, TimeZone };
+    use tokio::time::Instant;
+    use crate::adapter::chrono::time_of_event_on;
+    use crate::clock::MAX_NAP;
+    pub(crate) use crate::clock::test::TokioClock;
+
+    pub(crate) fn greenwich() -> GlobalPosition {
+        GlobalPosition::at(51.4810066, 0.0081805)
+    }
+
+    pub(crate) fn sunset() -> DateTime<Utc> {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        time_of_event_on(date, &Utc, &greenwich(), SunEvent::SUNSET).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn firings_should_wait_for_each_schedule() {
+        let clock = TokioClock::at(Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap());
+        let mut scheduler = Scheduler::with_clock(greenwich(), clock.clone())
+            .at(SunEvent::SUNSET)
+            .schedule(Schedule::parse("sunset-30m").unwrap());
+        assert_eq!(scheduler.upcoming().unwrap().time, sunset() - Duration::minutes(30));
+        for expected in [sunset() - Duration::minutes(30), sunset()] {
+            let firing = scheduler.next().await.unwrap();
+            assert_eq!(firing.time, expected);
+            assert!(clock.now() >= expected && clock.now() - expected < Duration::seconds(1));
+        }
+        let firing = scheduler.next().await.unwrap();
+        assert_eq!(firing.schedule.offset(), Duration::minutes(-30));
+        assert!(firing.time > sunset() + Duration::hours(23));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn schedules_due_together_should_all_fire() {
+        let clock = TokioClock::at(Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap());
+        let (mut firings, _) = Scheduler::with_clock(greenwich(), clock)
+            .at(SunEvent::SUNSET)
+            .schedule(Schedule::parse("sunset+0m").unwrap())
+            .spawn();
+        assert_eq!(firings.recv().await.unwrap().time, sunset());
+        assert_eq!(firings.recv().await.unwrap().time, sunset());
+        assert!(firings.recv().await.unwrap().time > sunset() + Duration::hours(23));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn firings_should_follow_clock_changes() {
+        let clock = TokioClock::at(Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap());
+        let (mut firings, _) = Scheduler::with_clock(greenwich(), clock.clone())
+            .at(SunEvent::SUNSET)
+            .spawn();
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        // The system clock is set forward past the sunset, as after a
+        // suspend, so the firing is late but not as late as a single
+        // long sleep would make it.
+        let before = Instant::now();
+        clock.jump(Duration::hours(10));
+        assert_eq!(firings.recv().await.unwrap().time, sunset());
+        assert!(before.elapsed() <= MAX_NAP.to_std().unwrap());
+    }
+
+}
@@ -0,0 +1,267 @@
+//! The solstices and equinoxes, from Jean Meeus, "Astronomical
+//! Algorithms", chapter 27, accurate to about a minute over the
+//! years 1000 to 3000, and the cross-quarter days between them.
+
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, Utc };
+use std::fmt;
+use crate::adapter::chrono::from_julian_day;
+use crate::astro::ephemeris_to_universal;
+
+/// The solstices and equinoxes, in the order they occur in a year.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum SeasonMarker {
+    MarchEquinox,
+    JuneSolstice,
+    SeptemberEquinox,
+    DecemberSolstice
+}
+
+impl SeasonMarker {
+
+    /// Every season marker, in the order they occur in a year.
+    pub const ALL: [SeasonMarker; 4] = [
+        SeasonMarker::MarchEquinox,
+        SeasonMarker::JuneSolstice,
+        SeasonMarker::SeptemberEquinox,
+        SeasonMarker::DecemberSolstice
+    ];
+
+    /// The coefficients of the mean instant, in JDE, as a polynomial
+    /// of the millennia since 2000 (Meeus table 27.B).
+    fn mean_coefficients(self) -> [f64; 5] {
+        match self {
+            SeasonMarker::MarchEquinox => [2451623.80984, 365242.37404, 0.05169, -0.00411, -0.00057],
+            SeasonMarker::JuneSolstice => [2451716.56767, 365241.62603, 0.00325, 0.00888, -0.00030],
+            SeasonMarker::SeptemberEquinox => [2451810.21715, 365242.01767, -0.11575, 0.00337, 0.00078],
+            SeasonMarker::DecemberSolstice => [2451900.05952, 365242.74049, -0.06223, -0.00823, 0.00032]
+        }
+    }
+
+}
+
+impl fmt::Display for SeasonMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SeasonMarker::MarchEquinox => write!(f, "March equinox"),
+            SeasonMarker::JuneSolstice => write!(f, "June solstice"),
+            SeasonMarker::SeptemberEquinox => write!(f, "September equinox"),
+            SeasonMarker::DecemberSolstice => write!(f, "December solstice"),
+        }
+    }
+}
+
+/// The instants of the solstices and equinoxes of a year.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct SeasonMarkers {
+    pub march_equinox: DateTime<Utc>,
+    pub june_solstice: DateTime<Utc>,
+    pub september_equinox: DateTime<Utc>,
+    pub december_solstice: DateTime<Utc>
+}
+
+impl SeasonMarkers {
+
+    /// The instant of `marker`.
+    pub fn get(&self, marker: SeasonMarker) -> DateTime<Utc> {
+        match marker {
+            SeasonMarker::MarchEquinox => self.march_equinox,
+            SeasonMarker::JuneSolstice => self.june_solstice,
+            SeasonMarker::SeptemberEquinox => self.september_equinox,
+            SeasonMarker::DecemberSolstice => self.december_solstice
+        }
+    }
+
+    /// The markers and their instants, in chronological order.
+    pub fn iter(&self) -> impl Iterator<Item = (SeasonMarker, DateTime<Utc>)> + '_ {
+        SeasonMarker::ALL.iter().map(move |marker| (*marker, self.get(*marker)))
+    }
+
+}
+
+/// Computes the instants of the solstices and equinoxes of `year`.
+///
+/// # Panics
+/// Panics when `year` is too far from the present for the instants
+/// to be represented.
+pub fn solstices_and_equinoxes(year: i32) -> SeasonMarkers {
+    let instant = |marker: SeasonMarker| {
+        from_julian_day(ephemeris_to_universal(season_marker_jde(year, marker)))
+            .expect("the season markers of the year should be representable")
+    };
+    SeasonMarkers {
+        march_equinox: instant(SeasonMarker::MarchEquinox),
+        june_solstice: instant(SeasonMarker::JuneSolstice),
+        september_equinox: instant(SeasonMarker::SeptemberEquinox),
+        december_solstice: instant(SeasonMarker::DecemberSolstice)
+    }
+}
+
+/// The cross-quarter days, midway between a solstice and an equinox,
+/// in the order they occur in a year.
+///
+/// In the northern hemisphere they match the Gaelic festivals of
+/// Imbolc, Beltane, Lughnasadh and Samhain.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum CrossQuarter {
+    /// Between the December solstice and the March equinox.
+    February,
+    /// Between the March equinox and the June solstice.
+    May,
+    /// Between the June solstice and the September equinox.
+    August,
+    /// Between the September equinox and the December solstice.
+    November
+}
+
+impl CrossQuarter {
+
+    /// Every cross-quarter day, in the order they occur in a year.
+    pub const ALL: [CrossQuarter; 4] = [
+        CrossQuarter::February,
+        CrossQuarter::May,
+        CrossQuarter::August,
+        CrossQuarter::November
+    ];
+
+}
+
+impl fmt::Display for CrossQuarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CrossQuarter::February => write!(f, "February cross-quarter day"),
+            CrossQuarter::May => write!(f, "May cross-quarter day"),
+            CrossQuarter::August => write!(f, "August cross-quarter day"),
+            CrossQuarter::November => write!(f, "November cross-quarter day"),
+        }
+    }
+}
+
+/// The instants of the cross-quarter days of a year.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct CrossQuarterDays {
+    pub february: DateTime<Utc>,
+    pub may: DateTime<Utc>,
+    pub august: DateTime<Utc>,
+    pub november: DateTime<Utc>
+}
+
+impl CrossQuarterDays {
+
+    /// The instant of `cross_quarter`.
+    pub fn get(&self, cross_quarter: CrossQuarter) -> DateTime<Utc> {
+        match cross_quarter {
+            CrossQuarter::February => self.february,
+            CrossQuarter::May => self.may,
+            CrossQuarter::August => self.august,
+            CrossQuarter::November => self.november
+        }
+    }
+
+    /// The cross-quarter days and their instants, in chronological order.
+    pub fn iter(&self) -> impl Iterator<Item = (CrossQuarter, DateTime<Utc>)> + '_ {
+        CrossQuarter::ALL.iter().map(move |cross_quarter| (*cross_quarter, self.get(*cross_quarter)))
+    }
+
+}
+
+/// Computes the instants of the cross-quarter days of `year`, each
+/// halfway in time between the solstice and the equinox around it.
+///
+/// # Panics
+/// Panics when `year` is too far from the present for the instants
+/// to be represented.
+pub fn cross_quarter_days(year: i32) -> CrossQuarterDays {
+    let previous = solstices_and_equinoxes(year - 1);
+    let markers = solstices_and_equinoxes(year);
+    let midway = |start: DateTime<Utc>, end: DateTime<Utc>| start + (end - start) / 2;
+    CrossQuarterDays {
+        february: midway(previous.december_solstice, markers.march_equinox),
+        may: midway(markers.march_equinox, markers.june_solstice),
+        august: midway(markers.june_solstice, markers.september_equinox),
+        november: midway(markers.september_equinox, markers.december_solstice)
+    }
+}
+
+// The amplitude (1e-5 days), phase and rate (degrees) of the periodic
+// terms correcting the mean instants (Meeus table 27.C).
+const PERIODIC_TERMS: [(f64, f64, f64); 24] = [
+    (485.0, 324.96, 1934.136),
+    (203.0, 337.23, 32964.467),
+    (199.0, 342.08, 20.186),
+    (182.0, 27.85, 445267.112),
+    (156.0, 73.14, 45036.886),
+    (136.0, 171.52, 22518.443),
+    (77.0, 222.54, 65928.934),
+    (74.0, 296.72, 3034.906),
+    (70.0, 243.58, 9037.513),
+    (58.0, 119.81, 33718.147),
+    (52.0, 297.17, 150.678),
+    (50.0, 21.02, 2281.226),
+    (45.0, 247.54, 29929.562),
+    (44.0, 325.15, 31555.956),
+    (29.0, 60.93, 4443.417),
+    (18.0, 155.12, 67555.328),
+    (17.0, 288.79, 4562.452),
+    (16.0, 198.04, 62894.029),
+    (14.0, 199.76, 31436.921),
+    (12.0, 95.39, 14577.848),
+    (12.0, 287.11, 31931.756),
+    (12.0, 320.81, 34777.259),
+    (9.0, 227.73, 1222.114),
+    (8.0, 15.45, 16859.074)
+];
+
+/// The Julian ephemeris day of `marker` in `year`.
+fn season_marker_jde(year: i32, marker: SeasonMarker) -> f64 {
+    let Y = (year as f64 - 2000.0) / 1000.0;
+    let JDE0 = marker.mean_coefficients()
+        .iter()
+        .rev()
+        .fold(0.0, |acc, c| acc * Y + c);
+    let T = (JDE0 - 2451545.0) / 36525.0;
+    let W = (35999.373 * T - 2.47).to_radians();
+    let dlambda = 1.0 + 0.0334 * W.cos() + 0.0007 * (2.0 * W).cos();
+    let S: f64 = PERIODIC_TERMS.iter()
+        .map(|(A, B, C)| A * (B + C * T).to_radians().cos())
+        .sum();
+    JDE0 + 0.00001 * S / dlambda
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{ Datelike, TimeZone };
+
+    #[test]
+    fn june_solstice_should_match_meeus() {
+        // Meeus example 27.a: 1962 June 21, 21h25m08s TD.
+        let jde = season_marker_jde(1962, SeasonMarker::JuneSolstice);
+        assert!((jde - 2437837.39245).abs() < 0.00001, "{}", jde);
+    }
+
+    #[test]
+    fn cross_quarter_days_should_fall_early_in_their_months() {
+        let days = cross_quarter_days(2021);
+        let expected = [(2, 3), (5, 5), (8, 6), (11, 6)];
+        for ((_, instant), (month, day)) in days.iter().zip(&expected) {
+            assert_eq!((instant.month(), instant.day()), (*month, *day), "{}", instant);
+        }
+    }
+
+    #[test]
+    fn markers_should_match_published_instants() {
+        let markers = solstices_and_equinoxes(2021);
+        let expected = [
+            Utc.with_ymd_and_hms(2021, 3, 20, 9, 37, 0).unwrap(),
+            Utc.with_ymd_and_hms(2021, 6, 21, 3, 32, 0).unwrap(),
+            Utc.with_ymd_and_hms(2021, 9, 22, 19, 21, 0).unwrap(),
+            Utc.with_ymd_and_hms(2021, 12, 21, 15, 59, 0).unwrap()
+        ];
+        for ((_, instant), expected) in markers.iter().zip(&expected) {
+            assert!((instant - *expected).num_seconds().abs() < 90, "{}", instant);
+        }
+    }
+
+}
@@ -0,0 +1,146 @@
+//! This module computes the shadows cast by the sun: the direction
+//! they point to, which makes for a solar compass, and the days on
+//! which the sun passes overhead, leaving upright objects without a
+//! shadow at noon.
+
+use chrono::{ DateTime, Duration, NaiveDate, Utc };
+use crate::adapter::chrono::{ days_of_year, from_julian_day, julian_day };
+use crate::pos::GlobalPosition;
+use crate::solar;
+
+/// Above this altitude in degrees shadows are too short to point
+/// in a usable direction.
+const MAX_COMPASS_ALTITUDE: f64 = 89.0;
+
+/// Calculates the bearing of the shadow of an upright object at `pos`
+/// at `time`, in degrees clockwise from true north.
+///
+/// Returns None when the sun is below the horizon or nearly overhead.
+pub fn shadow_bearing(time: DateTime<Utc>, pos: &GlobalPosition) -> Option<f64> {
+    sun_bearing(time, pos).map(|azimuth| (azimuth + 180.0) % 360.0)
+}
+
+/// Calculates the bearing of the sun at `pos` at `time`, in degrees
+/// clockwise from true north.
+///
+/// Returns None when the sun is below the horizon or nearly overhead.
+pub fn sun_bearing(time: DateTime<Utc>, pos: &GlobalPosition) -> Option<f64> {
+    let (altitude, azimuth) = solar::horizontal(julian_day(time), pos);
+    if altitude <= 0.0 || altitude >= MAX_COMPASS_ALTITUDE {
+        return None;
+    }
+    Some(azimuth)
+}
+
+/// Finds true north from the bearing of a shadow, `observed` on any
+/// scale of degrees increasing clockwise (a magnetic compass, the
+/// heading of a device or a protractor laid on the ground), at `pos`
+/// at `time`. Returns the bearing of true north on that same scale.
+///
+/// Returns None when the sun is below the horizon or nearly overhead.
+pub fn true_north_from_shadow(time: DateTime<Utc>, pos: &GlobalPosition, observed: f64) -> Option<f64> {
+    shadow_bearing(time, pos).map(|bearing| (observed - bearing).rem_euclid(360.0))
+}
+
+/// Like [true_north_from_shadow], given the `observed` bearing
+/// of the sun itself.
+pub fn true_north_from_sun(time: DateTime<Utc>, pos: &GlobalPosition, observed: f64) -> Option<f64> {
+    sun_bearing(time, pos).map(|bearing| (observed - bearing).rem_euclid(360.0))
+}
+
+/// A day on which the sun culminates at the zenith of a position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZeroShadowDay {
+    /// The date, in the local mean time of the position.
+    pub date: NaiveDate,
+    /// The instant of the sun's upper transit.
+    pub transit: DateTime<Utc>,
+    /// The angle between the sun and the zenith at transit, in degrees.
+    pub zenith_angle: f64
+}
+
+/// Finds the zero shadow days of `year` at `pos`, the days on which the
+/// sun at noon comes closest to the zenith as its declination crosses
+/// the latitude.
+///
+/// There are two such days a year between the tropics, except on the
+/// tropics themselves, and none beyond them.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn zero_shadow_days(year: i32, pos: &GlobalPosition) -> Vec<ZeroShadowDay> {
+    let days = days_of_year(year)
+        .map(|date| (date, transit_on(date, pos)));
+    let offsets: Vec<_> = days
+        .map(|(date, jd)| (date, jd, solar::Geocentric::at(jd).dec - pos.lat()))
+        .collect();
+    offsets.windows(2)
+        .filter(|pair| pair[0].2.signum() != pair[1].2.signum())
+        .filter_map(|pair| {
+            let &(date, jd, offset) = if pair[0].2.abs() <= pair[1].2.abs() { &pair[0] } else { &pair[1] };
+            Some(ZeroShadowDay { date, transit: from_julian_day(jd)?, zenith_angle: offset.abs() })
+        })
+        .collect()
+}
+
+/// The Julian day of the sun's upper transit on the local mean `date`.
+fn transit_on(date: NaiveDate, pos: &GlobalPosition) -> f64 {
+    let noon = date.and_hms_opt(12, 0, 0)
+        .expect("noon should be a valid time")
+        .and_utc();
+    let mean_noon = julian_day(noon - Duration::seconds((pos.lng() * 240.0) as i64));
+    solar::transit(mean_noon)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn honolulu_should_have_two_zero_shadow_days() {
+        // The "Lahaina noons" of Honolulu, at 12:28 HST on May 26
+        // and 12:37 HST on July 15.
+        let pos = GlobalPosition::at(21.3069, -157.8583);
+        let days = zero_shadow_days(2021, &pos);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2021, 5, 26).unwrap());
+        assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2021, 7, 15).unwrap());
+        assert!(days.iter().all(|day| day.zenith_angle < 0.3));
+        let may = Utc.with_ymd_and_hms(2021, 5, 26, 22, 28, 0).unwrap();
+        let july = Utc.with_ymd_and_hms(2021, 7, 15, 22, 37, 0).unwrap();
+        assert!((days[0].transit - may).num_minutes().abs() <= 1, "{:?}", days[0]);
+        assert!((days[1].transit - july).num_minutes().abs() <= 1, "{:?}", days[1]);
+    }
+
+    #[test]
+    fn shadows_should_point_north_at_noon_in_the_north() {
+        let pos = GlobalPosition::at(48.8566, 2.3522);
+        let transit = from_julian_day(transit_on(NaiveDate::from_ymd_opt(2021, 10, 1).unwrap(), &pos)).unwrap();
+        let bearing = shadow_bearing(transit, &pos).unwrap();
+        assert!(((bearing + 180.0) % 360.0 - 180.0).abs() < 0.05, "{}", bearing);
+        let night = Utc.with_ymd_and_hms(2021, 10, 1, 23, 0, 0).unwrap();
+        assert!(shadow_bearing(night, &pos).is_none());
+    }
+
+    #[test]
+    fn true_north_should_offset_the_observed_bearing() {
+        let pos = GlobalPosition::at(48.8566, 2.3522);
+        let time = Utc.with_ymd_and_hms(2021, 10, 1, 15, 0, 0).unwrap();
+        let shadow = shadow_bearing(time, &pos).unwrap();
+        // A compass rotated 10 degrees clockwise reads every bearing
+        // 10 degrees short, and north at 350.
+        let north = true_north_from_shadow(time, &pos, shadow - 10.0).unwrap();
+        assert!((north - 350.0).abs() < 1e-9, "{}", north);
+        let sun = sun_bearing(time, &pos).unwrap();
+        let north = true_north_from_sun(time, &pos, sun + 5.0).unwrap();
+        assert!((north - 5.0).abs() < 1e-9, "{}", north);
+    }
+
+    #[test]
+    fn no_zero_shadow_days_beyond_the_tropics() {
+        let pos = GlobalPosition::at(40.0, -105.0);
+        assert!(zero_shadow_days(2021, &pos).is_empty());
+    }
+
+}
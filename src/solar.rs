@@ -0,0 +1,214 @@
+//! The apparent position of the sun, to about 0.01 degree, from
+//! Jean Meeus, "Astronomical Algorithms", chapter 25.
+
+#![allow(non_snake_case)]
+
+use chrono::{ DateTime, NaiveDate, NaiveTime, TimeZone, Utc };
+use crate::adapter::chrono::{ days_of_year, julian_day };
+use crate::astro::*;
+use crate::event::Zenith;
+use crate::pos::GlobalPosition;
+
+/// The position of the sun in the sky of an observer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    /// Degrees clockwise from true north.
+    pub azimuth: f64,
+    /// Degrees of the center of the sun above the horizon,
+    /// not corrected for atmospheric refraction.
+    pub altitude: f64
+}
+
+/// Calculates the position of the sun at `time` seen from `pos`,
+/// to about 0.01 degree.
+pub fn sun_position<Tz: TimeZone>(pos: &GlobalPosition, time: DateTime<Tz>) -> SolarPosition {
+    let (altitude, azimuth) = horizontal(julian_day(time.with_timezone(&Utc)), pos);
+    SolarPosition { azimuth, altitude }
+}
+
+/// Samples the position of the sun at the clock `time` of the timezone
+/// `tz` on every day of `year` at `pos`, tracing its analemma.
+///
+/// Days on which `time` doesn't exist, such as when clocks skip
+/// forward, are left out.
+///
+/// # Panics
+/// Panics when `year` is outside the range of [NaiveDate].
+pub fn analemma<Tz: TimeZone>(year: i32, time: NaiveTime, tz: &Tz, pos: &GlobalPosition) -> Vec<(NaiveDate, SolarPosition)> {
+    days_of_year(year)
+        .filter_map(|date| {
+            let instant = tz.from_local_datetime(&date.and_time(time)).earliest()?;
+            Some((date, sun_position(pos, instant)))
+        })
+        .collect()
+}
+
+/// The point of the globe where the sun stands directly overhead at `time`.
+pub fn subsolar_point<Tz: TimeZone>(time: DateTime<Tz>) -> GlobalPosition {
+    let jd = julian_day(time.with_timezone(&Utc));
+    let sun = Geocentric::at(jd);
+    let lng = normalize_degrees(sun.ra - greenwich_sidereal_time(jd) + 180.0) - 180.0;
+    GlobalPosition::at(sun.dec, lng)
+}
+
+/// Traces the line of the globe where the sun stands at `zenith` at
+/// `time`, such as the day/night terminator for [Zenith::Official],
+/// as a closed ring of `points` positions around the subsolar point.
+pub fn terminator<Tz: TimeZone>(time: DateTime<Tz>, zenith: Zenith, points: usize) -> Vec<GlobalPosition> {
+    let center = subsolar_point(time);
+    let (lat, lng) = (center.lat().to_radians(), center.lng());
+    let distance = zenith.angle().to_radians();
+    (0..points)
+        .map(|i| {
+            let bearing = (i as f64 * 360.0 / points as f64).to_radians();
+            let sin_lat = lat.sin() * distance.cos() + lat.cos() * distance.sin() * bearing.cos();
+            let delta = (bearing.sin() * distance.sin() * lat.cos())
+                .atan2(distance.cos() - lat.sin() * sin_lat);
+            let point_lng = normalize_degrees(lng + delta.to_degrees() + 180.0) - 180.0;
+            GlobalPosition::at(sin_lat.asin().to_degrees(), point_lng)
+        })
+        .collect()
+}
+
+/// The declination of the sun at noon UTC on `date`, in degrees north
+/// of the celestial equator, to about 0.01 degree.
+pub fn solar_declination(date: NaiveDate) -> f64 {
+    let noon = date.and_hms_opt(12, 0, 0)
+        .expect("noon should be a valid time")
+        .and_utc();
+    Geocentric::at(julian_day(noon)).dec
+}
+
+/// The apparent geocentric coordinates of the sun. Angles in degrees.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Geocentric {
+    pub(crate) ra: f64,
+    pub(crate) dec: f64
+}
+
+impl Geocentric {
+
+    /// The coordinates of the sun at the Julian day `jd`.
+    pub(crate) fn at(jd: f64) -> Self {
+        let T = julian_centuries(jd);
+        let L0 = 280.46646 + 36000.76983 * T + 0.0003032 * T * T;
+        let M = (357.52911 + 35999.05029 * T - 0.0001537 * T * T).to_radians();
+        let C = (1.914602 - 0.004817 * T - 0.000014 * T * T) * M.sin()
+            + (0.019993 - 0.000101 * T) * (2.0 * M).sin()
+            + 0.000289 * (3.0 * M).sin();
+        let omega = (125.04 - 1934.136 * T).to_radians();
+        let longitude = normalize_degrees(L0 + C - 0.00569 - 0.00478 * omega.sin());
+        let eps = mean_obliquity(T) + 0.00256 * omega.cos();
+        let (ra, dec) = ecliptic_to_equatorial(longitude, 0.0, eps);
+        Geocentric { ra, dec }
+    }
+
+}
+
+/// The altitude and azimuth (clockwise from north) of the center of
+/// the sun at the Julian day `jd` seen from `pos`, in degrees, without
+/// atmospheric refraction.
+pub(crate) fn horizontal(jd: f64, pos: &GlobalPosition) -> (f64, f64) {
+    let sun = Geocentric::at(jd);
+    let H = greenwich_sidereal_time(jd) + pos.lng() - sun.ra;
+    equatorial_to_horizontal(H, sun.dec, pos.lat())
+}
+
+/// The equation of time at the Julian day `jd`, that is apparent minus
+/// mean solar time, in minutes (Meeus 28.3).
+pub(crate) fn equation_of_time(jd: f64) -> f64 {
+    let T = julian_centuries(jd);
+    let L0 = (280.46646 + 36000.76983 * T + 0.0003032 * T * T).to_radians();
+    let M = (357.52911 + 35999.05029 * T - 0.0001537 * T * T).to_radians();
+    let e = 0.016708634 - 0.000042037 * T - 0.0000001267 * T * T;
+    let y = (mean_obliquity(T).to_radians() / 2.0).tan().powi(2);
+    let E = y * (2.0 * L0).sin() - 2.0 * e * M.sin() + 4.0 * e * y * M.sin() * (2.0 * L0).cos()
+        - 0.5 * y * y * (4.0 * L0).sin() - 1.25 * e * e * (2.0 * M).sin();
+    4.0 * E.to_degrees()
+}
+
+/// The Julian day of the upper transit of the sun nearest to the
+/// Julian day `mean_noon` of local mean noon.
+pub(crate) fn transit(mean_noon: f64) -> f64 {
+    let mut jd = mean_noon;
+    for _ in 0..2 {
+        jd = mean_noon - equation_of_time(jd) / 1440.0;
+    }
+    jd
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn geocentric_position_should_match_meeus() {
+        // Meeus example 25.a: 1992 October 13, 0h TD.
+        let sun = Geocentric::at(2448908.5);
+        assert!((sun.ra - 198.38083).abs() < 0.0005, "{}", sun.ra);
+        assert!((sun.dec + 7.78507).abs() < 0.0005, "{}", sun.dec);
+    }
+
+    #[test]
+    fn declination_should_peak_at_the_solstices() {
+        let june = solar_declination(NaiveDate::from_ymd_opt(2021, 6, 21).unwrap());
+        assert!((june - 23.44).abs() < 0.01, "{}", june);
+        let december = solar_declination(NaiveDate::from_ymd_opt(2021, 12, 21).unwrap());
+        assert!((december + 23.44).abs() < 0.01, "{}", december);
+        let equinox = solar_declination(NaiveDate::from_ymd_opt(2021, 3, 20).unwrap());
+        assert!(equinox.abs() < 0.2, "{}", equinox);
+    }
+
+    #[test]
+    fn analemma_should_span_the_tropics() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let points = analemma(2021, noon, &Utc, &pos);
+        assert_eq!(points.len(), 365);
+        let altitudes = points.iter().map(|(_, position)| position.altitude);
+        let highest = altitudes.clone().fold(f64::MIN, f64::max);
+        let lowest = altitudes.fold(f64::MAX, f64::min);
+        assert!((highest - lowest - 46.9).abs() < 0.1, "{} {}", highest, lowest);
+        // The equation of time sways the sun by a few degrees east and west.
+        assert!(points.iter().all(|(_, position)| (position.azimuth - 180.0).abs() < 6.0));
+    }
+
+    #[test]
+    fn sun_should_stand_overhead_at_the_subsolar_point() {
+        let time = Utc.with_ymd_and_hms(2021, 6, 21, 18, 0, 0).unwrap();
+        let point = subsolar_point(time);
+        assert!((point.lat() - 23.44).abs() < 0.01, "{}", point.lat());
+        assert!((point.lng() + 89.6).abs() < 0.5, "{}", point.lng());
+        assert!((sun_position(&point, time).altitude - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn sun_should_sit_at_the_zenith_along_the_terminator() {
+        let time = Utc.with_ymd_and_hms(2021, 6, 21, 18, 0, 0).unwrap();
+        for &zenith in &[Zenith::Official, Zenith::Civil] {
+            let line = terminator(time, zenith, 72);
+            assert_eq!(line.len(), 72);
+            for point in &line {
+                let altitude = sun_position(point, time).altitude;
+                assert!((altitude - (90.0 - zenith.angle())).abs() < 0.01, "{:?} {}", point, altitude);
+            }
+        }
+    }
+
+    #[test]
+    fn sun_should_culminate_in_the_south_at_the_solstice() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let noon = Utc.with_ymd_and_hms(2020, 6, 21, 12, 2, 0).unwrap();
+        let position = sun_position(&pos, noon);
+        assert!((position.altitude - 61.96).abs() < 0.05, "{}", position.altitude);
+        assert!((position.azimuth - 180.0).abs() < 1.0, "{}", position.azimuth);
+    }
+
+    #[test]
+    fn equation_of_time_should_match_meeus() {
+        // Meeus example 28.b: 13m42.7s on 1992 October 13, 0h TD.
+        let minutes = equation_of_time(2448908.5);
+        assert!((minutes - 13.712).abs() < 0.05, "{}", minutes);
+    }
+
+}
@@ -0,0 +1,153 @@
+//! Solunar tables, which predict the periods of the day when fish and
+//! game are most active from the motion of the moon.
+//!
+//! Major periods span two hours centered on the moon's transit of the
+//! local meridian and on its passage underfoot; minor periods span one
+//! hour centered on moonrise and moonset.
+
+use chrono::{ DateTime, Duration, NaiveDate, TimeZone, Utc };
+use std::fmt;
+use crate::lunar::{ moon_events_between, MoonEvent };
+use crate::pos::GlobalPosition;
+
+/// The positions of the moon around which solunar periods are centered.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum SolunarEvent {
+    /// The moon crosses the meridian above the horizon.
+    Transit,
+    /// The moon crosses the meridian below the horizon.
+    Underfoot,
+    Moonrise,
+    Moonset
+}
+
+impl SolunarEvent {
+
+    /// The kind of period centered on this event.
+    pub fn period_kind(self) -> PeriodKind {
+        match self {
+            SolunarEvent::Transit | SolunarEvent::Underfoot => PeriodKind::Major,
+            SolunarEvent::Moonrise | SolunarEvent::Moonset => PeriodKind::Minor
+        }
+    }
+
+}
+
+impl fmt::Display for SolunarEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SolunarEvent::Transit => write!(f, "moon transit"),
+            SolunarEvent::Underfoot => write!(f, "moon underfoot"),
+            SolunarEvent::Moonrise => write!(f, "moonrise"),
+            SolunarEvent::Moonset => write!(f, "moonset"),
+        }
+    }
+}
+
+/// Solunar periods come in two lengths.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum PeriodKind {
+    Major,
+    Minor
+}
+
+impl PeriodKind {
+
+    /// How long a period of this kind lasts.
+    pub fn duration(self) -> Duration {
+        match self {
+            PeriodKind::Major => Duration::hours(2),
+            PeriodKind::Minor => Duration::hours(1)
+        }
+    }
+
+}
+
+/// A period of increased activity, centered on `peak`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolunarPeriod<Tz: TimeZone> {
+    pub kind: PeriodKind,
+    pub event: SolunarEvent,
+    pub peak: DateTime<Tz>,
+    pub start: DateTime<Tz>,
+    pub end: DateTime<Tz>
+}
+
+/// Computes the solunar periods peaking on the calendar `date` of the
+/// timezone `tz` at `pos`, in chronological order.
+///
+/// Some days lack a transit, an underfoot passage, a moonrise or a
+/// moonset since the moon comes back to the meridian every 24h50m
+/// on average, and near the poles it may not rise or set for days.
+pub fn solunar_periods<Tz: TimeZone>(date: NaiveDate, tz: &Tz, pos: &GlobalPosition) -> Vec<SolunarPeriod<Tz>> {
+    let midnight = date.and_hms_opt(0, 0, 0)
+        .expect("midnight should be a valid time")
+        .and_utc();
+    // Any timezone offset is less than a day.
+    let events = solunar_events_between(midnight - Duration::days(1), midnight + Duration::days(2), pos);
+    events.into_iter()
+        .map(|(event, time)| (event, time.with_timezone(tz)))
+        .filter(|(_, time)| time.date_naive() == date)
+        .map(|(event, peak)| {
+            let kind = event.period_kind();
+            let half = kind.duration() / 2;
+            SolunarPeriod { kind, event, start: peak.clone() - half, end: peak.clone() + half, peak }
+        })
+        .collect()
+}
+
+/// Lists the solunar events of the moon between `start` and `end`
+/// at `pos`, in chronological order.
+fn solunar_events_between(start: DateTime<Utc>, end: DateTime<Utc>, pos: &GlobalPosition) -> Vec<(SolunarEvent, DateTime<Utc>)> {
+    moon_events_between(start, end, pos)
+        .into_iter()
+        .map(|(event, time)| {
+            let event = match event {
+                MoonEvent::Moonrise => SolunarEvent::Moonrise,
+                MoonEvent::Transit => SolunarEvent::Transit,
+                MoonEvent::Moonset => SolunarEvent::Moonset,
+                MoonEvent::LowerTransit => SolunarEvent::Underfoot
+            };
+            (event, time)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::FixedOffset;
+    use crate::lunar::moon_position;
+
+    #[test]
+    fn transit_should_be_the_highest_point_of_the_moon() {
+        let pos = GlobalPosition::at(44.9778, -93.2650);
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2021, 6, 15).unwrap();
+        let periods = solunar_periods(date, &tz, &pos);
+        let transit = periods.iter().find(|period| period.event == SolunarEvent::Transit).unwrap();
+        assert_eq!(transit.kind, PeriodKind::Major);
+        assert_eq!(transit.end - transit.start, Duration::hours(2));
+        let peak = transit.peak.with_timezone(&Utc);
+        let altitude = moon_position(peak, &pos).altitude;
+        for minutes in &[-20, 20] {
+            assert!(moon_position(peak + Duration::minutes(*minutes), &pos).altitude < altitude);
+        }
+    }
+
+    #[test]
+    fn periods_should_fall_on_the_local_date_in_order() {
+        let pos = GlobalPosition::at(-33.8688, 151.2093);
+        let tz = FixedOffset::east_opt(10 * 3600).unwrap();
+        for day in 1..=30 {
+            let date = NaiveDate::from_ymd_opt(2022, 9, day).unwrap();
+            let periods = solunar_periods(date, &tz, &pos);
+            assert!(periods.len() >= 3 && periods.len() <= 4, "{:?}", periods);
+            assert!(periods.iter().all(|period| period.peak.date_naive() == date));
+            for pair in periods.windows(2) {
+                assert!(pair[0].peak < pair[1].peak);
+            }
+        }
+    }
+
+}
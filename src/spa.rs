@@ -0,0 +1,588 @@
+//! The Solar Position Algorithm of the National Renewable Energy
+//! Laboratory, from Reda and Andreas, "Solar Position Algorithm for
+//! Solar Radiation Applications" (NREL/TP-560-34302, 2008).
+//!
+//! It places the sun to within 0.0003 degree from the full VSOP87
+//! theory of the earth, for solar-energy work that has to cite
+//! SPA-grade accuracy. The events it finds agree with the Meeus
+//! backend to a second or so, for a few times its computation.
+
+#![allow(non_snake_case)]
+// The periodic terms are printed to the precision of the paper, some
+// of them close to π.
+#![allow(clippy::approx_constant)]
+// `chrono::Date` is deprecated upstream but remains the date type
+// of this crate's public API.
+#![allow(deprecated)]
+
+use chrono::{ Date, DateTime, TimeZone, Utc };
+use std::ops::RangeInclusive;
+use crate::adapter::chrono::{ julian_day, time_of_event_with };
+use crate::algorithm::SolarAlgorithm;
+use crate::astro::{ normalize_degrees, J2000 };
+use crate::core::EventResult;
+use crate::epoch::{ julian_day_from_unix, unix_from_julian_day, SECS_IN_DAY };
+use crate::event::SunEvent;
+use crate::meeus::{ solve, year_of, DeltaT };
+use crate::pos::GlobalPosition;
+
+/// The [SolarAlgorithm] of this module.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spa {
+    /// How to tell the terrestrial time of the motion of the sun from
+    /// the universal time of the rotation of the earth.
+    pub delta_t: DeltaT
+}
+
+impl Spa {
+
+    /// Calculates the position of the sun at `time` seen from `pos`,
+    /// to about 0.0003 degree.
+    pub fn sun_position<Tz: TimeZone>(&self, pos: &GlobalPosition, time: DateTime<Tz>) -> TopocentricPosition {
+        let jd = julian_day(time.with_timezone(&Utc));
+        topocentric(jd, self.delta_t.seconds(year_of(jd)), pos)
+    }
+
+}
+
+impl SolarAlgorithm for Spa {
+
+    fn event_result_unix(&self, day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+        let noon = julian_day_from_unix((day * SECS_IN_DAY) as f64) + 0.5;
+        let delta_t = self.delta_t.seconds(year_of(noon));
+        solve(day, pos, event, |jd| {
+            let sun = Geocentric::at(jd, delta_t);
+            (sun.nu - sun.alpha, sun.delta)
+        })
+        .map(|jd| unix_from_julian_day(jd).round() as i64)
+    }
+
+    fn supported_years(&self) -> RangeInclusive<i32> {
+        SUPPORTED_YEARS
+    }
+
+}
+
+/// The years over which the algorithm is stated to keep its
+/// accuracy of 0.0003 degree.
+pub const SUPPORTED_YEARS: RangeInclusive<i32> = -2000..=6000;
+
+/// The position of the sun in the sky of an observer, as seen from
+/// its elevation and through its atmosphere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopocentricPosition {
+    /// Degrees clockwise from true north.
+    pub azimuth: f64,
+    /// Degrees of the center of the sun from the vertical,
+    /// corrected for atmospheric refraction.
+    pub zenith: f64
+}
+
+/// Calculates the position of the sun at `time` seen from `pos`,
+/// to about 0.0003 degree.
+pub fn sun_position<Tz: TimeZone>(pos: &GlobalPosition, time: DateTime<Tz>) -> TopocentricPosition {
+    Spa::default().sun_position(pos, time)
+}
+
+/// Like [time_of_event](crate::time_of_event), from the SPA position
+/// of the sun.
+pub fn time_of_event(
+    date: Date<Utc>,
+    pos: &GlobalPosition,
+    event: SunEvent,
+) -> Option<DateTime<Utc>> {
+    time_of_event_with(&Spa::default(), date, pos, event)
+}
+
+/// Like [event_result_unix](crate::epoch::event_result_unix), from the
+/// SPA position of the sun.
+pub fn event_result_unix(day: i64, pos: &GlobalPosition, event: SunEvent) -> EventResult<i64> {
+    Spa::default().event_result_unix(day, pos, event)
+}
+
+/// The apparent geocentric position of the sun.
+struct Geocentric {
+    /// The apparent sidereal time at Greenwich, in degrees.
+    nu: f64,
+    /// The right ascension, in degrees.
+    alpha: f64,
+    /// The declination, in degrees.
+    delta: f64,
+    /// The distance to the earth, in astronomical units.
+    R: f64
+}
+
+impl Geocentric {
+
+    /// Computes the position of the sun at the Julian day `jd`,
+    /// `delta_t` seconds behind terrestrial time.
+    fn at(jd: f64, delta_t: f64) -> Self {
+        let JC = (jd - J2000) / 36525.0;
+        let JDE = jd + delta_t / 86400.0;
+        let JCE = (JDE - J2000) / 36525.0;
+        let JME = JCE / 10.0;
+
+        let L = normalize_degrees(earth_series(&L_TERMS, JME).to_degrees());
+        let B = earth_series(&B_TERMS, JME).to_degrees();
+        let R = earth_series(&R_TERMS, JME);
+        let theta = normalize_degrees(L + 180.0);
+        let beta = -B;
+
+        let (delta_psi, delta_epsilon) = nutation(JCE);
+        let U = JME / 10.0;
+        let epsilon0 = EPSILON_TERMS.iter().rev().fold(0.0, |acc, c| acc * U + c);
+        let epsilon = (epsilon0 / 3600.0 + delta_epsilon).to_radians();
+
+        let aberration = -20.4898 / (3600.0 * R);
+        let lambda = (theta + delta_psi + aberration).to_radians();
+
+        let nu0 = normalize_degrees(280.46061837 + 360.98564736629 * (jd - J2000)
+            + 0.000387933 * JC * JC - JC * JC * JC / 38710000.0);
+        let nu = nu0 + delta_psi * epsilon.cos();
+
+        let beta = beta.to_radians();
+        let alpha = normalize_degrees(
+            (lambda.sin() * epsilon.cos() - beta.tan() * epsilon.sin())
+                .atan2(lambda.cos())
+                .to_degrees(),
+        );
+        let delta = (beta.sin() * epsilon.cos() + beta.cos() * epsilon.sin() * lambda.sin())
+            .asin()
+            .to_degrees();
+        Geocentric { nu, alpha, delta, R }
+    }
+
+}
+
+/// Computes the position of the sun at the Julian day `jd` seen from
+/// `pos`, `delta_t` seconds behind terrestrial time.
+fn topocentric(jd: f64, delta_t: f64, pos: &GlobalPosition) -> TopocentricPosition {
+    let sun = Geocentric::at(jd, delta_t);
+    let phi = pos.lat().to_radians();
+    let H = normalize_degrees(sun.nu + pos.lng() - sun.alpha).to_radians();
+    let delta = sun.delta.to_radians();
+
+    // The parallax of the sun from the elevation of the observer on
+    // the flattened earth.
+    let xi = (8.794 / (3600.0 * sun.R)).to_radians();
+    let u = (0.99664719 * phi.tan()).atan();
+    let x = u.cos() + pos.elevation() / 6378140.0 * phi.cos();
+    let y = 0.99664719 * u.sin() + pos.elevation() / 6378140.0 * phi.sin();
+    let delta_alpha = (-x * xi.sin() * H.sin()).atan2(delta.cos() - x * xi.sin() * H.cos());
+    let delta_prime = ((delta.sin() - y * xi.sin()) * delta_alpha.cos())
+        .atan2(delta.cos() - x * xi.sin() * H.cos());
+    let H_prime = H - delta_alpha;
+
+    let e0 = (phi.sin() * delta_prime.sin() + phi.cos() * delta_prime.cos() * H_prime.cos())
+        .asin()
+        .to_degrees();
+    let atmosphere = pos.atmosphere();
+    let refraction = if e0 >= -(0.26667 + atmosphere.horizon_refraction()) {
+        atmosphere.pressure_hpa / 1010.0 * 283.0 / (273.0 + atmosphere.temperature_c)
+            * 1.02 / (60.0 * (e0 + 10.3 / (e0 + 5.11)).to_radians().tan())
+    } else {
+        0.0
+    };
+    let gamma = H_prime.sin()
+        .atan2(H_prime.cos() * phi.sin() - delta_prime.tan() * phi.cos())
+        .to_degrees();
+    TopocentricPosition {
+        azimuth: normalize_degrees(gamma + 180.0),
+        zenith: 90.0 - (e0 + refraction)
+    }
+}
+
+/// Sums the VSOP87 `series` of the earth at `JME` Julian millennia
+/// from J2000.0, in radians or astronomical units.
+fn earth_series(series: &[&[(f64, f64, f64)]], JME: f64) -> f64 {
+    series.iter()
+        .rev()
+        .map(|terms| terms.iter().map(|(A, B, C)| A * (B + C * JME).cos()).sum::<f64>())
+        .fold(0.0, |acc, term| acc * JME + term) / 1e8
+}
+
+/// The nutation in longitude and in obliquity at `JCE` Julian
+/// ephemeris centuries from J2000.0, in degrees.
+fn nutation(JCE: f64) -> (f64, f64) {
+    let X = [
+        297.85036 + 445267.111480 * JCE - 0.0019142 * JCE * JCE + JCE.powi(3) / 189474.0,
+        357.52772 + 35999.050340 * JCE - 0.0001603 * JCE * JCE - JCE.powi(3) / 300000.0,
+        134.96298 + 477198.867398 * JCE + 0.0086972 * JCE * JCE + JCE.powi(3) / 56250.0,
+        93.27191 + 483202.017538 * JCE - 0.0036825 * JCE * JCE + JCE.powi(3) / 327270.0,
+        125.04452 - 1934.136261 * JCE + 0.0020708 * JCE * JCE + JCE.powi(3) / 450000.0
+    ];
+    let (psi, epsilon) = NUTATION_TERMS.iter().fold((0.0, 0.0), |(psi, epsilon), (Y, a, b, c, d)| {
+        let arg = X.iter().zip(Y.iter()).map(|(x, &y)| x * y as f64).sum::<f64>().to_radians();
+        (psi + (a + b * JCE) * arg.sin(), epsilon + (c + d * JCE) * arg.cos())
+    });
+    (psi / 36e6, epsilon / 36e6)
+}
+
+/// The mean obliquity of the ecliptic, in arcseconds, as a polynomial
+/// of the Julian ephemeris millennia from J2000.0 over ten.
+const EPSILON_TERMS: [f64; 11] = [
+    84381.448, -4680.93, -1.55, 1999.25, -51.38, -249.67, -39.05, 7.12, 27.87, 5.79, 2.45
+];
+
+const L_TERMS: [&[(f64, f64, f64)]; 6] = [&L0, &L1, &L2, &L3, &L4, &L5];
+const B_TERMS: [&[(f64, f64, f64)]; 2] = [&B0, &B1];
+const R_TERMS: [&[(f64, f64, f64)]; 5] = [&R0, &R1, &R2, &R3, &R4];
+
+const L0: [(f64, f64, f64); 64] = [
+    (175347046.0, 0.0, 0.0),
+    (3341656.0, 4.6692568, 6283.07585),
+    (34894.0, 4.6261, 12566.1517),
+    (3497.0, 2.7441, 5753.3849),
+    (3418.0, 2.8289, 3.5231),
+    (3136.0, 3.6277, 77713.7715),
+    (2676.0, 4.4181, 7860.4194),
+    (2343.0, 6.1352, 3930.2097),
+    (1324.0, 0.7425, 11506.7698),
+    (1273.0, 2.0371, 529.691),
+    (1199.0, 1.1096, 1577.3435),
+    (990.0, 5.233, 5884.927),
+    (902.0, 2.045, 26.298),
+    (857.0, 3.508, 398.149),
+    (780.0, 1.179, 5223.694),
+    (753.0, 2.533, 5507.553),
+    (505.0, 4.583, 18849.228),
+    (492.0, 4.205, 775.523),
+    (357.0, 2.92, 0.067),
+    (317.0, 5.849, 11790.629),
+    (284.0, 1.899, 796.298),
+    (271.0, 0.315, 10977.079),
+    (243.0, 0.345, 5486.778),
+    (206.0, 4.806, 2544.314),
+    (205.0, 1.869, 5573.143),
+    (202.0, 2.458, 6069.777),
+    (156.0, 0.833, 213.299),
+    (132.0, 3.411, 2942.463),
+    (126.0, 1.083, 20.775),
+    (115.0, 0.645, 0.98),
+    (103.0, 0.636, 4694.003),
+    (102.0, 0.976, 15720.839),
+    (102.0, 4.267, 7.114),
+    (99.0, 6.21, 2146.17),
+    (98.0, 0.68, 155.42),
+    (86.0, 5.98, 161000.69),
+    (85.0, 1.3, 6275.96),
+    (85.0, 3.67, 71430.7),
+    (80.0, 1.81, 17260.15),
+    (79.0, 3.04, 12036.46),
+    (75.0, 1.76, 5088.63),
+    (74.0, 3.5, 3154.69),
+    (74.0, 4.68, 801.82),
+    (70.0, 0.83, 9437.76),
+    (62.0, 3.98, 8827.39),
+    (61.0, 1.82, 7084.9),
+    (57.0, 2.78, 6286.6),
+    (56.0, 4.39, 14143.5),
+    (56.0, 3.47, 6279.55),
+    (52.0, 0.19, 12139.55),
+    (52.0, 1.33, 1748.02),
+    (51.0, 0.28, 5856.48),
+    (49.0, 0.49, 1194.45),
+    (41.0, 5.37, 8429.24),
+    (41.0, 2.4, 19651.05),
+    (39.0, 6.17, 10447.39),
+    (37.0, 6.04, 10213.29),
+    (37.0, 2.57, 1059.38),
+    (36.0, 1.71, 2352.87),
+    (36.0, 1.78, 6812.77),
+    (33.0, 0.59, 17789.85),
+    (30.0, 0.44, 83996.85),
+    (30.0, 2.74, 1349.87),
+    (25.0, 3.16, 4690.48)
+];
+
+const L1: [(f64, f64, f64); 34] = [
+    (628331966747.0, 0.0, 0.0),
+    (206059.0, 2.678235, 6283.07585),
+    (4303.0, 2.6351, 12566.1517),
+    (425.0, 1.59, 3.523),
+    (119.0, 5.796, 26.298),
+    (109.0, 2.966, 1577.344),
+    (93.0, 2.59, 18849.23),
+    (72.0, 1.14, 529.69),
+    (68.0, 1.87, 398.15),
+    (67.0, 4.41, 5507.55),
+    (59.0, 2.89, 5223.69),
+    (56.0, 2.17, 155.42),
+    (45.0, 0.4, 796.3),
+    (36.0, 0.47, 775.52),
+    (29.0, 2.65, 7.11),
+    (21.0, 5.34, 0.98),
+    (19.0, 1.85, 5486.78),
+    (19.0, 4.97, 213.3),
+    (17.0, 2.99, 6275.96),
+    (16.0, 0.03, 2544.31),
+    (16.0, 1.43, 2146.17),
+    (15.0, 1.21, 10977.08),
+    (12.0, 2.83, 1748.02),
+    (12.0, 3.26, 5088.63),
+    (12.0, 5.27, 1194.45),
+    (12.0, 2.08, 4694.0),
+    (11.0, 0.77, 553.57),
+    (10.0, 1.3, 6286.6),
+    (10.0, 4.24, 1349.87),
+    (9.0, 2.7, 242.73),
+    (9.0, 5.64, 951.72),
+    (8.0, 5.3, 2352.87),
+    (6.0, 2.65, 9437.76),
+    (6.0, 4.67, 4690.48)
+];
+
+const L2: [(f64, f64, f64); 20] = [
+    (52919.0, 0.0, 0.0),
+    (8720.0, 1.0721, 6283.0758),
+    (309.0, 0.867, 12566.152),
+    (27.0, 0.05, 3.52),
+    (16.0, 5.19, 26.3),
+    (16.0, 3.68, 155.42),
+    (10.0, 0.76, 18849.23),
+    (9.0, 2.06, 77713.77),
+    (7.0, 0.83, 775.52),
+    (5.0, 4.66, 1577.34),
+    (4.0, 1.03, 7.11),
+    (4.0, 3.44, 5573.14),
+    (3.0, 5.14, 796.3),
+    (3.0, 6.05, 5507.55),
+    (3.0, 1.19, 242.73),
+    (3.0, 6.12, 529.69),
+    (3.0, 0.31, 398.15),
+    (3.0, 2.28, 553.57),
+    (2.0, 4.38, 5223.69),
+    (2.0, 3.75, 0.98)
+];
+
+const L3: [(f64, f64, f64); 7] = [
+    (289.0, 5.844, 6283.076),
+    (35.0, 0.0, 0.0),
+    (17.0, 5.49, 12566.15),
+    (3.0, 5.2, 155.42),
+    (1.0, 4.72, 3.52),
+    (1.0, 5.3, 18849.23),
+    (1.0, 5.97, 242.73)
+];
+
+const L4: [(f64, f64, f64); 3] = [
+    (114.0, 3.142, 0.0),
+    (8.0, 4.13, 6283.08),
+    (1.0, 3.84, 12566.15)
+];
+
+const L5: [(f64, f64, f64); 1] = [
+    (1.0, 3.14, 0.0)
+];
+
+const B0: [(f64, f64, f64); 5] = [
+    (280.0, 3.199, 84334.662),
+    (102.0, 5.422, 5507.553),
+    (80.0, 3.88, 5223.69),
+    (44.0, 3.7, 2352.87),
+    (32.0, 4.0, 1577.34)
+];
+
+const B1: [(f64, f64, f64); 2] = [
+    (9.0, 3.9, 5507.55),
+    (6.0, 1.73, 5223.69)
+];
+
+const R0: [(f64, f64, f64); 40] = [
+    (100013989.0, 0.0, 0.0),
+    (1670700.0, 3.0984635, 6283.07585),
+    (13956.0, 3.05525, 12566.1517),
+    (3084.0, 5.1985, 77713.7715),
+    (1628.0, 1.1739, 5753.3849),
+    (1576.0, 2.8469, 7860.4194),
+    (925.0, 5.453, 11506.77),
+    (542.0, 4.564, 3930.21),
+    (472.0, 3.661, 5884.927),
+    (346.0, 0.964, 5507.553),
+    (329.0, 5.9, 5223.694),
+    (307.0, 0.299, 5573.143),
+    (243.0, 4.273, 11790.629),
+    (212.0, 5.847, 1577.344),
+    (186.0, 5.022, 10977.079),
+    (175.0, 3.012, 18849.228),
+    (110.0, 5.055, 5486.778),
+    (98.0, 0.89, 6069.78),
+    (86.0, 5.69, 15720.84),
+    (86.0, 1.27, 161000.69),
+    (65.0, 0.27, 17260.15),
+    (63.0, 0.92, 529.69),
+    (57.0, 2.01, 83996.85),
+    (56.0, 5.24, 71430.7),
+    (49.0, 3.25, 2544.31),
+    (47.0, 2.58, 775.52),
+    (45.0, 5.54, 9437.76),
+    (43.0, 6.01, 6275.96),
+    (39.0, 5.36, 4694.0),
+    (38.0, 2.39, 8827.39),
+    (37.0, 0.83, 19651.05),
+    (37.0, 4.9, 12139.55),
+    (36.0, 1.67, 12036.46),
+    (35.0, 1.84, 2942.46),
+    (33.0, 0.24, 7084.9),
+    (32.0, 0.18, 5088.63),
+    (32.0, 1.78, 398.15),
+    (28.0, 1.21, 6286.6),
+    (28.0, 1.9, 6279.55),
+    (26.0, 4.59, 10447.39)
+];
+
+const R1: [(f64, f64, f64); 10] = [
+    (103019.0, 1.10749, 6283.07585),
+    (1721.0, 1.0644, 12566.1517),
+    (702.0, 3.142, 0.0),
+    (32.0, 1.02, 18849.23),
+    (31.0, 2.84, 5507.55),
+    (25.0, 1.32, 5223.69),
+    (18.0, 1.42, 1577.34),
+    (10.0, 5.91, 10977.08),
+    (9.0, 1.42, 6275.96),
+    (9.0, 0.27, 5486.78)
+];
+
+const R2: [(f64, f64, f64); 6] = [
+    (4359.0, 5.7846, 6283.0758),
+    (124.0, 5.579, 12566.152),
+    (12.0, 3.14, 0.0),
+    (9.0, 3.63, 77713.77),
+    (6.0, 1.87, 5573.14),
+    (3.0, 5.47, 18849.23)
+];
+
+const R3: [(f64, f64, f64); 2] = [
+    (145.0, 4.273, 6283.076),
+    (7.0, 3.92, 12566.15)
+];
+
+const R4: [(f64, f64, f64); 1] = [
+    (4.0, 2.56, 6283.08)
+];
+
+/// The periodic terms of the nutation: the multiples of the mean
+/// elongation of the moon, the anomalies of the sun and of the moon,
+/// the argument of latitude of the moon and the longitude of its
+/// ascending node, then the coefficients of the sine of the longitude
+/// and of the cosine of the obliquity, in 0.0001 arcsecond.
+const NUTATION_TERMS: [([i8; 5], f64, f64, f64, f64); 63] = [
+    ([0, 0, 0, 0, 1], -171996.0, -174.2, 92025.0, 8.9),
+    ([-2, 0, 0, 2, 2], -13187.0, -1.6, 5736.0, -3.1),
+    ([0, 0, 0, 2, 2], -2274.0, -0.2, 977.0, -0.5),
+    ([0, 0, 0, 0, 2], 2062.0, 0.2, -895.0, 0.5),
+    ([0, 1, 0, 0, 0], 1426.0, -3.4, 54.0, -0.1),
+    ([0, 0, 1, 0, 0], 712.0, 0.1, -7.0, 0.0),
+    ([-2, 1, 0, 2, 2], -517.0, 1.2, 224.0, -0.6),
+    ([0, 0, 0, 2, 1], -386.0, -0.4, 200.0, 0.0),
+    ([0, 0, 1, 2, 2], -301.0, 0.0, 129.0, -0.1),
+    ([-2, -1, 0, 2, 2], 217.0, -0.5, -95.0, 0.3),
+    ([-2, 0, 1, 0, 0], -158.0, 0.0, 0.0, 0.0),
+    ([-2, 0, 0, 2, 1], 129.0, 0.1, -70.0, 0.0),
+    ([0, 0, -1, 2, 2], 123.0, 0.0, -53.0, 0.0),
+    ([2, 0, 0, 0, 0], 63.0, 0.0, 0.0, 0.0),
+    ([0, 0, 1, 0, 1], 63.0, 0.1, -33.0, 0.0),
+    ([2, 0, -1, 2, 2], -59.0, 0.0, 26.0, 0.0),
+    ([0, 0, -1, 0, 1], -58.0, -0.1, 32.0, 0.0),
+    ([0, 0, 1, 2, 1], -51.0, 0.0, 27.0, 0.0),
+    ([-2, 0, 2, 0, 0], 48.0, 0.0, 0.0, 0.0),
+    ([0, 0, -2, 2, 1], 46.0, 0.0, -24.0, 0.0),
+    ([2, 0, 0, 2, 2], -38.0, 0.0, 16.0, 0.0),
+    ([0, 0, 2, 2, 2], -31.0, 0.0, 13.0, 0.0),
+    ([0, 0, 2, 0, 0], 29.0, 0.0, 0.0, 0.0),
+    ([-2, 0, 1, 2, 2], 29.0, 0.0, -12.0, 0.0),
+    ([0, 0, 0, 2, 0], 26.0, 0.0, 0.0, 0.0),
+    ([-2, 0, 0, 2, 0], -22.0, 0.0, 0.0, 0.0),
+    ([0, 0, -1, 2, 1], 21.0, 0.0, -10.0, 0.0),
+    ([0, 2, 0, 0, 0], 17.0, -0.1, 0.0, 0.0),
+    ([2, 0, -1, 0, 1], 16.0, 0.0, -8.0, 0.0),
+    ([-2, 2, 0, 2, 2], -16.0, 0.1, 7.0, 0.0),
+    ([0, 1, 0, 0, 1], -15.0, 0.0, 9.0, 0.0),
+    ([-2, 0, 1, 0, 1], -13.0, 0.0, 7.0, 0.0),
+    ([0, -1, 0, 0, 1], -12.0, 0.0, 6.0, 0.0),
+    ([0, 0, 2, -2, 0], 11.0, 0.0, 0.0, 0.0),
+    ([2, 0, -1, 2, 1], -10.0, 0.0, 5.0, 0.0),
+    ([2, 0, 1, 2, 2], -8.0, 0.0, 3.0, 0.0),
+    ([0, 1, 0, 2, 2], 7.0, 0.0, -3.0, 0.0),
+    ([-2, 1, 1, 0, 0], -7.0, 0.0, 0.0, 0.0),
+    ([0, -1, 0, 2, 2], -7.0, 0.0, 3.0, 0.0),
+    ([2, 0, 0, 2, 1], -7.0, 0.0, 3.0, 0.0),
+    ([2, 0, 1, 0, 0], 6.0, 0.0, 0.0, 0.0),
+    ([-2, 0, 2, 2, 2], 6.0, 0.0, -3.0, 0.0),
+    ([-2, 0, 1, 2, 1], 6.0, 0.0, -3.0, 0.0),
+    ([2, 0, -2, 0, 1], -6.0, 0.0, 3.0, 0.0),
+    ([2, 0, 0, 0, 1], -6.0, 0.0, 3.0, 0.0),
+    ([0, -1, 1, 0, 0], 5.0, 0.0, 0.0, 0.0),
+    ([-2, -1, 0, 2, 1], -5.0, 0.0, 3.0, 0.0),
+    ([-2, 0, 0, 0, 1], -5.0, 0.0, 3.0, 0.0),
+    ([0, 0, 2, 2, 1], -5.0, 0.0, 3.0, 0.0),
+    ([-2, 0, 2, 0, 1], 4.0, 0.0, 0.0, 0.0),
+    ([-2, 1, 0, 2, 1], 4.0, 0.0, 0.0, 0.0),
+    ([0, 0, 1, -2, 0], 4.0, 0.0, 0.0, 0.0),
+    ([-1, 0, 1, 0, 0], -4.0, 0.0, 0.0, 0.0),
+    ([-2, 1, 0, 0, 0], -4.0, 0.0, 0.0, 0.0),
+    ([1, 0, 0, 0, 0], -4.0, 0.0, 0.0, 0.0),
+    ([0, 0, 1, 2, 0], 3.0, 0.0, 0.0, 0.0),
+    ([0, 0, -2, 2, 2], -3.0, 0.0, 0.0, 0.0),
+    ([-1, -1, 1, 0, 0], -3.0, 0.0, 0.0, 0.0),
+    ([0, 1, 1, 0, 0], -3.0, 0.0, 0.0, 0.0),
+    ([0, -1, 1, 2, 2], -3.0, 0.0, 0.0, 0.0),
+    ([2, -1, -1, 2, 2], -3.0, 0.0, 0.0, 0.0),
+    ([0, 0, 3, 2, 2], -3.0, 0.0, 0.0, 0.0),
+    ([2, -1, 0, 2, 2], -3.0, 0.0, 0.0, 0.0)
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::pos::Atmosphere;
+
+    fn reference_position() -> GlobalPosition {
+        GlobalPosition::at(39.742476, -105.1786)
+            .with_elevation(1830.14)
+            .with_atmosphere(Atmosphere { pressure_hpa: 820.0, temperature_c: 11.0 })
+    }
+
+    #[test]
+    fn position_should_match_the_reference_example() {
+        // 2003-10-17 12:30:30 at UTC-7, from appendix A.5 of the paper.
+        let time = NaiveDate::from_ymd_opt(2003, 10, 17).unwrap()
+            .and_hms_opt(19, 30, 30).unwrap()
+            .and_utc();
+        let jd = julian_day(time);
+        assert!((jd - 2452930.312847).abs() < 1e-6);
+        let sun = Geocentric::at(jd, 67.0);
+        assert!((sun.R - 0.9965422974).abs() < 1e-8);
+        assert!((sun.alpha - 202.22741).abs() < 1e-5);
+        assert!((sun.delta - -9.31434).abs() < 1e-5);
+        let (delta_psi, delta_epsilon) = nutation((jd + 67.0 / 86400.0 - J2000) / 36525.0);
+        assert!((delta_psi - -0.00399840).abs() < 1e-8);
+        assert!((delta_epsilon - 0.00166657).abs() < 1e-8);
+        let position = topocentric(jd, 67.0, &reference_position());
+        assert!((position.zenith - 50.11162).abs() < 1e-5, "{:?}", position);
+        assert!((position.azimuth - 194.34024).abs() < 1e-5, "{:?}", position);
+    }
+
+    #[test]
+    fn events_should_agree_with_the_other_backends() {
+        let places = [(51.4810066, 0.0081805), (40.7831, -73.9712), (-33.8688, 151.2093), (64.1466, -21.9426)];
+        for &(lat, lng) in &places {
+            let pos = GlobalPosition::at(lat, lng);
+            for day in (18_262..18_628).step_by(15) {
+                for &event in &[SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET, SunEvent::SOLAR_MIDNIGHT] {
+                    let usno = crate::epoch::time_of_event_unix(day, &pos, event).unwrap();
+                    let meeus = crate::meeus::event_result_unix(day, &pos, event).at().unwrap();
+                    let spa = event_result_unix(day, &pos, event).at().unwrap();
+                    assert!((usno - spa).abs() < 120, "{} {} {} {}", lat, day, event, usno - spa);
+                    assert!((meeus - spa).abs() <= 10, "{} {} {} {}", lat, day, event, meeus - spa);
+                }
+            }
+        }
+        let pole = GlobalPosition::at(78.2232, 15.6267);
+        assert_eq!(event_result_unix(18_799, &pole, SunEvent::SUNSET), EventResult::PolarDay);
+        assert_eq!(event_result_unix(18_982, &pole, SunEvent::SUNRISE), EventResult::PolarNight);
+    }
+
+}
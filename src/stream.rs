@@ -0,0 +1,201 @@
+//! This module provides a `Stream` of the forecasted sun events, which
+//! yields each event once its time has come, and one of the events of a
+//! [Playback] as the simulated clock reaches them.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ Context, Poll };
+use chrono::{ DateTime, Utc };
+use futures_core::Stream;
+use tokio::time::{ Instant, Sleep };
+use super::clock::{ nap_until, Clock, SystemClock };
+use super::event::SunEvent;
+use super::iter::ForecastedSunEvents;
+use super::playback::{ Playback, SimulatedClock };
+
+impl ForecastedSunEvents {
+
+    /// Yield the events as a stream, each one at the time it occurs on
+    /// the system clock. Requires a tokio runtime with time enabled.
+    pub fn into_stream(self) -> SunEventStream {
+        self.into_stream_with_clock(SystemClock)
+    }
+
+    /// Yield the events as a stream, each one at the time it occurs on
+    /// `clock`. Requires a tokio runtime with time enabled.
+    pub fn into_stream_with_clock<C: Clock>(self, clock: C) -> SunEventStream<C> {
+        SunEventStream {
+            events: self,
+            clock,
+            pending: None,
+            sleep: Box::pin(tokio::time::sleep(std::time::Duration::ZERO))
+        }
+    }
+
+}
+
+/// A stream that yields the forecasted SunEvents as they occur, so that
+/// async services can wait for them with
+/// `while let Some((event, time)) = stream.next().await`.
+///
+/// The events which already occurred, when the forecast starts in the
+/// past or the machine was suspended past them, are yielded right away.
+#[derive(Debug)]
+pub struct SunEventStream<C = SystemClock> {
+    events: ForecastedSunEvents,
+    clock: C,
+    pending: Option<(SunEvent, DateTime<Utc>)>,
+    sleep: Pin<Box<Sleep>>
+}
+
+impl<C: Clock + Unpin> Stream for SunEventStream<C> {
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let (_, time) = match this.pending {
+            Some(pending) => pending,
+            None => match this.events.next() {
+                Some(next) => *this.pending.insert(next),
+                None => return Poll::Ready(None)
+            }
+        };
+        match poll_until(&this.clock, time, &mut this.sleep, cx) {
+            Poll::Ready(()) => Poll::Ready(this.pending.take()),
+            Poll::Pending => Poll::Pending
+        }
+    }
+
+}
+
+impl<I, C> Playback<I, C>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)>,
+    C: Clock
+{
+
+    /// Replay the events as a stream rather than blocking the thread
+    /// between them, each one once the simulated clock reaches it.
+    /// Requires a tokio runtime with time enabled.
+    pub fn into_stream(self) -> PlaybackStream<I, C> {
+        PlaybackStream {
+            events: self.events,
+            clock: self.clock,
+            pending: None,
+            sleep: Box::pin(tokio::time::sleep(std::time::Duration::ZERO))
+        }
+    }
+
+}
+
+/// A stream replaying sun events in accelerated time, as
+/// [Playback] does but without blocking.
+#[derive(Debug)]
+pub struct PlaybackStream<I, C = SystemClock> {
+    events: I,
+    clock: SimulatedClock<C>,
+    pending: Option<(SunEvent, DateTime<Utc>)>,
+    sleep: Pin<Box<Sleep>>
+}
+
+impl<I, C> Stream for PlaybackStream<I, C>
+where
+    I: Iterator<Item = (SunEvent, DateTime<Utc>)> + Unpin,
+    C: Clock + Unpin
+{
+
+    type Item = (SunEvent, DateTime<Utc>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let (_, time) = match this.pending {
+            Some(pending) => pending,
+            None => match this.events.next() {
+                Some(next) => *this.pending.insert(next),
+                None => return Poll::Ready(None)
+            }
+        };
+        // The naps follow the wrapped clock, as the sleeps of tokio do.
+        let due = this.clock.real_time_of(time);
+        match poll_until(&this.clock.clock, due, &mut this.sleep, cx) {
+            Poll::Ready(()) => Poll::Ready(this.pending.take()),
+            Poll::Pending => Poll::Pending
+        }
+    }
+
+}
+
+/// Polls `sleep` in naps until `clock` reaches `time`.
+fn poll_until<C: Clock>(clock: &C, time: DateTime<Utc>, sleep: &mut Pin<Box<Sleep>>, cx: &mut Context<'_>) -> Poll<()> {
+    while let Some(nap) = nap_until(clock, time) {
+        sleep.as_mut().reset(Instant::now() + nap);
+        if sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+    }
+    Poll::Ready(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use chrono::{ Duration, TimeZone };
+    use futures::StreamExt;
+    use crate::clock::test::TokioClock;
+    use crate::iter::SunEvents;
+    use crate::pos::GlobalPosition;
+
+    #[tokio::test(start_paused = true)]
+    async fn events_should_stream_at_their_time() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap();
+        let clock = TokioClock::at(start);
+        let events = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE, SunEvent::SUNSET]).forecast();
+        let expected: Vec<_> = events.clone().take(3).collect();
+        let mut stream = events.into_stream_with_clock(clock.clone());
+        for occurrence in expected {
+            assert_eq!(stream.next().await, Some(occurrence));
+            let late = clock.now() - occurrence.1;
+            assert!(late >= Duration::zero() && late < Duration::seconds(1), "{}", late);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn past_events_should_stream_right_away() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let clock = TokioClock::at(start);
+        let mut stream = SunEvents::starting_from(start, pos, &[SunEvent::SOLAR_NOON])
+            .forecast()
+            .into_stream_with_clock(clock.clone());
+        // The clock is set three days forward, as after a suspend.
+        clock.jump(Duration::days(3));
+        let resumed = Instant::now();
+        for _ in 0..3 {
+            assert!(stream.next().await.is_some());
+        }
+        assert!(resumed.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn playbacks_should_stream_in_accelerated_time() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2024, 6, 21, 0, 0, 0).unwrap();
+        let real = TokioClock::at(Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap());
+        let events = SunEvents::starting_from(start, pos, &[SunEvent::SUNRISE, SunEvent::SUNSET]).forecast().take(4);
+        let expected: Vec<_> = events.clone().collect();
+        // A simulated day per real minute.
+        let clock = SimulatedClock::new(real.clone(), start, 1440.0);
+        let mut stream = Playback::with_clock(events, clock.clone()).into_stream();
+        for occurrence in expected {
+            assert_eq!(stream.next().await, Some(occurrence));
+            let late = real.now() - clock.real_time_of(occurrence.1);
+            assert!(late >= Duration::zero() && late < Duration::milliseconds(10), "{}", late);
+        }
+        assert_eq!(stream.next().await, None);
+        assert!(real.now() - Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap() < Duration::minutes(3));
+    }
+
+}
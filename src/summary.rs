@@ -0,0 +1,103 @@
+//! This module computes all the events of a day in one pass.
+
+use chrono::{ DateTime, NaiveDate, Utc };
+use super::adapter::chrono::{ from_unix, unix_day };
+use super::core::EventResult;
+use super::epoch::event_results_unix;
+use super::event::SunEvent;
+use super::pos::GlobalPosition;
+
+/// The events of one day at one position, computed together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaySummary {
+    date: NaiveDate,
+    events: Vec<(SunEvent, EventResult<DateTime<Utc>>)>
+}
+
+impl DaySummary {
+
+    /// Computes `events` on the local mean `date` at `pos`, sharing the
+    /// computations common to the events of a day.
+    pub fn compute(date: NaiveDate, pos: &GlobalPosition, events: &[SunEvent]) -> Self {
+        let mut events = events.to_owned();
+        events.sort();
+        events.dedup();
+        let results = event_results_unix(unix_day(date), pos, &events);
+        let events = events.into_iter()
+            .zip(results)
+            .filter_map(|(event, result)| {
+                let result = match result {
+                    EventResult::At(time) => EventResult::At(from_unix(time)?),
+                    EventResult::PolarDay => EventResult::PolarDay,
+                    EventResult::PolarNight => EventResult::PolarNight
+                };
+                Some((event, result))
+            })
+            .collect();
+        DaySummary { date, events }
+    }
+
+    /// The date of the summary, in the local mean time of its position.
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// The time of `event`, or None if it doesn't happen that day or
+    /// wasn't requested.
+    pub fn time_of(&self, event: SunEvent) -> Option<DateTime<Utc>> {
+        self.result_of(event).and_then(EventResult::at)
+    }
+
+    /// The outcome of computing `event`, or None if it wasn't requested.
+    pub fn result_of(&self, event: SunEvent) -> Option<EventResult<DateTime<Utc>>> {
+        self.events.iter()
+            .find(|(e, _)| *e == event)
+            .map(|(_, result)| *result)
+    }
+
+    /// The events happening that day, in chronological order.
+    pub fn events(&self) -> Vec<(SunEvent, DateTime<Utc>)> {
+        let mut events: Vec<_> = self.events.iter()
+            .filter_map(|(event, result)| result.at().map(|time| (*event, time)))
+            .collect();
+        events.sort_by_key(|(_, time)| *time);
+        events
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adapter::chrono::time_of_event_on;
+    use crate::event::{ Event, Zenith };
+
+    #[test]
+    fn summary_should_match_events_computed_alone() {
+        let pos = GlobalPosition::at(40.7831, -73.9712);
+        let date = NaiveDate::from_ymd_opt(2021, 3, 20).unwrap();
+        let events = [SunEvent::SUNSET, SunEvent::DAWN, SunEvent::SOLAR_NOON, SunEvent::SUNRISE, SunEvent::DUSK];
+        let summary = DaySummary::compute(date, &pos, &events);
+        assert_eq!(summary.date(), date);
+        let tz = pos.lng_timezone();
+        for event in &events {
+            let expected = time_of_event_on(date, &tz, &pos, *event).map(|time| time.with_timezone(&Utc));
+            assert_eq!(summary.time_of(*event), expected);
+        }
+        let order: Vec<_> = summary.events().into_iter().map(|(event, _)| event).collect();
+        assert_eq!(order, vec![SunEvent::DAWN, SunEvent::SUNRISE, SunEvent::SOLAR_NOON, SunEvent::SUNSET, SunEvent::DUSK]);
+        assert_eq!(summary.result_of(SunEvent::SOLAR_MIDNIGHT), None);
+    }
+
+    #[test]
+    fn summary_should_tell_polar_days_apart() {
+        let pos = GlobalPosition::at(78.2232, 15.6267);
+        let date = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        let golden = SunEvent::new(Zenith::Golden, Event::Sunrise);
+        let summary = DaySummary::compute(date, &pos, &[SunEvent::SUNRISE, golden, SunEvent::SOLAR_NOON]);
+        assert_eq!(summary.result_of(SunEvent::SUNRISE), Some(EventResult::PolarDay));
+        assert_eq!(summary.time_of(SunEvent::SUNRISE), None);
+        assert_eq!(summary.events().len(), 1);
+    }
+
+}
@@ -0,0 +1,228 @@
+//! This module computes the spans of the civil, nautical and
+//! astronomical twilights of a day, and the phase of the day at
+//! any instant or over time.
+
+use chrono::{ DateTime, NaiveDate, TimeZone, Utc };
+use std::iter::FusedIterator;
+use std::ops::Range;
+use super::adapter::chrono::{ from_unix, unix_day };
+use super::core::EventResult;
+use super::epoch::{ event_result_unix, time_of_event_unix };
+use super::event::{ DayPhase, Event, SunEvent, Zenith };
+use super::iter::{ ForecastedSunEvents, SunEvents };
+use super::pos::GlobalPosition;
+use super::solar;
+
+/// The morning and evening spans of one twilight band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Twilight {
+    /// The span before noon with the sun in the band, None if the
+    /// sun doesn't enter it.
+    pub morning: Option<Range<DateTime<Utc>>>,
+    /// The span after noon with the sun in the band, None if the
+    /// sun doesn't enter it.
+    pub evening: Option<Range<DateTime<Utc>>>
+}
+
+/// The twilights of a day, each band being delimited by the zenith
+/// above and the zenith below it.
+///
+/// When the sun stays within a band through the night, as in summer
+/// at high latitudes, its morning span starts and its evening span ends
+/// at solar midnight. When the sun stays within a band through the day,
+/// its spans meet at solar noon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwilightPeriods {
+    /// The sun between the horizon and 6° below it.
+    pub civil: Twilight,
+    /// The sun between 6° and 12° below the horizon.
+    pub nautical: Twilight,
+    /// The sun between 12° and 18° below the horizon.
+    pub astronomical: Twilight
+}
+
+impl TwilightPeriods {
+
+    /// The band of twilight whose lower bound is `zenith`, None for the
+    /// zeniths not bounding a twilight.
+    pub fn below(&self, zenith: Zenith) -> Option<&Twilight> {
+        match zenith {
+            Zenith::Civil => Some(&self.civil),
+            Zenith::Nautical => Some(&self.nautical),
+            Zenith::Astronomical => Some(&self.astronomical),
+            Zenith::Golden | Zenith::Official | Zenith::BlueHour => None
+        }
+    }
+
+}
+
+/// Computes the twilight periods of the local mean `date` at `pos`.
+pub fn twilight_periods(date: NaiveDate, pos: &GlobalPosition) -> TwilightPeriods {
+    let day = unix_day(date);
+    TwilightPeriods {
+        civil: twilight(day, pos, Zenith::Official, Zenith::Civil),
+        nautical: twilight(day, pos, Zenith::Civil, Zenith::Nautical),
+        astronomical: twilight(day, pos, Zenith::Nautical, Zenith::Astronomical)
+    }
+}
+
+/// Determines the phase of the day at `time` at `pos`, from the
+/// altitude of the sun.
+pub fn day_phase<Tz: TimeZone>(pos: &GlobalPosition, time: DateTime<Tz>) -> DayPhase {
+    DayPhase::at_altitude(solar::sun_position(pos, time).altitude)
+}
+
+/// The events separating the phases of the day.
+const PHASE_BOUNDARIES: [SunEvent; 10] = [
+    SunEvent::new(Zenith::Golden, Event::Sunrise),
+    SunEvent::new(Zenith::Golden, Event::Sunset),
+    SunEvent::SUNRISE,
+    SunEvent::SUNSET,
+    SunEvent::DAWN,
+    SunEvent::DUSK,
+    SunEvent::new(Zenith::Nautical, Event::Sunrise),
+    SunEvent::new(Zenith::Nautical, Event::Sunset),
+    SunEvent::new(Zenith::Astronomical, Event::Sunrise),
+    SunEvent::new(Zenith::Astronomical, Event::Sunset)
+];
+
+/// An iterator that yields the contiguous intervals of each phase of
+/// the day, as `(phase, start, end)`, moving forward in time.
+///
+/// The first interval starts at the start date; each following one
+/// starts where the previous one ended.
+#[derive(Debug, Clone)]
+pub struct DayPhases {
+    start: DateTime<Utc>,
+    boundaries: ForecastedSunEvents
+}
+
+impl DayPhases {
+
+    /// List the phases of the day at `position` from `start_date` on.
+    pub fn starting_from(start_date: DateTime<Utc>, position: GlobalPosition) -> Self {
+        let boundaries = SunEvents::starting_from(start_date, position, &PHASE_BOUNDARIES).forecast();
+        DayPhases { start: start_date, boundaries }
+    }
+
+}
+
+impl Iterator for DayPhases {
+
+    type Item = (DayPhase, DateTime<Utc>, DateTime<Utc>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (event, end) = self.boundaries.next()?;
+        let (phase, _) = event.phase_boundary()?;
+        let start = std::mem::replace(&mut self.start, end);
+        Some((phase, start, end))
+    }
+
+}
+
+impl FusedIterator for DayPhases {}
+
+fn twilight(day: i64, pos: &GlobalPosition, upper: Zenith, lower: Zenith) -> Twilight {
+    let transit = |day: i64, event: SunEvent| {
+        time_of_event_unix(day, pos, event).expect("the sun should always transit")
+    };
+    let midnight = transit(day, SunEvent::SOLAR_MIDNIGHT);
+    let noon = transit(day, SunEvent::SOLAR_NOON);
+    let next_midnight = transit(day + 1, SunEvent::SOLAR_MIDNIGHT);
+    let result = |zenith: Zenith, event: Event| event_result_unix(day, pos, SunEvent::new(zenith, event));
+
+    let morning = span(result(lower, Event::Sunrise), result(upper, Event::Sunrise), midnight, noon);
+    let evening = span(result(upper, Event::Sunset), result(lower, Event::Sunset), noon, next_midnight);
+    Twilight { morning, evening }
+}
+
+/// The span from the `from` event to the `to` event, in Unix seconds,
+/// within `earliest..latest` where the events don't occur.
+fn span(from: EventResult<i64>, to: EventResult<i64>, earliest: i64, latest: i64) -> Option<Range<DateTime<Utc>>> {
+    use EventResult::*;
+    let start = match from {
+        At(time) => time,
+        // The sun stays above the band's boundary on the side of the
+        // night, so it lingers in the band through midnight.
+        PolarDay => earliest,
+        PolarNight => return None
+    };
+    let end = match to {
+        At(time) => time,
+        // The sun never rises above the band's boundary on the side of
+        // the day, so it lingers in the band through noon.
+        PolarNight => latest,
+        PolarDay => return None
+    };
+    if start >= end {
+        return None;
+    }
+    Some(from_unix(start)?..from_unix(end)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adapter::chrono::time_of_event;
+    use chrono::{ Duration, TimeZone };
+
+    #[test]
+    #[allow(deprecated)]
+    fn civil_twilight_should_span_dawn_to_sunrise() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let date = NaiveDate::from_ymd_opt(2020, 3, 1).unwrap();
+        let periods = twilight_periods(date, &pos);
+        let utc_date = Utc.from_utc_date(&date);
+        let dawn = time_of_event(utc_date, &pos, SunEvent::DAWN).unwrap();
+        let sunrise = time_of_event(utc_date, &pos, SunEvent::SUNRISE).unwrap();
+        assert_eq!(periods.civil.morning, Some(dawn..sunrise));
+        let evening = periods.astronomical.evening.clone().unwrap();
+        assert!(evening.end - evening.start > Duration::minutes(30));
+        assert_eq!(periods.below(Zenith::Nautical), Some(&periods.nautical));
+    }
+
+    #[test]
+    fn day_phase_should_change_at_the_twilight_boundaries() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let date = NaiveDate::from_ymd_opt(2020, 3, 1).unwrap();
+        let periods = twilight_periods(date, &pos);
+        let civil = periods.civil.morning.unwrap();
+        let minute = Duration::minutes(1);
+        assert_eq!(day_phase(&pos, civil.start + minute), DayPhase::CivilTwilight);
+        assert_eq!(day_phase(&pos, civil.start - minute), DayPhase::NauticalTwilight);
+        assert_eq!(day_phase(&pos, civil.end + minute), DayPhase::GoldenHour);
+        let noon = Utc.with_ymd_and_hms(2020, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(day_phase(&pos, noon), DayPhase::Day);
+        assert_eq!(day_phase(&pos, noon + Duration::hours(12)), DayPhase::Night);
+    }
+
+    #[test]
+    fn day_phases_should_be_contiguous_and_alternate() {
+        let pos = GlobalPosition::at(51.4810066, 0.0081805);
+        let start = Utc.with_ymd_and_hms(2020, 3, 1, 12, 0, 0).unwrap();
+        let phases: Vec<_> = DayPhases::starting_from(start, pos.clone()).take(20).collect();
+        assert_eq!(phases[0].1, start);
+        assert_eq!(phases[0].0, DayPhase::Day);
+        for pair in phases.windows(2) {
+            assert_eq!(pair[0].2, pair[1].1);
+            assert_ne!(pair[0].0, pair[1].0);
+        }
+        let night = phases.iter().find(|(phase, _, _)| *phase == DayPhase::Night).unwrap();
+        assert_eq!(day_phase(&pos, night.1 + (night.2 - night.1) / 2), DayPhase::Night);
+    }
+
+    #[test]
+    fn twilight_should_last_through_white_nights() {
+        // In Trondheim around the summer solstice, the sun stays in
+        // civil twilight through the night.
+        let pos = GlobalPosition::at(63.4305, 10.3951);
+        let date = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        let periods = twilight_periods(date, &pos);
+        let morning = periods.civil.morning.unwrap();
+        let midnight = time_of_event_unix(unix_day(date), &pos, SunEvent::SOLAR_MIDNIGHT).unwrap();
+        assert_eq!(morning.start.timestamp(), midnight);
+        assert!(periods.nautical.morning.is_none());
+        assert!(periods.astronomical.evening.is_none());
+    }
+
+}
@@ -0,0 +1,238 @@
+//! Measures how far an algorithm drifts from reference times of the
+//! sun events, to keep an eye on accuracy across versions.
+//!
+//! The embedded [references] were computed with the NREL Solar
+//! Position Algorithm (see the `spa` feature), which places the sun
+//! to 0.0003 degree, a second or so in the times of the events.
+//! Published tables, such as those of the USNO or NOAA, can be checked
+//! the same way by building [Reference]s from them.
+
+use std::fmt;
+use crate::algorithm::SolarAlgorithm;
+use crate::event::SunEvent;
+use crate::pos::GlobalPosition;
+
+/// The time of an event at a position, to compare against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reference {
+    pub lat: f64,
+    pub lng: f64,
+    /// The UTC day of the event, counted in days since the Unix epoch,
+    /// as for [event_result_unix](crate::epoch::event_result_unix).
+    pub day: i64,
+    pub event: SunEvent,
+    /// The Unix timestamp (in seconds) of the event.
+    pub time: i64
+}
+
+/// How far the times of an algorithm fall from a set of [Reference]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// The number of references the algorithm found an event for.
+    pub compared: usize,
+    /// The references the algorithm found no event for.
+    pub missing: Vec<Reference>,
+    /// The mean of the absolute errors, in seconds.
+    pub mean_error: f64,
+    /// The largest absolute error, in seconds.
+    pub max_error: i64,
+    /// The reference of the largest error.
+    pub worst: Option<Reference>
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} events compared, {} missing, mean error {:.1}s, max error {}s",
+            self.compared,
+            self.missing.len(),
+            self.mean_error,
+            self.max_error
+        )?;
+        if let Some(worst) = self.worst {
+            write!(f, " ({} at {}, {} on day {})", worst.event, worst.lat, worst.lng, worst.day)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the times `algorithm` finds to `references`.
+pub fn verify(algorithm: &dyn SolarAlgorithm, references: &[Reference]) -> Report {
+    let mut report = Report { compared: 0, missing: Vec::new(), mean_error: 0.0, max_error: 0, worst: None };
+    let mut total = 0;
+    for reference in references {
+        let pos = GlobalPosition::at(reference.lat, reference.lng);
+        match algorithm.event_result_unix(reference.day, &pos, reference.event).at() {
+            Some(time) => {
+                let error = (time - reference.time).abs();
+                if report.worst.is_none() || error > report.max_error {
+                    report.max_error = error;
+                    report.worst = Some(*reference);
+                }
+                report.compared += 1;
+                total += error;
+            },
+            None => report.missing.push(*reference)
+        }
+    }
+    if report.compared > 0 {
+        report.mean_error = total as f64 / report.compared as f64;
+    }
+    report
+}
+
+/// The embedded references: civil dawn, sunrise, solar noon, sunset
+/// and civil dusk around the solstices and equinoxes of 2024 in six
+/// cities, at sea level and in the standard atmosphere.
+pub fn references() -> Vec<Reference> {
+    REFERENCES.iter()
+        .map(|&(lat, lng, day, event, time)| Reference { lat, lng, day, event, time })
+        .collect()
+}
+
+const REFERENCES: [(f64, f64, i64, SunEvent, i64); 118] = [
+    (51.4779, -0.0015, 19_802, SunEvent::DAWN, 1_710_912_513),
+    (51.4779, -0.0015, 19_802, SunEvent::SUNRISE, 1_710_914_508),
+    (51.4779, -0.0015, 19_802, SunEvent::SOLAR_NOON, 1_710_936_439),
+    (51.4779, -0.0015, 19_802, SunEvent::SUNSET, 1_710_958_431),
+    (51.4779, -0.0015, 19_802, SunEvent::DUSK, 1_710_960_432),
+    (51.4779, -0.0015, 19_894, SunEvent::DAWN, 1_718_852_096),
+    (51.4779, -0.0015, 19_894, SunEvent::SUNRISE, 1_718_854_958),
+    (51.4779, -0.0015, 19_894, SunEvent::SOLAR_NOON, 1_718_884_903),
+    (51.4779, -0.0015, 19_894, SunEvent::SUNSET, 1_718_914_848),
+    (51.4779, -0.0015, 19_894, SunEvent::DUSK, 1_718_917_711),
+    (51.4779, -0.0015, 19_988, SunEvent::DAWN, 1_726_982_002),
+    (51.4779, -0.0015, 19_988, SunEvent::SUNRISE, 1_726_984_002),
+    (51.4779, -0.0015, 19_988, SunEvent::SOLAR_NOON, 1_727_005_952),
+    (51.4779, -0.0015, 19_988, SunEvent::SUNSET, 1_727_027_842),
+    (51.4779, -0.0015, 19_988, SunEvent::DUSK, 1_727_029_836),
+    (51.4779, -0.0015, 20_078, SunEvent::DAWN, 1_734_765_781),
+    (51.4779, -0.0015, 20_078, SunEvent::SUNRISE, 1_734_768_199),
+    (51.4779, -0.0015, 20_078, SunEvent::SOLAR_NOON, 1_734_782_298),
+    (51.4779, -0.0015, 20_078, SunEvent::SUNSET, 1_734_796_397),
+    (51.4779, -0.0015, 20_078, SunEvent::DUSK, 1_734_798_815),
+    // New York
+    (40.7128, -74.006, 19_802, SunEvent::DAWN, 1_710_930_673),
+    (40.7128, -74.006, 19_802, SunEvent::SUNRISE, 1_710_932_310),
+    (40.7128, -74.006, 19_802, SunEvent::SOLAR_NOON, 1_710_954_196),
+    (40.7128, -74.006, 19_802, SunEvent::SUNSET, 1_710_976_124),
+    (40.7128, -74.006, 19_802, SunEvent::DUSK, 1_710_977_764),
+    (40.7128, -74.006, 19_894, SunEvent::DAWN, 1_718_873_488),
+    (40.7128, -74.006, 19_894, SunEvent::SUNRISE, 1_718_875_494),
+    (40.7128, -74.006, 19_894, SunEvent::SOLAR_NOON, 1_718_902_666),
+    (40.7128, -74.006, 19_894, SunEvent::SUNSET, 1_718_929_839),
+    (40.7128, -74.006, 19_894, SunEvent::DUSK, 1_718_931_845),
+    (40.7128, -74.006, 19_988, SunEvent::DAWN, 1_727_000_204),
+    (40.7128, -74.006, 19_988, SunEvent::SUNRISE, 1_727_001_844),
+    (40.7128, -74.006, 19_988, SunEvent::SOLAR_NOON, 1_727_023_709),
+    (40.7128, -74.006, 19_988, SunEvent::SUNSET, 1_727_045_533),
+    (40.7128, -74.006, 19_988, SunEvent::DUSK, 1_727_047_170),
+    (40.7128, -74.006, 20_078, SunEvent::DAWN, 1_734_781_548),
+    (40.7128, -74.006, 20_078, SunEvent::SUNRISE, 1_734_783_408),
+    (40.7128, -74.006, 20_078, SunEvent::SOLAR_NOON, 1_734_800_065),
+    (40.7128, -74.006, 20_078, SunEvent::SUNSET, 1_734_816_722),
+    (40.7128, -74.006, 20_078, SunEvent::DUSK, 1_734_818_583),
+    // Sydney
+    (-33.8688, 151.2093, 19_802, SunEvent::DAWN, 1_710_876_805),
+    (-33.8688, 151.2093, 19_802, SunEvent::SUNRISE, 1_710_878_300),
+    (-33.8688, 151.2093, 19_802, SunEvent::SOLAR_NOON, 1_710_900_156),
+    (-33.8688, 151.2093, 19_802, SunEvent::SUNSET, 1_710_921_979),
+    (-33.8688, 151.2093, 19_802, SunEvent::DUSK, 1_710_923_472),
+    (-33.8688, 151.2093, 19_894, SunEvent::DAWN, 1_718_829_126),
+    (-33.8688, 151.2093, 19_894, SunEvent::SUNRISE, 1_718_830_790),
+    (-33.8688, 151.2093, 19_894, SunEvent::SOLAR_NOON, 1_718_848_607),
+    (-33.8688, 151.2093, 19_894, SunEvent::SUNSET, 1_718_866_423),
+    (-33.8688, 151.2093, 19_894, SunEvent::DUSK, 1_718_868_087),
+    (-33.8688, 151.2093, 19_988, SunEvent::DAWN, 1_726_946_386),
+    (-33.8688, 151.2093, 19_988, SunEvent::SUNRISE, 1_726_947_879),
+    (-33.8688, 151.2093, 19_988, SunEvent::SOLAR_NOON, 1_726_969_670),
+    (-33.8688, 151.2093, 19_988, SunEvent::SUNSET, 1_726_991_493),
+    (-33.8688, 151.2093, 19_988, SunEvent::DUSK, 1_726_992_989),
+    (-33.8688, 151.2093, 20_078, SunEvent::DAWN, 1_734_718_301),
+    (-33.8688, 151.2093, 20_078, SunEvent::SUNRISE, 1_734_720_051),
+    (-33.8688, 151.2093, 20_078, SunEvent::SOLAR_NOON, 1_734_745_995),
+    (-33.8688, 151.2093, 20_078, SunEvent::SUNSET, 1_734_771_939),
+    (-33.8688, 151.2093, 20_078, SunEvent::DUSK, 1_734_773_689),
+    // Reykjavik
+    (64.1466, -21.9426, 19_802, SunEvent::DAWN, 1_710_916_750),
+    (64.1466, -21.9426, 19_802, SunEvent::SUNRISE, 1_710_919_614),
+    (64.1466, -21.9426, 19_802, SunEvent::SOLAR_NOON, 1_710_941_704),
+    (64.1466, -21.9426, 19_802, SunEvent::SUNSET, 1_710_963_894),
+    (64.1466, -21.9426, 19_802, SunEvent::DUSK, 1_710_966_774),
+    (64.1466, -21.9426, 19_894, SunEvent::SUNRISE, 1_718_852_104),
+    (64.1466, -21.9426, 19_894, SunEvent::SOLAR_NOON, 1_718_890_169),
+    (64.1466, -21.9426, 19_894, SunEvent::SUNSET, 1_718_928_238),
+    (64.1466, -21.9426, 19_988, SunEvent::DAWN, 1_726_986_242),
+    (64.1466, -21.9426, 19_988, SunEvent::SUNRISE, 1_726_989_119),
+    (64.1466, -21.9426, 19_988, SunEvent::SOLAR_NOON, 1_727_011_217),
+    (64.1466, -21.9426, 19_988, SunEvent::SUNSET, 1_727_033_216),
+    (64.1466, -21.9426, 19_988, SunEvent::DUSK, 1_727_036_077),
+    (64.1466, -21.9426, 20_078, SunEvent::DAWN, 1_734_775_394),
+    (64.1466, -21.9426, 20_078, SunEvent::SUNRISE, 1_734_780_154),
+    (64.1466, -21.9426, 20_078, SunEvent::SOLAR_NOON, 1_734_787_565),
+    (64.1466, -21.9426, 20_078, SunEvent::SUNSET, 1_734_794_977),
+    (64.1466, -21.9426, 20_078, SunEvent::DUSK, 1_734_799_737),
+    // Quito
+    (-0.1807, -78.4678, 19_802, SunEvent::DAWN, 1_710_932_232),
+    (-0.1807, -78.4678, 19_802, SunEvent::SUNRISE, 1_710_933_471),
+    (-0.1807, -78.4678, 19_802, SunEvent::SOLAR_NOON, 1_710_955_267),
+    (-0.1807, -78.4678, 19_802, SunEvent::SUNSET, 1_710_977_062),
+    (-0.1807, -78.4678, 19_802, SunEvent::DUSK, 1_710_978_302),
+    (-0.1807, -78.4678, 19_894, SunEvent::DAWN, 1_718_880_583),
+    (-0.1807, -78.4678, 19_894, SunEvent::SUNRISE, 1_718_881_935),
+    (-0.1807, -78.4678, 19_894, SunEvent::SOLAR_NOON, 1_718_903_737),
+    (-0.1807, -78.4678, 19_894, SunEvent::SUNSET, 1_718_925_540),
+    (-0.1807, -78.4678, 19_894, SunEvent::DUSK, 1_718_926_892),
+    (-0.1807, -78.4678, 19_988, SunEvent::DAWN, 1_727_001_745),
+    (-0.1807, -78.4678, 19_988, SunEvent::SUNRISE, 1_727_002_985),
+    (-0.1807, -78.4678, 19_988, SunEvent::SOLAR_NOON, 1_727_024_779),
+    (-0.1807, -78.4678, 19_988, SunEvent::SUNSET, 1_727_046_574),
+    (-0.1807, -78.4678, 19_988, SunEvent::DUSK, 1_727_047_814),
+    (-0.1807, -78.4678, 20_078, SunEvent::DAWN, 1_734_777_939),
+    (-0.1807, -78.4678, 20_078, SunEvent::SUNRISE, 1_734_779_292),
+    (-0.1807, -78.4678, 20_078, SunEvent::SOLAR_NOON, 1_734_801_136),
+    (-0.1807, -78.4678, 20_078, SunEvent::SUNSET, 1_734_822_981),
+    (-0.1807, -78.4678, 20_078, SunEvent::DUSK, 1_734_824_333),
+    // Tokyo
+    (35.6762, 139.6503, 19_802, SunEvent::DAWN, 1_710_879_579),
+    (35.6762, 139.6503, 19_802, SunEvent::SUNRISE, 1_710_881_106),
+    (35.6762, 139.6503, 19_802, SunEvent::SOLAR_NOON, 1_710_902_929),
+    (35.6762, 139.6503, 19_802, SunEvent::SUNSET, 1_710_924_787),
+    (35.6762, 139.6503, 19_802, SunEvent::DUSK, 1_710_926_317),
+    (35.6762, 139.6503, 19_894, SunEvent::DAWN, 1_718_823_336),
+    (35.6762, 139.6503, 19_894, SunEvent::SUNRISE, 1_718_825_142),
+    (35.6762, 139.6503, 19_894, SunEvent::SOLAR_NOON, 1_718_851_381),
+    (35.6762, 139.6503, 19_894, SunEvent::SUNSET, 1_718_877_621),
+    (35.6762, 139.6503, 19_894, SunEvent::DUSK, 1_718_879_427),
+    (35.6762, 139.6503, 19_988, SunEvent::DAWN, 1_726_949_028),
+    (35.6762, 139.6503, 19_988, SunEvent::SUNRISE, 1_726_950_558),
+    (35.6762, 139.6503, 19_988, SunEvent::SOLAR_NOON, 1_726_972_444),
+    (35.6762, 139.6503, 19_988, SunEvent::SUNSET, 1_726_994_296),
+    (35.6762, 139.6503, 19_988, SunEvent::DUSK, 1_726_995_823),
+    (35.6762, 139.6503, 20_078, SunEvent::DAWN, 1_734_729_526),
+    (35.6762, 139.6503, 20_078, SunEvent::SUNRISE, 1_734_731_234),
+    (35.6762, 139.6503, 20_078, SunEvent::SOLAR_NOON, 1_734_748_770),
+    (35.6762, 139.6503, 20_078, SunEvent::SUNSET, 1_734_766_305),
+    (35.6762, 139.6503, 20_078, SunEvent::DUSK, 1_734_768_013)
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Usno;
+    use crate::meeus::Meeus;
+
+    #[test]
+    fn algorithms_should_stay_close_to_the_references() {
+        let references = references();
+        let usno = verify(&Usno::default(), &references);
+        assert_eq!(usno.compared, references.len());
+        assert!(usno.missing.is_empty());
+        assert!(usno.max_error < 120, "{}", usno);
+        let meeus = verify(&Meeus::default(), &references);
+        assert!(meeus.max_error <= 10, "{}", meeus);
+        assert!(meeus.mean_error < usno.mean_error);
+    }
+
+}
@@ -0,0 +1,170 @@
+//! Bindings for JavaScript through `wasm-bindgen`, so that web pages
+//! can compute the sun events client-side.
+//!
+//! Times cross the boundary as milliseconds since the Unix epoch, as
+//! taken and returned by `Date.prototype.getTime`, and events as their
+//! names, such as "civil sunrise" or "solar noon".
+
+use chrono::{ DateTime, TimeZone, Utc };
+use wasm_bindgen::prelude::*;
+use crate::epoch::time_of_event_unix;
+use crate::event::SunEvent;
+use crate::iter::SunEvents;
+use crate::pos::GlobalPosition;
+
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+/// A [GlobalPosition], exported to JavaScript under that name.
+#[wasm_bindgen(js_name = GlobalPosition)]
+#[derive(Debug, Clone)]
+pub struct Position(GlobalPosition);
+
+#[wasm_bindgen(js_class = GlobalPosition)]
+impl Position {
+
+    /// Creates the position at the latitude `lat` and the longitude
+    /// `lng`, in degrees, throwing when it doesn't lie on the globe.
+    #[wasm_bindgen(constructor)]
+    pub fn new(lat: f64, lng: f64) -> Result<Position, JsError> {
+        Ok(Position(GlobalPosition::try_at(lat, lng)?))
+    }
+
+    /// Parses a position written in decimal degrees or in degrees,
+    /// minutes and seconds, such as "51°28'52\"N 0°0'29\"E".
+    pub fn parse(text: &str) -> Result<Position, JsError> {
+        Ok(Position(text.parse()?))
+    }
+
+    /// The same position, `meters` above sea level.
+    #[wasm_bindgen(js_name = withElevation)]
+    pub fn with_elevation(&self, meters: f64) -> Position {
+        Position(self.0.clone().with_elevation(meters))
+    }
+
+    /// The latitude, in degrees.
+    #[wasm_bindgen(getter)]
+    pub fn lat(&self) -> f64 {
+        self.0.lat()
+    }
+
+    /// The longitude, in degrees.
+    #[wasm_bindgen(getter)]
+    pub fn lng(&self) -> f64 {
+        self.0.lng()
+    }
+
+    /// Formats the position in decimal degrees.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+
+}
+
+/// Calculates the time of the event named `event` on the UTC day of
+/// the time `date` (in milliseconds) at `pos`, as the root
+/// `time_of_event` does.
+///
+/// Returns the time in milliseconds, or `undefined` if the sun never
+/// sets/rises on that day (ie if you're in the arctic).
+#[wasm_bindgen(js_name = timeOfEvent)]
+pub fn time_of_event(date: f64, pos: &Position, event: &str) -> Result<Option<f64>, JsError> {
+    let event: SunEvent = event.parse()?;
+    let day = (date / MILLIS_PER_DAY).floor() as i64;
+    Ok(time_of_event_unix(day, &pos.0, event).map(|secs| secs as f64 * 1000.0))
+}
+
+/// A sun event and the time it occurs at, in milliseconds.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    /// The name of the event, such as "civil sunrise".
+    pub event: String,
+    /// The time of the event, in milliseconds since the Unix epoch.
+    pub time: f64
+}
+
+/// Pages through the sun events following or preceding a time, as the
+/// iterators of [SunEvents] do.
+#[wasm_bindgen]
+pub struct EventPager {
+    events: Box<dyn Iterator<Item = (SunEvent, DateTime<Utc>)>>
+}
+
+#[wasm_bindgen]
+impl EventPager {
+
+    /// Lists the events named in `events` occurring after the time
+    /// `start` (in milliseconds) at `pos`.
+    pub fn forecast(start: f64, pos: &Position, events: Vec<String>) -> Result<EventPager, JsError> {
+        let events = sun_events(start, pos, &events)?.forecast();
+        Ok(EventPager { events: Box::new(events) })
+    }
+
+    /// Lists the events named in `events` occurring before the time
+    /// `start` (in milliseconds) at `pos`, latest first.
+    pub fn history(start: f64, pos: &Position, events: Vec<String>) -> Result<EventPager, JsError> {
+        let events = sun_events(start, pos, &events)?.history();
+        Ok(EventPager { events: Box::new(events) })
+    }
+
+    /// Returns the next `count` events.
+    #[wasm_bindgen(js_name = nextPage)]
+    pub fn next_page(&mut self, count: usize) -> Vec<Occurrence> {
+        self.events.by_ref()
+            .take(count)
+            .map(|(event, time)| Occurrence { event: event.to_string(), time: time.timestamp_millis() as f64 })
+            .collect()
+    }
+
+}
+
+fn sun_events(start: f64, pos: &Position, events: &[String]) -> Result<SunEvents, JsError> {
+    let start = Utc.timestamp_millis_opt(start as i64)
+        .single()
+        .ok_or_else(|| JsError::new("the start time should be representable"))?;
+    let events = events.iter()
+        .map(|event| event.parse())
+        .collect::<Result<Vec<SunEvent>, _>>()?;
+    Ok(SunEvents::try_starting_from(start, pos.0.clone(), &events)?)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn events_should_be_timed_in_milliseconds() {
+        let pos = Position::new(51.4810066, 0.0081805).unwrap();
+        // 2024-06-21T15:00:00Z
+        let time = time_of_event(1_718_982_000_000.0, &pos, "sunrise").unwrap().unwrap();
+        let day = Utc.with_ymd_and_hms(2024, 6, 21, 0, 0, 0).unwrap().timestamp_millis() as f64;
+        assert_eq!(Some(time), time_of_event_unix(19_895, &pos.0, SunEvent::SUNRISE).map(|secs| secs as f64 * 1000.0));
+        assert!(time > day && time < day + MILLIS_PER_DAY / 4.0, "{}", time);
+        let arctic = Position::new(78.2232, 15.6267).unwrap();
+        assert_eq!(time_of_event(1_718_982_000_000.0, &arctic, "sunset").unwrap(), None);
+    }
+
+    #[test]
+    fn pages_should_continue_where_the_last_ended() {
+        let pos = Position::new(-33.8688, 151.2093).unwrap();
+        let events = vec!["sunrise".to_string(), "sunset".to_string()];
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().timestamp_millis() as f64;
+        let mut pager = EventPager::forecast(start, &pos, events.clone()).unwrap();
+        let mut pages = pager.next_page(3);
+        pages.extend(pager.next_page(3));
+        let expected: Vec<_> = SunEvents::starting_from(
+            Utc.timestamp_millis_opt(start as i64).unwrap(),
+            pos.0.clone(),
+            &[SunEvent::SUNRISE, SunEvent::SUNSET]
+        ).forecast().take(6).collect();
+        assert_eq!(pages.len(), 6);
+        for (page, (event, time)) in pages.iter().zip(expected) {
+            assert_eq!((page.event.as_str(), page.time), (event.to_string().as_str(), time.timestamp_millis() as f64));
+        }
+        let history = EventPager::history(start, &pos, events).unwrap().next_page(2);
+        assert!(history[0].time < start && history[1].time < history[0].time);
+    }
+
+}